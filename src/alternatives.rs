@@ -0,0 +1,139 @@
+use crate::data::{self, ScheduleDiff, SchedulingInput, SchedulingOutput, SolveMethod};
+use crate::solver::{SolveError, SolverSession};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// Most schedules one request may ask for; every one is a full solve.
+const MAX_ALTERNATIVES: usize = 20;
+
+/// An input and how many different schedules to find for it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlternativesRequest {
+    pub input: SchedulingInput,
+    /// Number of schedules, including the first, best one.
+    #[serde(default = "default_count")]
+    pub count: usize,
+    /// Perturb the objective before every solve after the first, so the alternatives differ
+    /// in more than the one course the no-good cut forces to move.
+    #[serde(default)]
+    pub diversify: bool,
+    /// Seed of the perturbation; the same seed gives the same alternatives. 0 when omitted.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_count() -> usize {
+    3
+}
+
+/// One of the schedules found for an [`AlternativesRequest`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alternative {
+    pub solution: SchedulingOutput,
+    /// How this schedule differs from the first one.
+    pub diff: ScheduleDiff,
+}
+
+/// Solves `request.input` up to `request.count` times, ruling out every schedule found so
+/// far with a no-good cut before the next solve. Stops early when a later solve finds no
+/// further schedule or fails. The input is solved as a whole, even with `decompose` set.
+///
+/// With `diversify`, solve `i` (counting from 1 after the first) adds a random reward below
+/// 1 in total to the objective, drawn from `seed + i`, so ties are settled differently each
+/// time. Reported objective values leave the reward out.
+pub fn alternatives(request: &AlternativesRequest) -> Result<Vec<Alternative>, SolveError> {
+    if request.count == 0 || request.count > MAX_ALTERNATIVES {
+        return Err(SolveError::InvalidInput(format!(
+            "count must be between 1 and {}, got {}.",
+            MAX_ALTERNATIVES, request.count
+        )));
+    }
+    let weights = request.input.weights.clone().unwrap_or_default();
+    let mut session = SolverSession::new(&request.input)?;
+    let first = session.resolve_with_weights(weights.clone())?;
+    let mut alternatives = vec![Alternative {
+        diff: data::diff_schedules(&first.assignments, &first.assignments),
+        solution: first.clone(),
+    }];
+    let seed = request.seed.unwrap_or(0);
+    for index in 1..request.count {
+        if !session.exclude_last_solution() {
+            break;
+        }
+        if request.diversify {
+            session.perturb_objective(seed.wrapping_add(index as u64));
+        }
+        // running out of schedules, or of time, still leaves the ones found so far
+        let solution = match session.resolve_with_weights(weights.clone()) {
+            Ok(solution) => solution,
+            Err(e) => {
+                info!("Stopping after {} alternative(s): {}", alternatives.len(), e);
+                break;
+            }
+        };
+        // the heuristic knows nothing of the cuts, so it would only repeat a schedule
+        if solution.solve_status.method == SolveMethod::Heuristic {
+            break;
+        }
+        alternatives.push(Alternative {
+            diff: data::diff_schedules(&first.assignments, &solution.assignments),
+            solution,
+        });
+    }
+    Ok(alternatives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Four single-slot courses with no preferences at all, so every schedule ties.
+    fn request(diversify: bool) -> AlternativesRequest {
+        let courses: Vec<_> = (1..=4)
+            .map(|id| {
+                json!({
+                    "id": id, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [id]
+                })
+            })
+            .collect();
+        let instructors: Vec<_> =
+            (1..=4).map(|id| json!({"id": id, "unavailableSlots": []})).collect();
+        serde_json::from_value(json!({
+            "input": {
+                "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+                "instructors": instructors,
+                "courses": courses,
+                "totalTimeslots": 4,
+                "morningCutoff": 0
+            },
+            "count": 3,
+            "diversify": diversify,
+            "seed": 7
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn diversified_alternatives_differ_in_more_than_one_course() {
+        // the no-good cut alone is satisfied by moving a single course
+        let plain = alternatives(&request(false)).unwrap();
+        let moved: Vec<_> = plain.iter().map(|a| a.diff.moved.len()).collect();
+        assert_eq!(moved, [0, 1, 1]);
+
+        let diversified = alternatives(&request(true)).unwrap();
+        assert_eq!(diversified.len(), 3);
+        assert_eq!(diversified[0].diff.unchanged, 4);
+        for alternative in &diversified[1..] {
+            let diff = &alternative.diff;
+            assert!(diff.moved.len() > 1, "{:?}", diff);
+        }
+        // the same seed gives the same schedules
+        let again = alternatives(&request(true)).unwrap();
+        for (first, second) in diversified.iter().zip(&again) {
+            assert_eq!(first.solution.assignments, second.solution.assignments);
+        }
+    }
+}
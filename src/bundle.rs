@@ -0,0 +1,61 @@
+use crate::data::{SchedulingInput, SchedulingOutput};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+struct BundleRef<'a> {
+    input: &'a SchedulingInput,
+    output: &'a SchedulingOutput,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(not(test), allow(dead_code))]
+struct Bundle {
+    input: SchedulingInput,
+    output: SchedulingOutput,
+}
+
+/// An input and the output solved from it as one indented JSON document, e.g. to attach to
+/// a bug report.
+pub fn to_bundle(input: &SchedulingInput, output: &SchedulingOutput) -> String {
+    serde_json::to_string_pretty(&BundleRef { input, output })
+        .expect("inputs and outputs always serialize")
+}
+
+/// Reads a document written by [`to_bundle`]. No endpoint takes bundles back yet, so outside
+/// the tests nothing calls this.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn from_bundle(bundle: &str) -> Result<(SchedulingInput, SchedulingOutput), String> {
+    let bundle: Bundle =
+        serde_json::from_str(bundle).map_err(|e| format!("Invalid bundle: {}", e))?;
+    Ok((bundle.input, bundle.output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data, solver};
+    use serde_json::json;
+
+    #[test]
+    fn a_bundle_round_trips_its_input_and_output() {
+        let input: SchedulingInput = serde_json::from_value(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": [0]}],
+            "courses": [
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 2, "instructorIds": [1]},
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]}
+            ],
+            "totalTimeslots": 6
+        }))
+        .unwrap();
+        let output = solver::solve(&input).unwrap();
+        let bundle = to_bundle(&input, &output);
+
+        let (read_input, read_output) = from_bundle(&bundle).unwrap();
+        assert_eq!(to_bundle(&read_input, &read_output), bundle);
+        assert_eq!(read_output.assignments, output.assignments);
+        assert_eq!(data::input_fingerprint(&read_input), data::input_fingerprint(&input));
+
+        assert!(from_bundle(r#"{"input": {}}"#).unwrap_err().starts_with("Invalid bundle"));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::data::{DateRange, SchedulingInput, SlotDate, Timeslot};
+use jiff::civil::DateTime;
+use jiff::tz::TimeZone;
+
+/// Slots of `calendar` that start within `range`, in ascending order.
+pub fn slots_in_range(calendar: &[SlotDate], range: &DateRange) -> Vec<Timeslot> {
+    let mut slots: Vec<Timeslot> = calendar
+        .iter()
+        .filter(|slot| range.start <= slot.start && slot.start < range.end)
+        .map(|slot| slot.slot)
+        .collect();
+    slots.sort_unstable();
+    slots.dedup();
+    slots
+}
+
+/// Copy of the input with every `unavailable_dates` range of an instructor or room added to
+/// its `unavailable_slots` through `slot_calendar`. The ranges are cleared afterwards, so
+/// converting an input twice changes nothing.
+pub fn with_dates_as_slots(input: &SchedulingInput) -> SchedulingInput {
+    let mut input = input.clone();
+    let calendar = std::mem::take(&mut input.slot_calendar);
+    for instructor in &mut input.instructors {
+        for range in std::mem::take(&mut instructor.unavailable_dates) {
+            instructor.unavailable_slots.extend(slots_in_range(&calendar, &range));
+        }
+    }
+    for room in &mut input.rooms {
+        for range in std::mem::take(&mut room.unavailable_dates) {
+            room.unavailable_slots.extend(slots_in_range(&calendar, &range));
+        }
+    }
+    input.slot_calendar = calendar;
+    input
+}
+
+/// Zone the slot calendar of `input` is local to; UTC when `time_zone` is omitted or unknown.
+pub fn input_time_zone(input: &SchedulingInput) -> TimeZone {
+    input
+        .time_zone
+        .as_deref()
+        .and_then(|name| TimeZone::get(name).ok())
+        .unwrap_or(TimeZone::UTC)
+}
+
+/// `time`, local to `from`, as the local time in `to` with its UTC offset, e.g.
+/// "2025-03-03T04:00:00-05:00". Times that fall into a daylight saving gap move forward.
+pub fn convert(time: DateTime, from: &TimeZone, to: &TimeZone) -> Option<String> {
+    let zoned = time.to_zoned(from.clone()).ok()?.with_time_zone(to.clone());
+    Some(zoned.strftime("%Y-%m-%dT%H:%M:%S%:z").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Three days from Monday 2025-03-03, with classes at 09:00 and 14:00.
+    fn input() -> SchedulingInput {
+        let calendar: Vec<_> = (0..6)
+            .map(|slot| {
+                let day = 3 + slot / 2;
+                let hour = if slot % 2 == 0 { 9 } else { 14 };
+                json!({"slot": slot, "start": format!("2025-03-{:02}T{:02}:00", day, hour)})
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "rooms": [{
+                "id": 1, "capacity": 30,
+                "unavailableDates": [{"start": "2025-03-05T12:00", "end": "2025-03-06"}]
+            }],
+            "instructors": [{
+                "id": 1, "unavailableSlots": [0],
+                "unavailableDates": [{"start": "2025-03-04", "end": "2025-03-05"}]
+            }],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 6,
+            "slotCalendar": calendar
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn a_date_range_blocks_the_slots_that_start_in_it() {
+        let converted = with_dates_as_slots(&input());
+        assert_eq!(converted.instructors[0].unavailable_slots, [0, 2, 3]);
+        assert!(converted.instructors[0].unavailable_dates.is_empty());
+        // the range starts at noon, so only the afternoon class of that day is blocked
+        assert_eq!(converted.rooms[0].unavailable_slots, [5]);
+
+        let again = with_dates_as_slots(&converted);
+        assert_eq!(again.instructors[0].unavailable_slots, [0, 2, 3]);
+        assert_eq!(again.rooms[0].unavailable_slots, [5]);
+    }
+
+    #[test]
+    fn a_utc_slot_time_is_converted_into_the_requested_zone() {
+        let new_york = TimeZone::get("America/New_York").unwrap();
+        let winter: DateTime = "2025-03-03T09:00".parse().unwrap();
+        let summer: DateTime = "2025-07-01T09:00".parse().unwrap();
+        let converted = |time| convert(time, &TimeZone::UTC, &new_york).unwrap();
+        assert_eq!(converted(winter), "2025-03-03T04:00:00-05:00");
+        assert_eq!(converted(summer), "2025-07-01T05:00:00-04:00");
+
+        let mut input = input();
+        assert_eq!(input_time_zone(&input), TimeZone::UTC);
+        input.time_zone = Some("Europe/Berlin".to_string());
+        let berlin = input_time_zone(&input);
+        assert_eq!(convert(winter, &berlin, &TimeZone::UTC).unwrap(), "2025-03-03T08:00:00+00:00");
+    }
+}
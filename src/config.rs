@@ -0,0 +1,108 @@
+use crate::data::{SchedulingInput, SolverWeights};
+use serde::Deserialize;
+
+/// Path of an optional TOML file with server-wide defaults.
+pub const CONFIG_PATH_ENV: &str = "SCHEDULE_SOLVER_CONFIG";
+
+/// Operational defaults applied to every request that leaves the field out. Keys are the
+/// same as in the JSON requests, e.g.
+///
+/// ```toml
+/// morningCutoff = 4
+/// timeLimitSeconds = 30.0
+/// maxVariables = 200000
+///
+/// [weights]
+/// backToBackPenalty = 2.0
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Used as a whole when a request has no `weights`; fields left out here keep their
+    /// built-in defaults.
+    pub weights: Option<SolverWeights>,
+    pub morning_cutoff: Option<u32>,
+    pub time_limit_seconds: Option<f64>,
+    pub max_variables: Option<usize>,
+}
+
+impl ServerConfig {
+    /// Reads the file named by `SCHEDULE_SOLVER_CONFIG`, or returns empty defaults when the
+    /// variable is unset.
+    pub fn from_env() -> Result<Self, String> {
+        let Ok(path) = std::env::var(CONFIG_PATH_ENV) else {
+            return Ok(ServerConfig::default());
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read config file {}: {}", path, e))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid config file {}: {}", path, e))
+    }
+
+    /// Fills in every field the request left out and expands `course_demands`, so every
+    /// handler sees the courses the schedule refers to. Demands whose sections can't be
+    /// numbered are left for validation to report.
+    pub fn apply_defaults(&self, input: SchedulingInput) -> SchedulingInput {
+        let mut input = input.with_sections_expanded().unwrap_or(input);
+        if input.weights.is_none() {
+            input.weights = self.weights.clone();
+        }
+        input.morning_cutoff = input.morning_cutoff.or(self.morning_cutoff);
+        input.time_limit_seconds = input.time_limit_seconds.or(self.time_limit_seconds);
+        input.max_variables = input.max_variables.or(self.max_variables);
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FILE: &str = "
+morningCutoff = 4
+timeLimitSeconds = 30.0
+
+[weights]
+backToBackPenalty = 2.0
+";
+
+    fn request(extra: serde_json::Value) -> SchedulingInput {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 8
+        });
+        for (key, field) in extra.as_object().unwrap() {
+            value[key] = field.clone();
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn a_request_without_weights_picks_up_the_file_defaults() {
+        let config: ServerConfig = toml::from_str(FILE).unwrap();
+        let input = config.apply_defaults(request(json!({})));
+        let weights = input.weights.unwrap();
+        assert_eq!(weights.back_to_back_penalty, 2.0);
+        // keys the file leaves out keep their built-in defaults
+        assert_eq!(weights.optional_course, SolverWeights::default().optional_course);
+        assert_eq!(input.morning_cutoff, Some(4));
+        assert_eq!(input.time_limit_seconds, Some(30.0));
+
+        let input = config.apply_defaults(request(json!({
+            "weights": {"morningPreference": 3.0},
+            "morningCutoff": 2
+        })));
+        let weights = input.weights.unwrap();
+        assert_eq!((weights.morning_preference, weights.back_to_back_penalty), (3.0, 1.0));
+        assert_eq!(input.morning_cutoff, Some(2));
+    }
+
+    #[test]
+    fn unknown_keys_in_the_file_are_rejected() {
+        assert!(toml::from_str::<ServerConfig>("morningCutof = 4").is_err());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::data::{ConstraintDescription, ConstraintSummary, ObjectiveMode, SchedulingInput};
+
+/// Lists the constraints the solver will enforce for `input`, judged from the input alone.
+///
+/// Names of soft constraints match the keys of `objectiveBreakdown`.
+pub fn describe_constraints(input: &SchedulingInput) -> ConstraintSummary {
+    let input = &input.with_sections_expanded().unwrap_or_else(|_| input.clone());
+    let mut summary = ConstraintSummary::default();
+    let weights = input.weights.clone().unwrap_or_default();
+    let mut hard = |name, description: &str| {
+        summary.hard.push(ConstraintDescription {
+            name,
+            description: description.to_string(),
+            weight: None,
+        })
+    };
+
+    let optional = input.courses.iter().filter(|c| c.optional).count();
+    if optional == 0 {
+        hard("scheduledOnce", "Every course is scheduled exactly once.");
+    } else {
+        hard(
+            "scheduledOnce",
+            &format!(
+                "Every course is scheduled exactly once, except {} optional course(s), which are scheduled at most once.",
+                optional
+            ),
+        );
+    }
+    let relaxations = &input.relaxations;
+    if relaxations.room_overlap.is_none() {
+        hard("noRoomOverlap", "A room holds at most one course at a time.");
+    }
+    if relaxations.instructor_overlap.is_none() {
+        hard("noInstructorOverlap", "An instructor teaches at most one course at a time.");
+    }
+    let flexible = input.courses.iter().filter(|c| c.eligible_instructors.is_some()).count();
+    if flexible > 0 {
+        hard(
+            "eligibleInstructors",
+            &format!(
+                "{} course(s) with eligibleInstructors are taught by exactly one of them.",
+                flexible
+            ),
+        );
+    }
+    if relaxations.capacity.is_none() {
+        hard("roomCapacity", "A course only uses rooms with at least its required capacity.");
+    }
+    if !input.combinable.is_empty() {
+        hard(
+            "combinedRooms",
+            &format!(
+                "{} combinable room set(s) may hold one course with their summed capacity, blocking each of their rooms meanwhile.",
+                input.combinable.len()
+            ),
+        );
+    }
+    hard(
+        "withinDay",
+        &format!(
+            "A course fits within one day of {} period(s) and within the {} timeslot(s).",
+            input.slots_per_day(),
+            input.total_timeslots
+        ),
+    );
+    if !input.allow_override_unavailability {
+        hard(
+            "instructorAvailability",
+            "A course never runs in a slot one of its instructors is unavailable in.",
+        );
+    }
+    if input.rooms.iter().any(|r| {
+        !r.recurring_unavailable.is_empty()
+            || !r.unavailable_slots.is_empty()
+            || !r.unavailable_dates.is_empty()
+    }) {
+        hard("roomAvailability", "A course never uses a room during its blocks.");
+    }
+    if !input.maintenance_windows.is_empty() {
+        let reasons: Vec<&str> =
+            input.maintenance_windows.iter().map(|w| w.reason.as_str()).collect();
+        hard(
+            "maintenanceWindows",
+            &format!("No course runs while closed for maintenance ({}).", reasons.join("; ")),
+        );
+    }
+    if input.courses.iter().any(|c| c.required_day.is_some()) {
+        hard("requiredDay", "Courses with a requiredDay run on that day.");
+    }
+    if input.courses.iter().any(|c| c.fixed_room.is_some() || c.fixed_slot.is_some()) {
+        hard(
+            "fixedRoomOrSlot",
+            "Courses with a fixedRoom use that room and courses with a fixedSlot start in that slot.",
+        );
+    }
+    if input.courses.iter().any(|c| !c.no_cross_slots.is_empty()) {
+        hard("noCrossSlots", "Courses do not run through their noCrossSlots.");
+    }
+    if !input.student_groups.is_empty() {
+        hard(
+            "studentGroupOverlap",
+            &format!(
+                "Courses of the same student group never overlap ({} group(s)).",
+                input.student_groups.len()
+            ),
+        );
+    }
+    if input.student_groups.iter().any(|g| g.daily_window.is_some()) {
+        hard("studentGroupWindow", "Courses stay within their student groups' daily windows.");
+    }
+    if !input.tag_conflicts.is_empty() {
+        hard(
+            "tagConflicts",
+            &format!(
+                "Courses sharing one of the tags {:?} never overlap.",
+                input.tag_conflicts
+            ),
+        );
+    }
+    if input.instructors.iter().any(|i| i.max_consecutive_slots.is_some()) {
+        hard(
+            "maxConsecutiveSlots",
+            "Instructors with maxConsecutiveSlots get a break after that many slots.",
+        );
+    }
+    if !input.section_links.is_empty() {
+        hard(
+            "sectionLinks",
+            &format!(
+                "{} lab section(s) are only scheduled with their lecture, in the same week or after it where required.",
+                input.section_links.len()
+            ),
+        );
+    }
+    if !input.distinct_instructor_groups.is_empty() {
+        hard(
+            "distinctInstructors",
+            &format!(
+                "The courses of each of {} distinct instructor group(s) are taught by different instructors.",
+                input.distinct_instructor_groups.len()
+            ),
+        );
+    }
+    if input.instructors.iter().any(|i| i.max_daily_span.is_some()) {
+        hard(
+            "maxDailySpan",
+            "Instructors with maxDailySpan teach within that many slots from their first start to their last end on every day.",
+        );
+    }
+    if input.instructors.iter().any(|i| i.max_slots_per_day.is_some()) {
+        hard(
+            "maxSlotsPerDay",
+            "Instructors with maxSlotsPerDay teach at most that many slots on every day, however many courses they fill.",
+        );
+    }
+    if input.instructors.iter().any(|i| i.earliest_start.is_some() || i.latest_end.is_some()) {
+        hard(
+            "instructorHours",
+            "Instructors with earliestStart or latestEnd teach within those periods of every day, even where unavailable slots may be overridden.",
+        );
+    }
+    if input.instructors.iter().any(|i| i.max_courses.is_some()) {
+        hard("maxCourses", "Instructors with maxCourses teach at most that many courses.");
+    }
+    if let Some(min_courses) = input.min_courses_per_active_slot {
+        hard(
+            "minCoursesPerActiveSlot",
+            &format!("A timeslot in use holds at least {} course(s).", min_courses),
+        );
+    }
+    if input.require_all_rooms_used {
+        hard("allRoomsUsed", "Every room holds at least one course.");
+    }
+    if let Some(min_courses) = input.min_courses_per_active_zone
+        && input.rooms.iter().any(|r| r.zone.is_some())
+    {
+        hard(
+            "minCoursesPerActiveZone",
+            &format!(
+                "A zone in use in a timeslot holds at least {} course(s) in it.",
+                min_courses
+            ),
+        );
+    }
+    if let Some(max_concurrent) = input.max_concurrent_courses {
+        hard(
+            "maxConcurrentCourses",
+            &format!("At most {} course(s) run at the same time across all rooms.", max_concurrent),
+        );
+    }
+    if let Some(max_pairs) = input.max_back_to_back_pairs {
+        hard(
+            "maxBackToBackPairs",
+            &format!(
+                "At most {} pair(s) of back-to-back classes across all instructors.",
+                max_pairs
+            ),
+        );
+    }
+    if let Some(caps) = input.max_courses_in_slot.as_ref().filter(|caps| !caps.is_empty()) {
+        hard(
+            "maxCoursesInSlot",
+            &format!("At most the given number of courses start in {} capped slot(s).", caps.len()),
+        );
+    }
+    if !input.slot_modes.is_empty() {
+        hard(
+            "slotModes",
+            "Exams only run in examOnly slots or slots of any mode, other courses only in classOnly slots or slots of any mode.",
+        );
+    }
+    if !input.frozen_slots.is_empty() {
+        hard(
+            "frozenSlots",
+            &format!(
+                "{} frozen slot(s) keep the frozenAssignments that run within them; no other course uses them.",
+                input.frozen_slots.len()
+            ),
+        );
+    }
+    if input.courses.iter().any(|c| c.is_cancelled()) {
+        hard(
+            "minimumEnrollment",
+            "Courses with fewer enrolledStudents than their minEnrollment are cancelled.",
+        );
+    }
+
+    let mut soft = |name, weight, description: &str| {
+        summary.soft.push(ConstraintDescription {
+            name,
+            description: description.to_string(),
+            weight,
+        })
+    };
+    if input.repair_warm_start {
+        soft(
+            "warmStartChanges",
+            None,
+            "Minimise the number of warmStart assignments that change; replaces the weighted preferences.",
+        );
+    } else if input.objective_mode == ObjectiveMode::MinimizeLatestInstructorSlot {
+        soft(
+            "latestInstructorEnd",
+            None,
+            "Minimise the latest period any instructor teaches in; replaces the weighted preferences.",
+        );
+    } else if input.objective_mode == ObjectiveMode::ExamSpread {
+        soft(
+            "examClustering",
+            None,
+            "Penalise every exam beyond the first a student group has on a day; replaces the weighted preferences and comes before examEarliness.",
+        );
+        soft(
+            "examEarliness",
+            None,
+            "Penalise the start day of every exam by its difficulty, so harder exams come earlier.",
+        );
+    } else {
+        soft(
+            "morningPreference",
+            Some(weights.morning_preference),
+            "Reward courses that start in the morning.",
+        );
+        soft(
+            "backToBackPenalty",
+            Some(weights.back_to_back_penalty),
+            "Penalise back-to-back classes of an instructor, scaled by their backToBackWeight.",
+        );
+        if input.courses.iter().any(|c| c.target_slot.is_some()) {
+            soft(
+                "targetDeviation",
+                None,
+                "Penalise each slot of distance from a course's targetSlot by its targetWeight.",
+            );
+        }
+        if input.instructors.iter().any(|i| !i.preferred_days.is_empty()) {
+            soft(
+                "preferredDay",
+                Some(weights.preferred_day),
+                "Reward courses on their instructors' preferred days.",
+            );
+        }
+        if input.instructors.iter().any(|i| i.avoid_single_course_days) {
+            soft(
+                "singleCourseDays",
+                Some(weights.single_course_day),
+                "Penalise days on which an instructor with avoidSingleCourseDays teaches exactly one course.",
+            );
+        }
+        if input.instructors.iter().any(|i| i.home_room.is_some()) {
+            soft(
+                "homeRoom",
+                Some(weights.home_room),
+                "Reward courses in their instructors' home rooms.",
+            );
+        }
+        if input.courses.iter().any(|c| c.department_id.is_some()) {
+            soft(
+                "departmentCompactness",
+                Some(weights.department_compactness),
+                "Penalise the span of slots each department's courses cover.",
+            );
+        }
+        if !input.soft_adjacency.is_empty() {
+            soft(
+                "adjacency",
+                Some(weights.adjacency),
+                "Reward requested course pairs that run back-to-back.",
+            );
+        }
+        if input.day_weights.is_some() {
+            soft("dayWeights", None, "Reward or penalise courses by the weight of their day.");
+        }
+        if !input.day_soft_caps.is_empty() {
+            soft(
+                "daySoftCaps",
+                None,
+                "Penalise every course starting on a day beyond its soft cap by the cap's weight.",
+            );
+        }
+        if input.rooms.iter().any(|r| r.reserve_preference > 0.0) {
+            soft(
+                "reservePreference",
+                None,
+                "Penalise courses in rooms held in reserve by the room's reservePreference.",
+            );
+        }
+        if weights.rightsizing != 0.0 {
+            soft(
+                "rightsizing",
+                Some(weights.rightsizing),
+                "Penalise every seat of unused room capacity.",
+            );
+        }
+        if weights.compact_schedule != 0.0 {
+            soft(
+                "compactSchedule",
+                Some(weights.compact_schedule),
+                "Penalise the latest end slot of the schedule.",
+            );
+        }
+        if weights.isolation_weight != 0.0 {
+            soft(
+                "isolatedSlots",
+                Some(weights.isolation_weight),
+                "Penalise every timeslot that runs exactly one course.",
+            );
+        }
+        if weights.room_clustering_weight != 0.0 && !input.room_distances.is_empty() {
+            soft(
+                "roomClustering",
+                Some(weights.room_clustering_weight),
+                "Penalise the room distance between every two courses of the same instructor.",
+            );
+        }
+        if weights.idle_time_weight != 0.0 {
+            soft(
+                "idleTime",
+                Some(weights.idle_time_weight),
+                "Penalise every idle slot between the first and last class of an instructor's day.",
+            );
+        }
+        if weights.cohort_room_changes_weight != 0.0 && !input.student_groups.is_empty() {
+            soft(
+                "cohortRoomChanges",
+                Some(weights.cohort_room_changes_weight),
+                "Penalise every room beyond the first a student group uses on a day.",
+            );
+        }
+        if weights.long_course_morning_weight != 0.0 {
+            soft(
+                "longCourseMorning",
+                Some(weights.long_course_morning_weight),
+                "Reward courses that start in the morning by their duration in slots times their morning reward.",
+            );
+        }
+        if weights.minimize_instructors_weight != 0.0 {
+            soft(
+                "instructorsUsed",
+                Some(weights.minimize_instructors_weight),
+                "Penalise every instructor who teaches at least one course.",
+            );
+        }
+    }
+    if let Some(penalty) = relaxations.room_overlap {
+        soft(
+            "noRoomOverlap",
+            Some(penalty),
+            "Relaxed: penalise every course beyond the first a room holds in a slot.",
+        );
+    }
+    if let Some(penalty) = relaxations.instructor_overlap {
+        soft(
+            "noInstructorOverlap",
+            Some(penalty),
+            "Relaxed: penalise every course beyond the first an instructor teaches in a slot.",
+        );
+    }
+    if let Some(penalty) = relaxations.capacity {
+        soft(
+            "roomCapacity",
+            Some(penalty),
+            "Relaxed: penalise every course in a room with fewer seats than it requires.",
+        );
+    }
+    if input.allow_override_unavailability {
+        soft(
+            "unavailabilityOverrides",
+            None,
+            "Courses may use unavailable instructor slots at the instructor's overrideCost per slot.",
+        );
+    }
+    if optional > 0 {
+        soft(
+            "optionalCourses",
+            Some(weights.optional_course),
+            "Reward every optional course that is scheduled.",
+        );
+    }
+
+    summary
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn names(constraints: &[ConstraintDescription]) -> Vec<&'static str> {
+        constraints.iter().map(|c| c.name).collect()
+    }
+
+    #[test]
+    fn enabled_features_appear_in_the_list() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 4
+        });
+        let plain: SchedulingInput = serde_json::from_value(value.clone()).unwrap();
+        let plain = describe_constraints(&plain);
+        assert!(!names(&plain.hard).contains(&"tagConflicts"));
+        assert!(!names(&plain.soft).contains(&"homeRoom"));
+
+        value["tagConflicts"] = json!(["lab"]);
+        value["instructors"][0]["homeRoom"] = json!(1);
+        value["weights"] = json!({"homeRoom": 2.5});
+        let input: SchedulingInput = serde_json::from_value(value).unwrap();
+        let summary = describe_constraints(&input);
+        let hard = names(&summary.hard);
+        for name in ["scheduledOnce", "noRoomOverlap", "noInstructorOverlap", "tagConflicts"] {
+            assert!(hard.contains(&name), "{} missing from {:?}", name, hard);
+        }
+        let home_room = summary.soft.iter().find(|c| c.name == "homeRoom").unwrap();
+        assert_eq!(home_room.weight, Some(2.5));
+    }
+}
@@ -1,17 +1,136 @@
+use jiff::civil::DateTime;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
+use std::ops::Range;
 
-// Type aliases for clarity
-pub type RoomId = u32;
-pub type CourseId = u32;
-pub type InstructorId = u32;
+/// Declares an id newtype that serializes as a plain number and prints like one, so ids of
+/// different entities cannot be mixed up.
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(pub u32);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl From<u32> for $name {
+            fn from(id: u32) -> Self {
+                $name(id)
+            }
+        }
+    };
+}
+
+id_type!(
+    /// Id of a [`Room`].
+    RoomId
+);
+id_type!(
+    /// Id of a [`Course`].
+    CourseId
+);
+id_type!(
+    /// Id of an [`Instructor`].
+    InstructorId
+);
+id_type!(
+    /// Id of a [`StudentGroup`].
+    StudentGroupId
+);
 pub type Timeslot = u32;
 
 /// Represents a physical room with a given capacity.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Room {
     pub id: RoomId,
     pub capacity: u32,
+    /// (day, period) pairs the room is blocked on, with the day counted within the week. A
+    /// block on day 0 period 0 recurs as "every Monday, first period" in every week.
+    #[serde(default)]
+    pub recurring_unavailable: Vec<(u32, Timeslot)>,
+    /// Timeslots the room is blocked in once, e.g. for an exam or an event.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_slots: Vec<Timeslot>,
+    /// Calendar ranges the room is blocked in; see `SchedulingInput::slot_calendar`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_dates: Vec<DateRange>,
+    /// Objective penalty per course placed in this room, to keep it free as a buffer.
+    #[serde(default)]
+    pub reserve_preference: f64,
+    /// Building wing or other group of rooms; see `SchedulingInput::min_courses_per_active_zone`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<u32>,
+}
+
+/// Local date and time a timeslot starts at.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotDate {
+    pub slot: Timeslot,
+    /// E.g. "2025-03-03T09:00".
+    pub start: DateTime,
+}
+
+/// Timeslots nothing may be scheduled in, e.g. a campus closure.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindow {
+    pub slots: Vec<Timeslot>,
+    /// Quoted in the unmet soft constraints of courses the window pushed out of a preferred
+    /// slot.
+    pub reason: String,
+}
+
+/// A lecture and the lab section that goes with it. A lab is only scheduled together with
+/// its lecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionLink {
+    pub lecture: CourseId,
+    pub lab: CourseId,
+    /// The lab runs in the same week as the lecture.
+    #[serde(default)]
+    pub same_week: bool,
+    /// The lab starts no earlier than the end of the lecture.
+    #[serde(default)]
+    pub lab_after_lecture: bool,
+}
+
+impl SectionLink {
+    /// Whether a lab starting at `lab_start` may follow a lecture running `lecture_slots`.
+    pub fn allows(
+        &self,
+        input: &SchedulingInput,
+        lecture_slots: Range<Timeslot>,
+        lab_start: Timeslot,
+    ) -> bool {
+        (!self.same_week || input.week_of(lecture_slots.start) == input.week_of(lab_start))
+            && (!self.lab_after_lecture || lab_start >= lecture_slots.end)
+    }
+}
+
+/// A span of local calendar time; `end` is exclusive. A bound without a time means midnight,
+/// so "2025-03-03" to "2025-03-08" covers five whole days.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateRange {
+    pub start: DateTime,
+    pub end: DateTime,
 }
 
 /// Represents a course to be scheduled.
@@ -19,9 +138,146 @@ pub struct Room {
 #[serde(rename_all = "camelCase")]
 pub struct Course {
     pub id: CourseId,
-    pub instructor_id: InstructorId,
+    /// Deprecated: list every instructor in `instructor_ids`. Still honoured alongside it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructor_id: Option<InstructorId>,
+    /// Everyone teaching the course; all of them have to be free while it runs.
+    #[serde(default)]
+    pub instructor_ids: Vec<InstructorId>,
+    /// Instructors qualified to teach the course; the solver picks exactly one of them.
+    /// Replaces `instructor_id` and `instructor_ids` when given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eligible_instructors: Option<Vec<InstructorId>>,
+    /// May be omitted when `duration_minutes` is given.
+    #[serde(default)]
     pub duration_slots: u32,
     pub required_capacity: u32,
+    /// Length in minutes; rounded up to whole slots of `slot_minutes`. Takes precedence over
+    /// `duration_slots`.
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+    /// Slot the course should start near; every slot of distance from it is penalised.
+    #[serde(default)]
+    pub target_slot: Option<Timeslot>,
+    /// Penalty per slot of distance from `target_slot`.
+    #[serde(default = "default_target_weight")]
+    pub target_weight: f64,
+    /// Courses sharing a department are pulled into a compact span of slots.
+    #[serde(default)]
+    pub department_id: Option<u32>,
+    /// Slots the course may start or end on but not run through.
+    #[serde(default)]
+    pub no_cross_slots: Vec<Timeslot>,
+    /// Free-form labels; see `SchedulingInput::tag_conflicts`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Day (0-based) the course has to run on. Any day when omitted.
+    #[serde(default)]
+    pub required_day: Option<u32>,
+    /// Room the course has to use; the solver still picks the slot. Only this room, never a
+    /// combinable set containing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_room: Option<RoomId>,
+    /// Slot the course has to start in; the solver still picks the room.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_slot: Option<Timeslot>,
+    /// Enrollment below which the course is cancelled instead of scheduled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_enrollment: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrolled_students: Option<u32>,
+    /// May be left unscheduled; the objective rewards scheduling it.
+    #[serde(default)]
+    pub optional: bool,
+    /// How demanding the course is as an exam; `ObjectiveMode::ExamSpread` places harder
+    /// exams earlier. Treated as 0 when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u32>,
+    /// Exams only run in slots of mode `ExamOnly` or `Any`, other courses only in `ClassOnly`
+    /// or `Any`; see `SchedulingInput::slot_modes`.
+    #[serde(default)]
+    pub is_exam: bool,
+    /// Display name for calendars; passed through to the assignment, never used in solving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Display colour for calendars, e.g. "#1f77b4"; passed through like `label`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Template id of the `course_demands` entry this course is a section of; set when the
+    /// demand is expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_of: Option<CourseId>,
+}
+
+/// Penalty per violation of each hard constraint that may be broken; `None` keeps it hard.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relaxations {
+    /// Per course beyond the first in a room and slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_overlap: Option<f64>,
+    /// Per course beyond the first an instructor teaches in a slot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructor_overlap: Option<f64>,
+    /// Per course placed in a room with fewer seats than it requires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<f64>,
+}
+
+impl Relaxations {
+    pub fn is_empty(&self) -> bool {
+        self.room_overlap.is_none() && self.instructor_overlap.is_none() && self.capacity.is_none()
+    }
+}
+
+/// A number of identical sections of a course, when only how many are needed is known.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseDemand {
+    /// Every section is a copy of this course; its id only names the demand.
+    pub template: Course,
+    pub sections: u32,
+}
+
+impl Course {
+    /// Every fixed instructor of the course, from both `instructor_id` and `instructor_ids`.
+    /// Empty when the solver picks one from `eligible_instructors` instead.
+    pub fn instructors(&self) -> Vec<InstructorId> {
+        if self.eligible_instructors.is_some() {
+            return Vec::new();
+        }
+        let mut instructors: Vec<InstructorId> =
+            self.instructor_id.into_iter().chain(self.instructor_ids.iter().copied()).collect();
+        instructors.sort_unstable();
+        instructors.dedup();
+        instructors
+    }
+
+    /// Every instructor who may end up teaching the course: the eligible ones when the solver
+    /// picks, otherwise the fixed ones.
+    pub fn possible_instructors(&self) -> Vec<InstructorId> {
+        match &self.eligible_instructors {
+            Some(eligible) => {
+                let mut instructors = eligible.clone();
+                instructors.sort_unstable();
+                instructors.dedup();
+                instructors
+            }
+            None => self.instructors(),
+        }
+    }
+
+    /// Whether the course has fewer students than its minimum enrollment.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(
+            (self.min_enrollment, self.enrolled_students),
+            (Some(min), Some(enrolled)) if enrolled < min
+        )
+    }
+}
+
+fn default_target_weight() -> f64 {
+    1.0
 }
 
 /// Represents an instructor with their scheduling constraints.
@@ -30,6 +286,219 @@ pub struct Course {
 pub struct Instructor {
     pub id: InstructorId,
     pub unavailable_slots: Vec<Timeslot>,
+    /// Calendar ranges the instructor is unavailable in, e.g. a holiday or a conference;
+    /// see `SchedulingInput::slot_calendar`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_dates: Vec<DateRange>,
+    /// Longest run of consecutive slots the instructor may teach before needing a break.
+    #[serde(default)]
+    pub max_consecutive_slots: Option<u32>,
+    /// Most slots from the instructor's first start to their last end on any one day, to
+    /// avoid split shifts with long gaps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_daily_span: Option<u32>,
+    /// Most slots the instructor may teach on any one day, counting slots rather than courses,
+    /// e.g. a contractual limit on daily teaching hours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slots_per_day: Option<u32>,
+    /// First period of each day the instructor may teach in, e.g. nothing before 10am.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub earliest_start: Option<Timeslot>,
+    /// Period of each day by which every class of the instructor has ended, e.g. nothing
+    /// after 4pm. Unlike `unavailable_slots`, neither bound can be overridden.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_end: Option<Timeslot>,
+    /// Penalise days on which the instructor teaches exactly one course, so they come in for
+    /// several classes or none.
+    #[serde(default)]
+    pub avoid_single_course_days: bool,
+    /// Multiplier for the back-to-back penalty. Negative values reward back-to-back classes instead.
+    #[serde(default = "default_back_to_back_weight")]
+    pub back_to_back_weight: f64,
+    /// Days of the week (0-based) the instructor would like to teach on. Empty means no
+    /// preference.
+    #[serde(default)]
+    pub preferred_days: Vec<u32>,
+    /// Objective cost per unavailable slot taught when `allowOverrideUnavailability` is set.
+    /// Defaults to [`DEFAULT_OVERRIDE_COST`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub override_cost: Option<f64>,
+    /// Room the instructor would like to teach in, e.g. the one next to their office.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_room: Option<RoomId>,
+    /// Most courses the instructor may teach, regardless of their length.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_courses: Option<u32>,
+}
+
+impl Instructor {
+    /// Whether a class of `duration` slots from `start_slot` stays between the instructor's
+    /// `earliest_start` and `latest_end` of its day.
+    pub fn within_hours(
+        &self,
+        input: &SchedulingInput,
+        start_slot: Timeslot,
+        duration: u32,
+    ) -> bool {
+        let first_period = input.period_of(start_slot);
+        self.earliest_start.is_none_or(|earliest| first_period >= earliest)
+            && self.latest_end.is_none_or(|latest| first_period + duration <= latest)
+    }
+}
+
+/// Cost per overridden slot for instructors without their own `override_cost`; large enough
+/// to outweigh every soft preference of a single course.
+pub const DEFAULT_OVERRIDE_COST: f64 = 1000.0;
+
+/// How far below 1 a binary variable may end up and still count as chosen, unless the input
+/// sets its own `solution_tolerance`.
+pub const DEFAULT_SOLUTION_TOLERANCE: f64 = 0.1;
+
+fn default_back_to_back_weight() -> f64 {
+    1.0
+}
+
+/// Global weights of the soft constraints in the objective function.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SolverWeights {
+    pub morning_preference: f64,
+    /// Multiplied with each instructor's own back-to-back weight.
+    pub back_to_back_penalty: f64,
+    /// Reward for scheduling a course on one of its instructor's preferred days.
+    pub preferred_day: f64,
+    /// Penalty per slot of span (latest end minus earliest start) of each department.
+    pub department_compactness: f64,
+    /// Reward for every `soft_adjacency` pair that ends up back-to-back.
+    pub adjacency: f64,
+    /// Penalty per seat of unused capacity in the chosen room. Off by default.
+    pub rightsizing: f64,
+    /// Reward for every course an instructor teaches in their home room.
+    pub home_room: f64,
+    /// Reward for every optional course that is scheduled. High by default so optional
+    /// courses are only dropped when they cannot fit or would cost a lot of other preferences.
+    pub optional_course: f64,
+    /// Penalty per slot of makespan, the latest end of any course. Off by default; small
+    /// values nudge the schedule toward the start of the horizon without overriding the rest.
+    pub compact_schedule: f64,
+    /// Penalty for every timeslot that runs exactly one course. Off by default; adds two
+    /// binary variables per timeslot when set.
+    pub isolation_weight: f64,
+    /// Penalty per unit of `room_distances` between the rooms of every two courses of the
+    /// same instructor. Off by default; adds a variable per such pair of courses and rooms.
+    pub room_clustering_weight: f64,
+    /// Penalty per idle slot between the first and last class of an instructor's day. Off by
+    /// default; adds three variables per instructor and day when set.
+    pub idle_time_weight: f64,
+    /// Penalty for every room beyond the first a student group uses on a day. Off by default;
+    /// adds a variable per student group, day and candidate room when set.
+    pub cohort_room_changes_weight: f64,
+    /// Penalty for every instructor who teaches at least one course, so courses with
+    /// `eligible_instructors` go to fewer people. Off by default; adds a binary variable per
+    /// instructor when set.
+    pub minimize_instructors_weight: f64,
+    /// Reward for every course starting in the morning, scaled by its duration in slots times
+    /// its morning reward, so long courses win the morning over short ones. Off by default.
+    pub long_course_morning_weight: f64,
+    /// Penalty for every day an instructor with `avoid_single_course_days` teaches exactly one
+    /// course.
+    pub single_course_day: f64,
+}
+
+impl Default for SolverWeights {
+    fn default() -> Self {
+        SolverWeights {
+            morning_preference: 1.0,
+            back_to_back_penalty: 1.0,
+            preferred_day: 1.0,
+            department_compactness: 1.0,
+            adjacency: 1.0,
+            rightsizing: 0.0,
+            home_room: 1.0,
+            optional_course: 10.0,
+            compact_schedule: 0.0,
+            isolation_weight: 0.0,
+            room_clustering_weight: 0.0,
+            idle_time_weight: 0.0,
+            cohort_room_changes_weight: 0.0,
+            minimize_instructors_weight: 0.0,
+            long_course_morning_weight: 0.0,
+            single_course_day: 1.0,
+        }
+    }
+}
+
+/// What the solver optimises.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ObjectiveMode {
+    /// Weighted sum of the soft constraints.
+    #[default]
+    Weighted,
+    /// Minimise the latest period of the day any instructor still teaches in; ties are broken
+    /// by each instructor's own latest period. The soft constraint weights are ignored, but
+    /// override costs and the optional course reward still apply.
+    MinimizeLatestInstructorSlot,
+    /// For exam periods: first spread every student group's exams over as many days as
+    /// possible, then place harder exams (by `difficulty`) earlier. The soft constraint
+    /// weights are ignored like in `MinimizeLatestInstructorSlot`.
+    ExamSpread,
+}
+
+/// Which courses a timeslot may hold, e.g. to keep finals week for exams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotMode {
+    /// Only courses that are not exams.
+    ClassOnly,
+    /// Only courses with `is_exam` set.
+    ExamOnly,
+    #[default]
+    Any,
+}
+
+impl SlotMode {
+    pub fn allows(self, course: &Course) -> bool {
+        match self {
+            SlotMode::ClassOnly => !course.is_exam,
+            SlotMode::ExamOnly => course.is_exam,
+            SlotMode::Any => true,
+        }
+    }
+}
+
+/// How hard the solver works on the objective.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SolveMode {
+    /// Search until the solution is proven optimal.
+    #[default]
+    Optimize,
+    /// Stop at the first feasible schedule; useful for quick previews.
+    FirstFeasible,
+}
+
+/// How a schedule was found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SolveMethod {
+    /// By the ILP solver.
+    #[default]
+    Ilp,
+    /// By the greedy fallback after the ILP solver failed; see `fallback_to_heuristic`.
+    Heuristic,
+}
+
+/// A cohort of students attending the same courses; none of its courses may overlap.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentGroup {
+    pub id: StudentGroupId,
+    pub course_ids: Vec<CourseId>,
+    /// (first period, end period) within each day. The group's courses must start at or after
+    /// the first period and finish by the end period.
+    #[serde(default)]
+    pub daily_window: Option<(Timeslot, Timeslot)>,
 }
 
 /// The complete input for the scheduling problem.
@@ -38,27 +507,533 @@ pub struct Instructor {
 pub struct SchedulingInput {
     pub rooms: Vec<Room>,
     pub courses: Vec<Course>,
+    /// Courses given as a number of sections; see
+    /// [`SchedulingInput::with_sections_expanded`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub course_demands: Vec<CourseDemand>,
     pub instructors: Vec<Instructor>,
     pub total_timeslots: u32,
+    /// When true, a morning assignment is rewarded once per slot it occupies instead of once per course.
+    #[serde(default)]
+    pub scale_morning_by_duration: bool,
+    /// Number of slots in one day. When omitted, all timeslots belong to a single day.
+    #[serde(default)]
+    pub periods_per_day: Option<u32>,
+    /// Number of weeks the timeslots span, each with the same number of days. Room blocks
+    /// and preferred days recur every week; slots and every other day index count through
+    /// the whole horizon. One week when omitted.
+    #[serde(default)]
+    pub weeks: Option<u32>,
+    #[serde(default)]
+    pub student_groups: Vec<StudentGroup>,
+    /// Soft constraint weights; the defaults are used when omitted.
+    #[serde(default)]
+    pub weights: Option<SolverWeights>,
+    #[serde(default)]
+    pub objective_mode: ObjectiveMode,
+    #[serde(default)]
+    pub solve_mode: SolveMode,
+    /// Length of one slot in minutes; required for courses that give `duration_minutes`.
+    #[serde(default)]
+    pub slot_minutes: Option<u32>,
+    /// Course pairs that are rewarded for running back-to-back, in either order, on the same day.
+    #[serde(default)]
+    pub soft_adjacency: Vec<(CourseId, CourseId)>,
+    /// Sets of courses that must all be taught by different instructors, e.g. exams that
+    /// each need their own proctor. Mostly useful with `eligible_instructors`.
+    #[serde(default)]
+    pub distinct_instructor_groups: Vec<Vec<CourseId>>,
+    /// Lecture and lab pairs whose lab has to follow the lecture.
+    #[serde(default)]
+    pub section_links: Vec<SectionLink>,
+    /// Calendar date and time every slot starts at, so rooms and instructors can be blocked
+    /// by `unavailable_dates`. Slots left out are never blocked by a date range.
+    #[serde(default)]
+    pub slot_calendar: Vec<SlotDate>,
+    /// IANA time zone the times of `slot_calendar` are local to, e.g. "Europe/Berlin"; UTC
+    /// when omitted. Only needed to convert exported times into another zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    /// Slots closed to every room, e.g. a snow day.
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    /// (room, room, distance) triples, e.g. walking metres; the same in either direction.
+    /// Pairs left out are treated as distance 0.
+    #[serde(default)]
+    pub room_distances: Vec<(RoomId, RoomId, f64)>,
+    /// Sets of adjacent rooms that can be opened up into one larger room, e.g. two seminar
+    /// rooms with a movable partition. A combined room seats the sum of its rooms' capacities,
+    /// and while a course uses it none of its rooms can be used on their own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combinable: Vec<Vec<RoomId>>,
+    /// Assignments leaving more empty seats than this are reported as unmet soft constraints.
+    #[serde(default)]
+    pub max_capacity_slack: Option<u32>,
+    /// Tags whose courses may never overlap each other.
+    #[serde(default)]
+    pub tag_conflicts: Vec<String>,
+    /// Objective reward per course scheduled on each day (negative to discourage a day).
+    #[serde(default)]
+    pub day_weights: Option<Vec<f64>>,
+    /// (day, soft cap, weight) triples: every course starting on the day beyond the cap
+    /// costs the weight, e.g. to keep Fridays light without forbidding more.
+    #[serde(default)]
+    pub day_soft_caps: Vec<(u32, u32, f64)>,
+    /// Every slot that runs any course has to run at least this many.
+    #[serde(default)]
+    pub min_courses_per_active_slot: Option<u32>,
+    /// Every zone that runs any course in a slot has to run at least this many in it, e.g. 2
+    /// so no course is alone in a wing. Rooms without a zone are exempt.
+    #[serde(default)]
+    pub min_courses_per_active_zone: Option<u32>,
+    /// Report how much each objective term contributed to the solution.
+    #[serde(default)]
+    pub explain_objective: bool,
+    /// Let courses use unavailable instructor slots at a heavy cost instead of forbidding them.
+    #[serde(default)]
+    pub allow_override_unavailability: bool,
+    /// Solve independent groups of courses (no shared room, instructor, group, ...) separately.
+    #[serde(default)]
+    pub decompose: bool,
+    /// First period of the day that no longer counts as morning; half the day when omitted.
+    #[serde(default)]
+    pub morning_cutoff: Option<u32>,
+    /// Stop the solver after this many seconds and return the best schedule found so far.
+    #[serde(default)]
+    pub time_limit_seconds: Option<f64>,
+    /// Most courses running at the same time across all rooms; unlimited when omitted.
+    #[serde(default)]
+    pub max_concurrent_courses: Option<u32>,
+    /// Most back-to-back pairs of classes across all instructors, whatever their
+    /// `back_to_back_weight`; uncapped when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_back_to_back_pairs: Option<u32>,
+    /// Most courses that may start in a timeslot, as (slot, max) pairs; slots left out are
+    /// uncapped. Unlike `max_concurrent_courses`, courses already running do not count.
+    #[serde(default)]
+    pub max_courses_in_slot: Option<Vec<(Timeslot, u32)>>,
+    /// (slot, mode) pairs restricting timeslots to exams or to classes; slots left out take
+    /// any course.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slot_modes: Vec<(Timeslot, SlotMode)>,
+    /// Report the timeslots every room is left free in.
+    #[serde(default)]
+    pub include_free_slots: bool,
+    /// Report how many candidate assignments survive pre-filtering.
+    #[serde(default)]
+    pub include_stats: bool,
+    /// Report which room-overlap, instructor-overlap and capacity constraints are binding.
+    #[serde(default)]
+    pub include_binding: bool,
+    /// Hard constraints the solver may break, at a penalty per violation, when the input
+    /// cannot meet them all. Every constraint stays hard by default.
+    #[serde(default, skip_serializing_if = "Relaxations::is_empty")]
+    pub relaxations: Relaxations,
+    /// Build a schedule greedily when the solver fails or runs out of time without one, or
+    /// when the model would exceed `max_variables`.
+    #[serde(default)]
+    pub fallback_to_heuristic: bool,
+    /// Most candidate assignments left after pre-filtering that a model is built for; larger
+    /// inputs are rejected, or solved by the heuristic with `fallback_to_heuristic`. Applies
+    /// to each component of a decomposed input. Unlimited when omitted.
+    #[serde(default)]
+    pub max_variables: Option<usize>,
+    /// Every room has to hold at least one course.
+    #[serde(default)]
+    pub require_all_rooms_used: bool,
+    /// Reject inputs whose mandatory courses need more room-slots than the rooms offer,
+    /// instead of only warning about them.
+    #[serde(default)]
+    pub reject_oversubscribed: bool,
+    /// Scale the objective to integer coefficients so repeated solves rank schedules exactly
+    /// alike.
+    #[serde(default)]
+    pub integer_objective: bool,
+    /// Stop at the first schedule whose objective value reaches this instead of optimising;
+    /// implies `SolveMode::FirstFeasible`.
+    #[serde(default)]
+    pub min_objective: Option<f64>,
+    /// A previous schedule to start from, e.g. the assignments of an earlier output. Only a
+    /// hint: the solver drops it when it breaks a hard constraint, unless
+    /// `repair_warm_start` is set.
+    #[serde(default)]
+    pub warm_start: Vec<Assignment>,
+    /// Change as few `warm_start` assignments as possible instead of optimising the weighted
+    /// preferences, so a schedule an input edit invalidated is repaired with the fewest
+    /// moves.
+    #[serde(default)]
+    pub repair_warm_start: bool,
+    /// Timeslots kept as they are, e.g. the morning when only the afternoon is re-solved.
+    /// Courses outside `frozen_assignments` may not use them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frozen_slots: Vec<Timeslot>,
+    /// The schedule of the `frozen_slots`. An assignment that runs entirely within them is
+    /// fixed; the others are re-optimised like any course.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frozen_assignments: Vec<Assignment>,
+    /// How far below 1 an assignment variable may be and still count as chosen; defaults to
+    /// [`DEFAULT_SOLUTION_TOLERANCE`]. Only worth raising for solver settings that leave
+    /// slightly fractional values.
+    #[serde(default)]
+    pub solution_tolerance: Option<f64>,
+}
+
+impl SchedulingInput {
+    /// Copy of the input with every `duration_minutes` converted to `duration_slots`,
+    /// rounding up to whole slots.
+    pub fn with_durations_in_slots(&self) -> SchedulingInput {
+        let mut input = self.clone();
+        if let Some(slot_minutes) = self.slot_minutes.filter(|m| *m > 0) {
+            for course in &mut input.courses {
+                if let Some(minutes) = course.duration_minutes {
+                    course.duration_slots = minutes.div_ceil(slot_minutes);
+                }
+            }
+        }
+        input
+    }
+
+    /// Copy of the input with every `course_demands` entry replaced by its sections: copies
+    /// of the template with `section_of` set and ids counting up from above every course and
+    /// template id. Fails when the sections would need ids beyond `u32::MAX`.
+    pub fn with_sections_expanded(&self) -> Result<SchedulingInput, String> {
+        if self.course_demands.is_empty() {
+            return Ok(self.clone());
+        }
+        let mut input = self.clone();
+        let demands = std::mem::take(&mut input.course_demands);
+        let highest_id = self
+            .courses
+            .iter()
+            .map(|c| c.id.0)
+            .chain(demands.iter().map(|d| d.template.id.0))
+            .max();
+        let mut next_id = highest_id.map_or(Some(0), |id| id.checked_add(1));
+        for demand in demands {
+            for _ in 0..demand.sections {
+                let id = next_id.ok_or_else(|| {
+                    format!(
+                        "courseDemands need section ids above {}, but no ids are left.",
+                        highest_id.unwrap_or(0)
+                    )
+                })?;
+                input.courses.push(Course {
+                    id: CourseId(id),
+                    section_of: Some(demand.template.id),
+                    ..demand.template.clone()
+                });
+                next_id = id.checked_add(1);
+            }
+        }
+        Ok(input)
+    }
+
+    /// One virtual room per `combinable` set, with the rooms it combines in ascending order.
+    ///
+    /// A virtual room seats the sum of its rooms' capacities and pays their summed
+    /// `reserve_preference`; it is blocked whenever any of its rooms is, and belongs to their
+    /// zone when they share one. Its id lies above every real room id, so candidates can
+    /// refer to it like any other room. Rooms that do not exist are left out, as are sets
+    /// without a free id (see [`combined_room_id`](Self::combined_room_id)).
+    pub fn combined_rooms(&self) -> Vec<(Room, Vec<RoomId>)> {
+        if self.combinable.is_empty() {
+            return Vec::new();
+        }
+        self.combinable
+            .iter()
+            .enumerate()
+            .filter_map(|(index, room_ids)| Some((self.combined_room_id(index)?, room_ids)))
+            .map(|(id, room_ids)| {
+                let members: Vec<&Room> =
+                    self.rooms.iter().filter(|r| room_ids.contains(&r.id)).collect();
+                let zone = members.first().and_then(|r| r.zone);
+                let room = Room {
+                    id,
+                    capacity: members.iter().map(|r| r.capacity).sum(),
+                    recurring_unavailable: members
+                        .iter()
+                        .flat_map(|r| r.recurring_unavailable.iter().copied())
+                        .collect(),
+                    unavailable_slots: members
+                        .iter()
+                        .flat_map(|r| r.unavailable_slots.iter().copied())
+                        .collect(),
+                    unavailable_dates: members
+                        .iter()
+                        .flat_map(|r| r.unavailable_dates.iter().cloned())
+                        .collect(),
+                    reserve_preference: members.iter().map(|r| r.reserve_preference).sum(),
+                    zone: zone.filter(|zone| members.iter().all(|r| r.zone == Some(*zone))),
+                };
+                let mut room_ids: Vec<RoomId> = members.iter().map(|r| r.id).collect();
+                room_ids.sort_unstable();
+                (room, room_ids)
+            })
+            .collect()
+    }
+
+    /// Id of the virtual room of `combinable[index]`, counting up from above every real room
+    /// id; `None` when that lies beyond `u32::MAX`.
+    pub fn combined_room_id(&self, index: usize) -> Option<RoomId> {
+        let first_id =
+            self.rooms.iter().map(|r| r.id.0).max().map_or(Some(0), |id| id.checked_add(1))?;
+        let index = u32::try_from(index).ok()?;
+        first_id.checked_add(index).map(RoomId)
+    }
+
+    /// Mode of timeslot `k`, `Any` unless `slot_modes` lists it.
+    pub fn slot_mode(&self, k: Timeslot) -> SlotMode {
+        self.slot_modes
+            .iter()
+            .find(|(slot, _)| *slot == k)
+            .map_or(SlotMode::Any, |(_, mode)| *mode)
+    }
+
+    /// The `frozen_assignments` that run entirely within `frozen_slots`, by course; `self` has
+    /// to have its durations in slots already.
+    pub fn frozen_placements(&self) -> HashMap<CourseId, &Assignment> {
+        let frozen_slots: HashSet<Timeslot> = self.frozen_slots.iter().copied().collect();
+        self.frozen_assignments
+            .iter()
+            .filter(|a| {
+                let course = self.courses.iter().find(|c| c.id == a.course_id);
+                let end_slot = course.and_then(|c| a.start_slot.checked_add(c.duration_slots));
+                end_slot.is_some_and(|end_slot| {
+                    (a.start_slot..end_slot).all(|k| frozen_slots.contains(&k))
+                })
+            })
+            .map(|a| (a.course_id, a))
+            .collect()
+    }
+
+    /// Copy of the input in which every course with `eligible_instructors` is taught by the
+    /// instructor its assignment names, so it can be reported like any other course. Courses
+    /// without an assignment keep their eligible instructors.
+    pub fn with_chosen_instructors(&self, assignments: &[Assignment]) -> SchedulingInput {
+        let mut input = self.clone();
+        for course in &mut input.courses {
+            let chosen = assignments
+                .iter()
+                .find(|a| a.course_id == course.id)
+                .and_then(|a| a.instructor_id);
+            if let (Some(_), Some(instructor_id)) = (&course.eligible_instructors, chosen) {
+                course.eligible_instructors = None;
+                course.instructor_id = None;
+                course.instructor_ids = vec![instructor_id];
+            }
+        }
+        input
+    }
+
+    /// Number of slots in one day.
+    pub fn slots_per_day(&self) -> u32 {
+        self.periods_per_day.unwrap_or(self.total_timeslots).max(1)
+    }
+
+    /// Number of weeks in the horizon.
+    pub fn num_weeks(&self) -> u32 {
+        self.weeks.unwrap_or(1).max(1)
+    }
+
+    /// Number of days in one week.
+    pub fn days_per_week(&self) -> u32 {
+        (self.total_timeslots / self.slots_per_day() / self.num_weeks()).max(1)
+    }
+
+    /// Day (0-based) that a flat timeslot falls on, counted through the whole horizon.
+    pub fn day_of(&self, slot: Timeslot) -> u32 {
+        slot / self.slots_per_day()
+    }
+
+    /// Week (0-based) that a flat timeslot falls on.
+    pub fn week_of(&self, slot: Timeslot) -> u32 {
+        self.day_of(slot) / self.days_per_week()
+    }
+
+    /// Day within its week of a flat timeslot.
+    pub fn weekday_of(&self, slot: Timeslot) -> u32 {
+        self.day_of(slot) % self.days_per_week()
+    }
+
+    /// Period within its day of a flat timeslot.
+    pub fn period_of(&self, slot: Timeslot) -> u32 {
+        slot % self.slots_per_day()
+    }
+
+    /// Flat timeslot of a period on a given day.
+    pub fn slot_at(&self, day: u32, period: u32) -> Timeslot {
+        day * self.slots_per_day() + period
+    }
+
+    /// Flat timeslots of a period on a given day of the week, one per week.
+    pub fn weekly_slots(&self, weekday: u32, period: u32) -> impl Iterator<Item = Timeslot> {
+        let days_per_week = self.days_per_week();
+        let slots_per_day = self.slots_per_day();
+        (0..self.num_weeks())
+            .map(move |week| (week * days_per_week + weekday) * slots_per_day + period)
+    }
+
+    /// First period of the day that no longer counts as morning.
+    pub fn morning_cutoff(&self) -> u32 {
+        self.morning_cutoff.unwrap_or(self.slots_per_day() / 2)
+    }
+
+    /// Morning is the start of every day, by default its first half.
+    pub fn is_morning(&self, slot: Timeslot) -> bool {
+        self.period_of(slot) < self.morning_cutoff()
+    }
+}
+
+/// Stable SHA-256 fingerprint of an input, usable as a cache or idempotency key.
+///
+/// Lists whose order carries no meaning (rooms, courses, slot lists, ...) are sorted before
+/// hashing, so two inputs that only differ in ordering share a fingerprint.
+pub fn input_fingerprint(input: &SchedulingInput) -> String {
+    // an input whose sections can't be numbered is still fingerprinted as given
+    let mut canonical = input.with_sections_expanded().unwrap_or_else(|_| input.clone());
+    canonical.rooms.sort_by_key(|r| r.id);
+    for room in &mut canonical.rooms {
+        room.recurring_unavailable.sort_unstable();
+        room.recurring_unavailable.dedup();
+        room.unavailable_slots.sort_unstable();
+        room.unavailable_slots.dedup();
+    }
+    canonical.courses.sort_by_key(|c| c.id);
+    for course in &mut canonical.courses {
+        // the deprecated single field and the list mean the same thing
+        course.instructor_ids = course.instructors();
+        course.instructor_id = None;
+        if let Some(eligible) = &mut course.eligible_instructors {
+            eligible.sort_unstable();
+            eligible.dedup();
+        }
+        course.no_cross_slots.sort_unstable();
+        course.no_cross_slots.dedup();
+        course.tags.sort_unstable();
+        course.tags.dedup();
+    }
+    canonical.room_distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    for window in &mut canonical.maintenance_windows {
+        window.slots.sort_unstable();
+        window.slots.dedup();
+    }
+    canonical.maintenance_windows.sort();
+    if let Some(caps) = &mut canonical.max_courses_in_slot {
+        caps.sort_unstable();
+    }
+    for group in &mut canonical.distinct_instructor_groups {
+        group.sort_unstable();
+        group.dedup();
+    }
+    canonical.distinct_instructor_groups.sort();
+    canonical.section_links.sort_unstable();
+    for room_ids in &mut canonical.combinable {
+        room_ids.sort_unstable();
+        room_ids.dedup();
+    }
+    canonical.combinable.sort();
+    canonical.day_soft_caps.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    canonical.tag_conflicts.sort_unstable();
+    canonical.tag_conflicts.dedup();
+    canonical.instructors.sort_by_key(|i| i.id);
+    for instructor in &mut canonical.instructors {
+        instructor.unavailable_slots.sort_unstable();
+        instructor.unavailable_slots.dedup();
+        instructor.preferred_days.sort_unstable();
+        instructor.preferred_days.dedup();
+    }
+    canonical.student_groups.sort_by_key(|g| g.id);
+    for group in &mut canonical.student_groups {
+        group.course_ids.sort_unstable();
+        group.course_ids.dedup();
+    }
+    // weights equal to the defaults solve the same problem as no weights at all
+    canonical.weights = Some(canonical.weights.unwrap_or_default());
+
+    let json = serde_json::to_vec(&canonical).expect("scheduling input serializes to JSON");
+    format!("{:x}", Sha256::digest(json))
 }
 
 /// Represents a single, scheduled course assignment.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub struct Assignment {
     pub course_id: CourseId,
     pub room_id: RoomId,
     pub start_slot: Timeslot,
+    /// Instructor picked from the course's `eligible_instructors`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructor_id: Option<InstructorId>,
+    /// Every room of the combined room the course uses, `room_id` being the first of them;
+    /// empty for a single room. See `SchedulingInput::combinable`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combined_rooms: Vec<RoomId>,
+    /// Copied from the course for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Copied from the course for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl Assignment {
+    /// Every room the course occupies: its combined rooms, or just `room_id`.
+    pub fn rooms(&self) -> &[RoomId] {
+        if self.combined_rooms.is_empty() {
+            std::slice::from_ref(&self.room_id)
+        } else {
+            &self.combined_rooms
+        }
+    }
 }
 
 /// Describes a soft constraint that was not met in the final schedule.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnmetSoftConstraint {
     pub constraint_type: String,
     pub description: String,
 }
 
+/// How often one kind of soft constraint was met, e.g. how many courses start in the morning.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftConstraintSummary {
+    /// Same as the `constraint_type` of its unmet entries.
+    pub constraint_type: String,
+    pub met: u32,
+    pub total: u32,
+    /// `met` as a percentage of `total`.
+    pub percentage: f64,
+}
+
+impl SoftConstraintSummary {
+    pub fn new(constraint_type: &str) -> Self {
+        SoftConstraintSummary {
+            constraint_type: constraint_type.to_string(),
+            met: 0,
+            total: 0,
+            percentage: 0.0,
+        }
+    }
+
+    /// Counts one more case, met or not.
+    pub fn record(&mut self, met: bool) {
+        self.add(u32::from(met), 1);
+    }
+
+    /// Counts `met` out of `total` more cases, e.g. those of another component.
+    pub fn add(&mut self, met: u32, total: u32) {
+        self.met += met;
+        self.total += total;
+        self.percentage = if self.total == 0 {
+            0.0
+        } else {
+            100.0 * self.met as f64 / self.total as f64
+        };
+    }
+}
+
 impl fmt::Display for UnmetSoftConstraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[{}] {}", self.constraint_type, self.description)
@@ -66,11 +1041,749 @@ impl fmt::Display for UnmetSoftConstraint {
 }
 
 
+/// How far an anchored course ended up from its target slot.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetDeviation {
+    pub course_id: CourseId,
+    pub target_slot: Timeslot,
+    pub start_slot: Timeslot,
+    pub deviation: u32,
+}
+
+/// Slots covered by a department's courses, from the earliest start to the latest end.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepartmentSpan {
+    pub department_id: u32,
+    pub first_slot: Timeslot,
+    /// Exclusive.
+    pub end_slot: Timeslot,
+    pub span: u32,
+}
+
+/// Whether a `soft_adjacency` pair ended up back-to-back.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjacencyStatus {
+    pub course_ids: (CourseId, CourseId),
+    pub satisfied: bool,
+}
+
+/// End of an instructor's latest class, as a period within the day (exclusive).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructorLatestEnd {
+    pub instructor_id: InstructorId,
+    pub latest_end_period: u32,
+}
+
+/// Sum of the `room_distances` between the rooms of every two courses of an instructor.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructorRoomDistance {
+    pub instructor_id: InstructorId,
+    pub total_distance: f64,
+}
+
+/// Free slots between the classes of an instructor, summed over their days.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructorIdleSlots {
+    pub instructor_id: InstructorId,
+    pub idle_slots: u32,
+}
+
+/// The courses generated for one `course_demands` entry.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseSections {
+    pub template_id: CourseId,
+    pub section_ids: Vec<CourseId>,
+}
+
+/// Rooms a student group moves between on one day of classes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortRoomChanges {
+    pub student_group_id: StudentGroupId,
+    pub day: u32,
+    /// Distinct rooms the group's courses use that day, minus one.
+    pub room_changes: u32,
+}
+
+/// How far a day with a `day_soft_caps` entry went over its cap.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayOverflow {
+    pub day: u32,
+    pub course_count: u32,
+    pub soft_cap: u32,
+    /// Courses beyond the cap; 0 when the day stayed within it.
+    pub overflow: u32,
+}
+
+/// A course taught in slots its instructor marked as unavailable.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnavailabilityOverride {
+    pub instructor_id: InstructorId,
+    pub course_id: CourseId,
+    pub slots: Vec<Timeslot>,
+    pub cost: f64,
+}
+
+/// Whether a room held in reserve ended up being used.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedRoomUsage {
+    pub room_id: RoomId,
+    pub used: bool,
+    pub course_count: u32,
+}
+
+/// Timeslots a room is not used in by any course.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomFreeSlots {
+    pub room_id: RoomId,
+    pub slots: Vec<Timeslot>,
+}
+
+/// Size of the model compared to trying every course in every room at every slot.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefilterStats {
+    /// (course, room, start slot) candidates left after pre-filtering, one variable each.
+    pub candidates: usize,
+    /// Courses × rooms × timeslots.
+    pub theoretical_max: usize,
+    /// Share of the theoretical maximum that pre-filtering removed, from 0 to 1.
+    pub reduction_ratio: f64,
+}
+
+impl PrefilterStats {
+    pub fn new(candidates: usize, theoretical_max: usize) -> Self {
+        let reduction_ratio = if theoretical_max == 0 {
+            0.0
+        } else {
+            1.0 - candidates as f64 / theoretical_max as f64
+        };
+        PrefilterStats {
+            candidates,
+            theoretical_max,
+            reduction_ratio,
+        }
+    }
+}
+
+/// How the solve ended.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveStatus {
+    pub mode: SolveMode,
+    pub method: SolveMethod,
+    /// False whenever the search stopped early, which `FirstFeasible` always reports.
+    pub proven_optimal: bool,
+}
+
 /// The final output of the solver.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchedulingOutput {
     pub assignments: Vec<Assignment>,
-    pub score: i32,
+    pub score: f64,
+    /// Value of the solver's objective; absent for heuristic schedules. Exact when
+    /// `integer_objective` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective_value: Option<f64>,
     pub unmet_soft_constraints: Vec<UnmetSoftConstraint>,
-}
\ No newline at end of file
+    /// How many cases of each soft constraint were met, in the order they are checked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub soft_constraint_summary: Vec<SoftConstraintSummary>,
+    pub solve_status: SolveStatus,
+    /// Distance from the target slot for every course that has one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_deviations: Vec<TargetDeviation>,
+    /// Resulting span of every department.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub department_spans: Vec<DepartmentSpan>,
+    /// Outcome of every requested soft adjacency.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adjacencies: Vec<AdjacencyStatus>,
+    /// Latest end of every instructor; only reported in `MinimizeLatestInstructorSlot` mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructor_latest_ends: Vec<InstructorLatestEnd>,
+    /// Room distance covered by every instructor; only reported with a nonzero
+    /// `room_clustering_weight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructor_room_distances: Vec<InstructorRoomDistance>,
+    /// Assignments of the `warm_start` that the schedule does not keep; only reported with a
+    /// warm start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_start_changes: Option<u32>,
+    /// Idle slots of every instructor; only reported with a nonzero `idle_time_weight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructor_idle_slots: Vec<InstructorIdleSlots>,
+    /// Room changes of every student group on each of its days; only reported with a nonzero
+    /// `cohort_room_changes_weight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cohort_room_changes: Vec<CohortRoomChanges>,
+    /// Instructors who teach at least one course; only reported with a nonzero
+    /// `minimize_instructors_weight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructors_used: Vec<InstructorId>,
+    /// Courses dropped before solving because they are below their minimum enrollment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cancelled_courses: Vec<CourseId>,
+    /// Optional courses the solver left out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unscheduled_courses: Vec<CourseId>,
+    /// Latest end slot of any scheduled course.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub makespan: Option<Timeslot>,
+    /// Timeslots that run exactly one course; only reported with a nonzero
+    /// `isolation_weight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub isolated_slots: Vec<Timeslot>,
+    /// Unavailable slots that were used because `allow_override_unavailability` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailability_overrides: Vec<UnavailabilityOverride>,
+    /// Usage of every room with a positive `reserve_preference`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reserved_rooms: Vec<ReservedRoomUsage>,
+    /// Free timeslots of every room; only reported when `include_free_slots` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub free_slots: Vec<RoomFreeSlots>,
+    /// Constraints the schedule meets exactly at their bound, e.g. a room booked in a slot;
+    /// only reported when `include_binding` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binding_constraints: Vec<String>,
+    /// Every violation of a hard constraint allowed by `relaxations`, prefixed with the name
+    /// of the constraint.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraint_violations: Vec<String>,
+    /// Ids of the sections generated for every `course_demands` entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub course_sections: Vec<CourseSections>,
+    /// Number of courses starting on each day; only reported when `day_weights` is given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub courses_per_day: Vec<u32>,
+    /// Load of every day with a `day_soft_caps` entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub day_overflows: Vec<DayOverflow>,
+    /// Weighted value of every objective term at the solution; their sum is the objective
+    /// value. Only reported when `explain_objective` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective_breakdown: Option<BTreeMap<String, f64>>,
+    /// Pre-filtering statistics; only reported when `include_stats` is set. A decomposed input
+    /// reports the totals over its components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefilter_stats: Option<PrefilterStats>,
+    /// Number of assignments in the full schedule; only set when the response is paginated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<usize>,
+}
+
+/// Assignments keyed by room, each list ordered by start slot. A course in a combined room
+/// appears under each of its rooms.
+pub fn group_by_room(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+) -> BTreeMap<RoomId, Vec<Assignment>> {
+    let mut groups: BTreeMap<RoomId, Vec<Assignment>> =
+        input.rooms.iter().map(|r| (r.id, Vec::new())).collect();
+    for assignment in assignments {
+        for room_id in assignment.rooms() {
+            groups.entry(*room_id).or_default().push(assignment.clone());
+        }
+    }
+    for group in groups.values_mut() {
+        group.sort_by_key(|a| (a.start_slot, a.course_id));
+    }
+    groups
+}
+
+/// Assignments keyed by the instructors teaching the course, each list ordered by start slot.
+/// A co-taught course appears under each of its instructors.
+pub fn group_by_instructor(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+) -> BTreeMap<InstructorId, Vec<Assignment>> {
+    let input = input.with_chosen_instructors(assignments);
+    let mut groups: BTreeMap<InstructorId, Vec<Assignment>> =
+        input.instructors.iter().map(|i| (i.id, Vec::new())).collect();
+    for assignment in assignments {
+        if let Some(course) = input.courses.iter().find(|c| c.id == assignment.course_id) {
+            for instructor_id in course.instructors() {
+                groups.entry(instructor_id).or_default().push(assignment.clone());
+            }
+        }
+    }
+    for group in groups.values_mut() {
+        group.sort_by_key(|a| (a.start_slot, a.course_id));
+    }
+    groups
+}
+
+/// Per-room timelines of a schedule, run-length encoded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OccupancyMatrix {
+    pub total_timeslots: u32,
+    pub rooms: Vec<RoomOccupancy>,
+}
+
+/// One room's timeline as `(course id, length)` runs covering every timeslot in order; a
+/// `None` course id is a free stretch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomOccupancy {
+    pub room_id: RoomId,
+    pub runs: Vec<(Option<CourseId>, u32)>,
+}
+
+pub fn to_occupancy(output: &SchedulingOutput, input: &SchedulingInput) -> OccupancyMatrix {
+    let durations: HashMap<CourseId, u32> = input
+        .with_durations_in_slots()
+        .courses
+        .iter()
+        .map(|c| (c.id, c.duration_slots))
+        .collect();
+    let rooms = group_by_room(&output.assignments, input)
+        .into_iter()
+        .map(|(room_id, assignments)| {
+            let mut grid: Vec<Option<CourseId>> = vec![None; input.total_timeslots as usize];
+            for assignment in assignments {
+                let duration = durations.get(&assignment.course_id).copied().unwrap_or(1);
+                let start = assignment.start_slot as usize;
+                let end = (start + duration as usize).min(grid.len());
+                for cell in grid.iter_mut().take(end).skip(start) {
+                    *cell = Some(assignment.course_id);
+                }
+            }
+            let mut runs: Vec<(Option<CourseId>, u32)> = Vec::new();
+            for cell in grid {
+                match runs.last_mut() {
+                    Some((course_id, length)) if *course_id == cell => *length += 1,
+                    _ => runs.push((cell, 1)),
+                }
+            }
+            RoomOccupancy { room_id, runs }
+        })
+        .collect();
+    OccupancyMatrix {
+        total_timeslots: input.total_timeslots,
+        rooms,
+    }
+}
+
+/// Free timeslots of every room, given the duration of each assigned course.
+pub fn free_slots(assignments: &[Assignment], input: &SchedulingInput) -> Vec<RoomFreeSlots> {
+    let durations: HashMap<CourseId, u32> = input
+        .with_durations_in_slots()
+        .courses
+        .iter()
+        .map(|c| (c.id, c.duration_slots))
+        .collect();
+    group_by_room(assignments, input)
+        .into_iter()
+        .map(|(room_id, assignments)| {
+            let mut free = vec![true; input.total_timeslots as usize];
+            for assignment in assignments {
+                let duration = durations.get(&assignment.course_id).copied().unwrap_or(1);
+                let start = assignment.start_slot as usize;
+                let end = (start + duration as usize).min(free.len());
+                for cell in free.iter_mut().take(end).skip(start) {
+                    *cell = false;
+                }
+            }
+            RoomFreeSlots {
+                room_id,
+                slots: (0..input.total_timeslots).filter(|t| free[*t as usize]).collect(),
+            }
+        })
+        .collect()
+}
+
+/// A course that is scheduled in both schedules, but not the same way.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedCourse {
+    pub course_id: CourseId,
+    pub before: Assignment,
+    pub after: Assignment,
+}
+
+/// How one schedule differs from another, course by course.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleDiff {
+    /// Courses in another room, slot or with another instructor.
+    pub moved: Vec<MovedCourse>,
+    /// Courses only the new schedule holds, e.g. optional courses that now fit.
+    pub added: Vec<Assignment>,
+    /// Courses only the old schedule holds.
+    pub removed: Vec<Assignment>,
+    /// Number of courses placed exactly as before.
+    pub unchanged: usize,
+}
+
+/// Compares two schedules of the same courses; labels and colours are ignored.
+pub fn diff_schedules(before: &[Assignment], after: &[Assignment]) -> ScheduleDiff {
+    let old: BTreeMap<CourseId, &Assignment> = before.iter().map(|a| (a.course_id, a)).collect();
+    let new: BTreeMap<CourseId, &Assignment> = after.iter().map(|a| (a.course_id, a)).collect();
+    let placement =
+        |a: &Assignment| (a.room_id, a.combined_rooms.clone(), a.start_slot, a.instructor_id);
+    let mut diff = ScheduleDiff::default();
+    for (course_id, old_assignment) in &old {
+        match new.get(course_id) {
+            None => diff.removed.push((*old_assignment).clone()),
+            Some(new_assignment) if placement(new_assignment) == placement(old_assignment) => {
+                diff.unchanged += 1
+            }
+            Some(new_assignment) => diff.moved.push(MovedCourse {
+                course_id: *course_id,
+                before: (*old_assignment).clone(),
+                after: (*new_assignment).clone(),
+            }),
+        }
+    }
+    diff.added = new
+        .iter()
+        .filter(|(course_id, _)| !old.contains_key(course_id))
+        .map(|(_, assignment)| (*assignment).clone())
+        .collect();
+    diff
+}
+
+impl SchedulingOutput {
+    /// Keeps only the requested page of the assignments, ordered by start slot. The score and
+    /// the unmet soft constraints still describe the full schedule.
+    pub fn paginate(&mut self, offset: usize, limit: Option<usize>) {
+        self.assignments
+            .sort_by_key(|a| (a.start_slot, a.course_id, a.room_id));
+        let total = self.assignments.len();
+        let start = offset.min(total);
+        let end = limit.map_or(total, |limit| start.saturating_add(limit).min(total));
+        self.assignments = self.assignments[start..end].to_vec();
+        self.total_count = Some(total);
+    }
+}
+
+/// Result of checking an input without solving it.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub valid: bool,
+    /// Problems that make the input unsolvable as given.
+    pub errors: Vec<String>,
+    /// Problems that do not block solving but likely make the result surprising.
+    pub warnings: Vec<String>,
+}
+
+/// One constraint the solver will apply to an input.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintDescription {
+    pub name: &'static str,
+    pub description: String,
+    /// Global weight of a soft constraint; absent for hard constraints and for soft
+    /// constraints weighted per course, room or instructor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// Every hard and soft constraint that applies to an input, without building the model.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintSummary {
+    pub hard: Vec<ConstraintDescription>,
+    pub soft: Vec<ConstraintDescription>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(value: serde_json::Value) -> SchedulingInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn sections_of_the_highest_course_id_are_rejected_not_wrapped() {
+        let course = json!({
+            "id": u32::MAX, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+        });
+        let plain = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [course],
+            "totalTimeslots": 4
+        }));
+        assert_eq!(plain.with_sections_expanded().unwrap().courses.len(), 1);
+        input_fingerprint(&plain);
+        assert!(crate::validation::validate_input(&plain).valid);
+
+        let with_demand = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [course],
+            "totalTimeslots": 4,
+            "courseDemands": [{
+                "template": {"id": 1, "requiredCapacity": 10, "durationSlots": 1},
+                "sections": 1
+            }]
+        }));
+        assert!(with_demand.with_sections_expanded().is_err());
+        assert!(!crate::validation::validate_input(&with_demand).valid);
+    }
+
+    #[test]
+    fn combined_rooms_need_a_free_id_only_when_rooms_are_combined() {
+        let mut highest_room = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": u32::MAX, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 4
+        }));
+        assert!(highest_room.combined_rooms().is_empty());
+        assert!(crate::validation::validate_input(&highest_room).valid);
+        assert!(!crate::solver::possible_assignments(&highest_room).is_empty());
+
+        highest_room.combinable = vec![vec![RoomId(1), RoomId(u32::MAX)]];
+        assert!(highest_room.combined_rooms().is_empty());
+        assert!(!crate::validation::validate_input(&highest_room).valid);
+    }
+
+    #[test]
+    fn pagination_clamps_to_the_schedule_and_keeps_its_score() {
+        let course = |id: u32| {
+            json!({"id": id, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]})
+        };
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [course(1), course(2), course(3)],
+            "totalTimeslots": 4
+        }));
+        let full = crate::solver::solve(&input).unwrap();
+        let page = |offset: usize, limit: Option<usize>| {
+            let mut output = full.clone();
+            output.paginate(offset, limit);
+            assert_eq!(output.total_count, Some(3));
+            assert_eq!(output.score, full.score);
+            assert_eq!(output.unmet_soft_constraints.len(), full.unmet_soft_constraints.len());
+            output.assignments.iter().map(|a| a.start_slot).collect::<Vec<_>>()
+        };
+        let mut starts: Vec<Timeslot> = full.assignments.iter().map(|a| a.start_slot).collect();
+        starts.sort();
+
+        assert_eq!(page(0, None), starts);
+        assert_eq!(page(1, Some(1)), starts[1..2]);
+        assert_eq!(page(2, Some(5)), starts[2..]);
+        assert!(page(3, Some(1)).is_empty());
+        assert!(page(usize::MAX, Some(usize::MAX)).is_empty());
+        assert!(page(0, Some(0)).is_empty());
+    }
+
+    #[test]
+    fn the_fingerprint_ignores_list_order_but_not_content() {
+        let fingerprint = |value: serde_json::Value| input_fingerprint(&input(value));
+        let a = fingerprint(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "recurringUnavailable": [[0, 3], [0, 1]]},
+                {"id": 2, "capacity": 20}
+            ],
+            "instructors": [
+                {"id": 1, "unavailableSlots": [2, 0]}, {"id": 2, "unavailableSlots": []}
+            ],
+            "courses": [
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]},
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [2]}
+            ],
+            "studentGroups": [{"id": 1, "courseIds": [2, 1]}],
+            "totalTimeslots": 4
+        }));
+        let b = fingerprint(json!({
+            "totalTimeslots": 4,
+            "studentGroups": [{"id": 1, "courseIds": [1, 2]}],
+            "courses": [
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [2]},
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]}
+            ],
+            "instructors": [
+                {"id": 2, "unavailableSlots": []}, {"id": 1, "unavailableSlots": [0, 2]}
+            ],
+            "rooms": [
+                {"id": 2, "capacity": 20},
+                {"id": 1, "capacity": 30, "recurringUnavailable": [[0, 1], [0, 3]]}
+            ]
+        }));
+        let c = fingerprint(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "recurringUnavailable": [[0, 1], [0, 3]]},
+                {"id": 2, "capacity": 20}
+            ],
+            "instructors": [
+                {"id": 1, "unavailableSlots": [0, 2]}, {"id": 2, "unavailableSlots": []}
+            ],
+            "courses": [
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 2, "instructorIds": [1]},
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [2]}
+            ],
+            "studentGroups": [{"id": 1, "courseIds": [1, 2]}],
+            "totalTimeslots": 4
+        }));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.len(), 64);
+    }
+
+    #[test]
+    fn assignments_group_by_room_and_by_instructor() {
+        let input = input(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}, {"id": 3, "capacity": 30}
+            ],
+            "instructors": [
+                {"id": 1, "unavailableSlots": []}, {"id": 2, "unavailableSlots": []}
+            ],
+            "courses": [
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]},
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [2]},
+                {"id": 3, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]}
+            ],
+            "totalTimeslots": 4
+        }));
+        let assignment = |course_id, room_id, start_slot| Assignment {
+            course_id: CourseId(course_id),
+            room_id: RoomId(room_id),
+            start_slot,
+            instructor_id: None,
+            combined_rooms: Vec::new(),
+            label: None,
+            color: None,
+        };
+        let assignments = [assignment(3, 1, 2), assignment(1, 1, 0), assignment(2, 2, 1)];
+        let ids = |list: &Vec<Assignment>| list.iter().map(|a| a.course_id.0).collect::<Vec<_>>();
+
+        let by_room = group_by_room(&assignments, &input);
+        let by_room: Vec<_> = by_room.iter().map(|(id, list)| (id.0, ids(list))).collect();
+        assert_eq!(by_room, [(1, vec![1, 3]), (2, vec![2]), (3, vec![])]);
+
+        let by_instructor = group_by_instructor(&assignments, &input);
+        let by_instructor: Vec<_> =
+            by_instructor.iter().map(|(id, list)| (id.0, ids(list))).collect();
+        assert_eq!(by_instructor, [(1, vec![1, 3]), (2, vec![2])]);
+    }
+
+    #[test]
+    fn occupancy_runs_cover_a_multi_slot_course_once() {
+        let course = |id: u32, duration_slots: u32| {
+            json!({
+                "id": id, "requiredCapacity": 10, "durationSlots": duration_slots,
+                "instructorIds": [id]
+            })
+        };
+        // each instructor is free only where their course has to go
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 5}],
+            "instructors": [
+                {"id": 1, "unavailableSlots": [0, 4, 5, 6, 7]},
+                {"id": 2, "unavailableSlots": [0, 1, 2, 3, 5, 6, 7]}
+            ],
+            "courses": [course(1, 3), course(2, 1)],
+            "totalTimeslots": 8
+        }));
+        let occupancy = to_occupancy(&crate::solver::solve(&input).unwrap(), &input);
+        assert_eq!(occupancy.total_timeslots, 8);
+        assert_eq!(
+            occupancy.rooms,
+            [
+                RoomOccupancy {
+                    room_id: RoomId(1),
+                    runs: vec![
+                        (None, 1),
+                        (Some(CourseId(1)), 3),
+                        (Some(CourseId(2)), 1),
+                        (None, 3)
+                    ],
+                },
+                RoomOccupancy { room_id: RoomId(2), runs: vec![(None, 8)] },
+            ]
+        );
+    }
+
+    #[test]
+    fn free_slots_leave_out_every_slot_of_a_multi_slot_course() {
+        let course = |id: u32, duration_slots: u32| {
+            json!({
+                "id": id, "requiredCapacity": 10, "durationSlots": duration_slots,
+                "instructorIds": [1]
+            })
+        };
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [course(1, 3), course(2, 1)],
+            "totalTimeslots": 6
+        }));
+        let assignment = |course_id, start_slot| Assignment {
+            course_id: CourseId(course_id),
+            room_id: RoomId(1),
+            start_slot,
+            instructor_id: None,
+            combined_rooms: Vec::new(),
+            label: None,
+            color: None,
+        };
+        let assignments = [assignment(1, 1), assignment(2, 5)];
+        let free: Vec<_> =
+            free_slots(&assignments, &input).into_iter().map(|r| (r.room_id.0, r.slots)).collect();
+        assert_eq!(free, [(1, vec![0, 4]), (2, vec![0, 1, 2, 3, 4, 5])]);
+    }
+
+    #[test]
+    fn typed_ids_round_trip_as_plain_numbers() {
+        let value = json!({
+            "rooms": [{"id": 3, "capacity": 30}],
+            "courses": [{
+                "id": 7, "instructorIds": [5], "durationSlots": 1, "requiredCapacity": 10
+            }],
+            "instructors": [{"id": 5, "unavailableSlots": [1]}],
+            "totalTimeslots": 4,
+            "studentGroups": [{"id": 2, "courseIds": [7]}]
+        });
+        let parsed = input(value.clone());
+        assert_eq!(parsed.courses[0].id, CourseId(7));
+        assert_eq!(parsed.courses[0].instructor_ids, [InstructorId(5)]);
+        assert_eq!(parsed.student_groups[0].course_ids, [CourseId(7)]);
+        let round_trip = serde_json::to_value(&parsed).unwrap();
+        for path in [
+            "/rooms/0/id",
+            "/courses/0/id",
+            "/courses/0/instructorIds/0",
+            "/studentGroups/0/courseIds/0",
+        ] {
+            assert_eq!(round_trip.pointer(path), value.pointer(path), "{}", path);
+        }
+
+        let assignment = Assignment {
+            course_id: CourseId(7),
+            room_id: RoomId(3),
+            start_slot: 2,
+            instructor_id: Some(InstructorId(5)),
+            combined_rooms: Vec::new(),
+            label: None,
+            color: None,
+        };
+        let json = serde_json::to_string(&assignment).unwrap();
+        assert_eq!(json, r#"{"courseId":7,"roomId":3,"startSlot":2,"instructorId":5}"#);
+        let quoted_id = json!({"id": "3", "capacity": 30});
+        assert!(serde_json::from_value::<Room>(quoted_id).is_err());
+    }
+}
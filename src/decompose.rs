@@ -0,0 +1,377 @@
+use crate::data::{
+    CourseId, ObjectiveMode, PrefilterStats, RoomId, SchedulingInput, SchedulingOutput,
+    SolveMethod, Timeslot,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Splits an input into independent sub-problems that share no room, instructor, student
+/// group, department, tag conflict, soft adjacency or section link, so each can be solved on
+/// its own. The rooms of a `combinable` set always stay together.
+///
+/// A course is linked to every room it has a candidate in (see
+/// [`possible_assignments`](crate::solver::possible_assignments)), so two components never
+/// compete for a room. Since a course fits every room at least as large as its requirement,
+/// rooms only separate components when recurring room blocks keep them apart, e.g. rooms
+/// blocked on the days other courses are pinned to.
+///
+/// Returns `None` when there is only one component anyway, or when the input has a setting
+/// that couples every course regardless:
+/// - the `MinimizeLatestInstructorSlot` and `ExamSpread` objectives;
+/// - `min_courses_per_active_slot`, `max_concurrent_courses` and `max_courses_in_slot`;
+/// - `max_back_to_back_pairs` and `day_soft_caps`;
+/// - `min_objective`;
+/// - a `compact_schedule` or `isolation_weight`.
+pub fn split_components(
+    input: &SchedulingInput,
+    candidates: &[(CourseId, RoomId, Timeslot)],
+) -> Option<Vec<SchedulingInput>> {
+    if input.objective_mode == ObjectiveMode::MinimizeLatestInstructorSlot
+        || input.objective_mode == ObjectiveMode::ExamSpread
+        || input.min_courses_per_active_slot.is_some()
+        || input.max_concurrent_courses.is_some()
+        || input.max_back_to_back_pairs.is_some()
+        || input.max_courses_in_slot.is_some()
+        || !input.day_soft_caps.is_empty()
+        || input.min_objective.is_some()
+        || input
+            .weights
+            .as_ref()
+            .is_some_and(|w| w.compact_schedule != 0.0 || w.isolation_weight != 0.0)
+    {
+        return None;
+    }
+
+    // nodes 0..courses are courses, the rest are rooms
+    let num_courses = input.courses.len();
+    let mut components = UnionFind::new(num_courses + input.rooms.len());
+    let course_index: HashMap<CourseId, usize> =
+        input.courses.iter().enumerate().map(|(index, c)| (c.id, index)).collect();
+    let room_index: HashMap<RoomId, usize> =
+        input.rooms.iter().enumerate().map(|(index, r)| (r.id, index)).collect();
+    // a combined room is as good as its first room, since its rooms are joined anyway
+    let mut first_room: HashMap<RoomId, RoomId> = HashMap::new();
+    for (room, room_ids) in input.combined_rooms() {
+        let indices: Vec<usize> =
+            room_ids.iter().filter_map(|r_id| room_index.get(r_id).copied()).collect();
+        for pair in indices.windows(2) {
+            components.union(num_courses + pair[0], num_courses + pair[1]);
+        }
+        if let Some(first) = room_ids.first() {
+            first_room.insert(room.id, *first);
+        }
+    }
+    let course_rooms: HashSet<(CourseId, RoomId)> = candidates
+        .iter()
+        .map(|(c_id, r_id, _)| (*c_id, first_room.get(r_id).copied().unwrap_or(*r_id)))
+        .collect();
+    for (c_id, r_id) in &course_rooms {
+        if let (Some(course), Some(room)) = (course_index.get(c_id), room_index.get(r_id)) {
+            components.union(*course, num_courses + room);
+        }
+    }
+    // rooms of a zone with a minimum occupancy are filled together
+    if input.min_courses_per_active_zone.is_some() {
+        let mut first_by_zone = HashMap::new();
+        for (index, room) in input.rooms.iter().enumerate() {
+            if let Some(zone) = room.zone {
+                let first = *first_by_zone.entry(zone).or_insert(index);
+                components.union(num_courses + first, num_courses + index);
+            }
+        }
+    }
+    let mut first_by_instructor = HashMap::new();
+    let mut first_by_department = HashMap::new();
+    for (index, course) in input.courses.iter().enumerate() {
+        for instructor_id in course.possible_instructors() {
+            let first = *first_by_instructor.entry(instructor_id).or_insert(index);
+            components.union(first, index);
+        }
+        if let Some(department_id) = course.department_id {
+            let first = *first_by_department.entry(department_id).or_insert(index);
+            components.union(first, index);
+        }
+    }
+    let linked_sets = input
+        .student_groups
+        .iter()
+        .map(|g| g.course_ids.clone())
+        .chain(input.tag_conflicts.iter().map(|tag| {
+            input.courses.iter().filter(|c| c.tags.contains(tag)).map(|c| c.id).collect()
+        }))
+        .chain(input.soft_adjacency.iter().map(|(first, second)| vec![*first, *second]))
+        .chain(input.section_links.iter().map(|link| vec![link.lecture, link.lab]));
+    for course_ids in linked_sets {
+        let indices: Vec<usize> =
+            course_ids.iter().filter_map(|c_id| course_index.get(c_id).copied()).collect();
+        for pair in indices.windows(2) {
+            components.union(pair[0], pair[1]);
+        }
+    }
+
+    // one sub-problem per component that has a course, in order of its first course
+    let mut roots: Vec<usize> = Vec::new();
+    for index in 0..num_courses {
+        let root = components.find(index);
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    if roots.len() < 2 {
+        return None;
+    }
+    let mut parts: Vec<SchedulingInput> = roots
+        .iter()
+        .map(|root| {
+            let courses: Vec<_> = input
+                .courses
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| components.find(*index) == *root)
+                .map(|(_, c)| c.clone())
+                .collect();
+            let has_course = |c_id: &CourseId| courses.iter().any(|c| c.id == *c_id);
+            SchedulingInput {
+                rooms: input
+                    .rooms
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| components.find(num_courses + index) == *root)
+                    .map(|(_, r)| r.clone())
+                    .collect(),
+                instructors: input
+                    .instructors
+                    .iter()
+                    .filter(|i| courses.iter().any(|c| c.instructors().contains(&i.id)))
+                    .cloned()
+                    .collect(),
+                student_groups: input
+                    .student_groups
+                    .iter()
+                    .filter(|g| g.course_ids.iter().any(has_course))
+                    .cloned()
+                    .collect(),
+                soft_adjacency: input
+                    .soft_adjacency
+                    .iter()
+                    .filter(|(first, _)| has_course(first))
+                    .copied()
+                    .collect(),
+                section_links: input
+                    .section_links
+                    .iter()
+                    .filter(|link| has_course(&link.lecture))
+                    .copied()
+                    .collect(),
+                warm_start: input
+                    .warm_start
+                    .iter()
+                    .filter(|a| has_course(&a.course_id))
+                    .cloned()
+                    .collect(),
+                frozen_assignments: input
+                    .frozen_assignments
+                    .iter()
+                    .filter(|a| has_course(&a.course_id))
+                    .cloned()
+                    .collect(),
+                distinct_instructor_groups: input
+                    .distinct_instructor_groups
+                    .iter()
+                    .filter(|group| group.iter().any(has_course))
+                    .cloned()
+                    .collect(),
+                combinable: input
+                    .combinable
+                    .iter()
+                    .filter(|room_ids| {
+                        let index = room_ids.first().and_then(|r_id| room_index.get(r_id));
+                        index.is_some_and(|index| components.find(num_courses + index) == *root)
+                    })
+                    .cloned()
+                    .collect(),
+                decompose: false,
+                courses,
+                ..input.clone()
+            }
+        })
+        .collect();
+    // rooms no course can use still belong in the reports (e.g. unused reserved rooms)
+    let leftover_rooms = input
+        .rooms
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !roots.contains(&components.find(num_courses + index)))
+        .map(|(_, r)| r.clone());
+    parts[0].rooms.extend(leftover_rooms);
+    Some(parts)
+}
+
+/// Combines the outputs of independently solved components into one output.
+pub fn merge_outputs(outputs: Vec<SchedulingOutput>) -> SchedulingOutput {
+    let mut outputs = outputs.into_iter();
+    let mut merged = outputs.next().expect("at least one component");
+    for output in outputs {
+        merged.assignments.extend(output.assignments);
+        merged.score += output.score;
+        merged.objective_value =
+            merged.objective_value.zip(output.objective_value).map(|(a, b)| a + b);
+        merged.unmet_soft_constraints.extend(output.unmet_soft_constraints);
+        for summary in output.soft_constraint_summary {
+            let existing = merged
+                .soft_constraint_summary
+                .iter_mut()
+                .find(|s| s.constraint_type == summary.constraint_type);
+            match existing {
+                Some(merged_summary) => merged_summary.add(summary.met, summary.total),
+                None => merged.soft_constraint_summary.push(summary),
+            }
+        }
+        merged.solve_status.proven_optimal &= output.solve_status.proven_optimal;
+        if output.solve_status.method == SolveMethod::Heuristic {
+            merged.solve_status.method = SolveMethod::Heuristic;
+        }
+        merged.target_deviations.extend(output.target_deviations);
+        merged.department_spans.extend(output.department_spans);
+        merged.adjacencies.extend(output.adjacencies);
+        merged.instructor_latest_ends.extend(output.instructor_latest_ends);
+        merged.instructor_room_distances.extend(output.instructor_room_distances);
+        merged.instructor_idle_slots.extend(output.instructor_idle_slots);
+        merged.cohort_room_changes.extend(output.cohort_room_changes);
+        merged.instructors_used.extend(output.instructors_used);
+        if let Some(changes) = output.warm_start_changes {
+            *merged.warm_start_changes.get_or_insert(0) += changes;
+        }
+        merged.cancelled_courses.extend(output.cancelled_courses);
+        merged.unscheduled_courses.extend(output.unscheduled_courses);
+        merged.makespan = merged.makespan.max(output.makespan);
+        merged.unavailability_overrides.extend(output.unavailability_overrides);
+        merged.reserved_rooms.extend(output.reserved_rooms);
+        merged.free_slots.extend(output.free_slots);
+        merged.binding_constraints.extend(output.binding_constraints);
+        merged.constraint_violations.extend(output.constraint_violations);
+        for sections in output.course_sections {
+            match merged.course_sections.iter_mut().find(|s| s.template_id == sections.template_id)
+            {
+                Some(merged_sections) => merged_sections.section_ids.extend(sections.section_ids),
+                None => merged.course_sections.push(sections),
+            }
+        }
+        if let (Some(total), Some(stats)) = (&merged.prefilter_stats, output.prefilter_stats) {
+            merged.prefilter_stats = Some(PrefilterStats::new(
+                total.candidates + stats.candidates,
+                total.theoretical_max + stats.theoretical_max,
+            ));
+        }
+        for (total, count) in merged.courses_per_day.iter_mut().zip(output.courses_per_day) {
+            *total += count;
+        }
+        if let (Some(total), Some(breakdown)) =
+            (&mut merged.objective_breakdown, output.objective_breakdown)
+        {
+            for (name, value) in breakdown {
+                *total.entry(name).or_insert(0.0) += value;
+            }
+        }
+    }
+    merged.assignments.sort();
+    merged.cancelled_courses.sort_unstable();
+    merged.unscheduled_courses.sort_unstable();
+    merged.reserved_rooms.sort_by_key(|usage| usage.room_id);
+    merged.instructor_room_distances.sort_by_key(|distance| distance.instructor_id);
+    merged.instructor_idle_slots.sort_by_key(|idle| idle.instructor_id);
+    merged.cohort_room_changes.sort_by_key(|changes| (changes.student_group_id, changes.day));
+    merged.free_slots.sort_by_key(|free| free.room_id);
+    merged.instructors_used.sort_unstable();
+    merged.course_sections.sort_by_key(|sections| sections.template_id);
+    for sections in &mut merged.course_sections {
+        sections.section_ids.sort_unstable();
+    }
+    merged
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parent[root_b] = root_a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{possible_assignments, solve};
+    use serde_json::json;
+
+    /// Courses 1-3 run on day 0 in room 1, courses 4-6 on day 1 in room 2; they share nothing.
+    fn separable() -> SchedulingInput {
+        let course = |id: u32, instructor_id: u32, day: u32| {
+            json!({
+                "id": id, "requiredCapacity": 10, "durationSlots": if id % 3 == 1 { 2 } else { 1 },
+                "instructorIds": [instructor_id], "requiredDay": day
+            })
+        };
+        let instructors = [1, 2, 3, 4].map(|id| json!({"id": id, "unavailableSlots": []}));
+        let whole_day = |day: u32| (0..4).map(|period| [day, period]).collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "recurringUnavailable": whole_day(1)},
+                {"id": 2, "capacity": 30, "recurringUnavailable": whole_day(0)}
+            ],
+            "instructors": instructors,
+            "courses": [
+                course(1, 1, 0), course(2, 1, 0), course(3, 2, 0),
+                course(4, 3, 1), course(5, 4, 1), course(6, 4, 1)
+            ],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn decomposed_and_monolithic_solves_agree_on_a_separable_instance() {
+        let mut input = separable();
+        input.explain_objective = true;
+        let parts = split_components(&input, &possible_assignments(&input)).unwrap();
+        let course_ids = |part: &SchedulingInput| part.courses.iter().map(|c| c.id.0).collect();
+        let mut parts: Vec<Vec<u32>> = parts.iter().map(course_ids).collect();
+        parts.sort();
+        assert_eq!(parts, [vec![1, 2, 3], vec![4, 5, 6]]);
+
+        let monolithic = solve(&input).unwrap();
+        input.decompose = true;
+        let decomposed = solve(&input).unwrap();
+        let objective = |output: &SchedulingOutput| -> f64 {
+            output.objective_breakdown.as_ref().unwrap().values().sum()
+        };
+        assert!((objective(&decomposed) - objective(&monolithic)).abs() < 1e-6);
+        assert_eq!(decomposed.score, monolithic.score);
+        assert_eq!(decomposed.assignments.len(), 6);
+    }
+
+    #[test]
+    fn a_coupling_setting_keeps_the_input_whole() {
+        let mut input = separable();
+        input.min_courses_per_active_slot = Some(2);
+        assert!(split_components(&input, &possible_assignments(&input)).is_none());
+    }
+}
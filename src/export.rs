@@ -0,0 +1,509 @@
+use crate::calendar;
+use crate::data::{
+    group_by_instructor, to_occupancy, Assignment, CourseId, InstructorId, RoomId,
+    SchedulingInput, SchedulingOutput, Timeslot,
+};
+use itertools::Itertools;
+use jiff::civil::DateTime;
+use jiff::tz::TimeZone;
+use jiff::Span;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+
+const HEADER: [&str; 6] = ["Course", "Instructor", "Room", "Day", "Start", "End"];
+
+/// One row per assignment, resolved against the input: co-taught courses list every
+/// instructor, and with a day model Start and End are periods of the day (End exclusive).
+/// Without one, Day is blank and Start and End are absolute slots.
+pub fn build_rows(output: &SchedulingOutput, input: &SchedulingInput) -> Vec<[String; 6]> {
+    let input = input.with_durations_in_slots().with_chosen_instructors(&output.assignments);
+    output
+        .assignments
+        .iter()
+        .map(|a| {
+            let course = input.courses.iter().find(|c| c.id == a.course_id);
+            let instructors = course
+                .map(|c| c.instructors().iter().join(", "))
+                .unwrap_or_default();
+            let duration = course.map_or(1, |c| c.duration_slots);
+            let (day, start) = match input.periods_per_day {
+                Some(_) => (
+                    input.day_of(a.start_slot).to_string(),
+                    input.period_of(a.start_slot),
+                ),
+                None => (String::new(), a.start_slot),
+            };
+            [
+                a.course_id.to_string(),
+                instructors,
+                a.room_id.to_string(),
+                day,
+                start.to_string(),
+                (start + duration).to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// Comma-separated rows with a header line; fields holding commas or quotes are quoted.
+pub fn to_csv(output: &SchedulingOutput, input: &SchedulingInput) -> String {
+    to_delimited(output, input, ',', |field| {
+        if field.contains([',', '"', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    })
+}
+
+/// Tab-separated rows with a header line, ready to paste into a spreadsheet.
+pub fn to_tsv(output: &SchedulingOutput, input: &SchedulingInput) -> String {
+    // TSV has no quoting, so tabs and line breaks inside a field become spaces
+    to_delimited(output, input, '\t', |field| field.replace(['\t', '\n'], " "))
+}
+
+fn to_delimited(
+    output: &SchedulingOutput,
+    input: &SchedulingInput,
+    delimiter: char,
+    escape: impl Fn(&str) -> String,
+) -> String {
+    let header = HEADER.iter().map(|field| escape(field)).join(&delimiter.to_string());
+    let rows = build_rows(output, input)
+        .into_iter()
+        .map(|row| row.iter().map(|field| escape(field)).join(&delimiter.to_string()));
+    std::iter::once(header).chain(rows).map(|line| line + "\n").collect()
+}
+
+/// Start or end of a Gantt task: a local time when the calendar allows, a slot otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum GanttTime {
+    Slot(Timeslot),
+    Time(DateTime),
+    /// A time converted into a requested zone, with its UTC offset.
+    Zoned(String),
+}
+
+/// One bar of a Gantt chart, with the room as its lane.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GanttTask {
+    pub id: CourseId,
+    pub resource: RoomId,
+    pub start: GanttTime,
+    /// Exclusive.
+    pub end: GanttTime,
+    pub label: String,
+}
+
+/// One task per assignment, ordered by room and start. Start and end are slots (end
+/// exclusive) unless `slot_calendar` dates the first and last slot of the course and
+/// `slot_minutes` is set; then they are local times, the end being the last slot's start plus
+/// one slot.
+pub fn to_gantt(output: &SchedulingOutput, input: &SchedulingInput) -> Vec<GanttTask> {
+    let input = input.with_durations_in_slots();
+    let calendar: HashMap<Timeslot, DateTime> =
+        input.slot_calendar.iter().map(|entry| (entry.slot, entry.start)).collect();
+    output
+        .assignments
+        .iter()
+        .sorted_by_key(|a| (a.room_id, a.start_slot, a.course_id))
+        .map(|a| {
+            let course = input.courses.iter().find(|c| c.id == a.course_id);
+            let duration = course.map_or(1, |c| c.duration_slots);
+            let end_slot = a.start_slot + duration;
+            let (start, end) = match local_times(&input, &calendar, a.start_slot..end_slot) {
+                Some((start, end)) => (GanttTime::Time(start), GanttTime::Time(end)),
+                None => (GanttTime::Slot(a.start_slot), GanttTime::Slot(end_slot)),
+            };
+            GanttTask {
+                id: a.course_id,
+                resource: a.room_id,
+                start,
+                end,
+                label: a.label.clone().unwrap_or_else(|| format!("Course {}", a.course_id)),
+            }
+        })
+        .collect()
+}
+
+// local start and end of a course running in `slots`, the end being the last slot's start plus
+// one slot; `None` unless the calendar dates both and `slot_minutes` is set
+fn local_times(
+    input: &SchedulingInput,
+    calendar: &HashMap<Timeslot, DateTime>,
+    slots: Range<Timeslot>,
+) -> Option<(DateTime, DateTime)> {
+    let minutes = input.slot_minutes?;
+    let start = calendar.get(&slots.start)?;
+    let last = calendar.get(&(slots.end - 1))?;
+    let end = last.checked_add(Span::new().minutes(minutes)).ok()?;
+    Some((*start, end))
+}
+
+/// An input and, optionally, a schedule already solved for it, to take one instructor's
+/// timetable from.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructorTimetableRequest {
+    pub input: SchedulingInput,
+    /// The schedule to read; the input is solved when omitted.
+    #[serde(default)]
+    pub assignments: Option<Vec<Assignment>>,
+}
+
+/// One class of an instructor's timetable, with where and when it takes place.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimetableEntry {
+    pub course_id: CourseId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub room_id: RoomId,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub combined_rooms: Vec<RoomId>,
+    pub start_slot: Timeslot,
+    /// Exclusive.
+    pub end_slot: Timeslot,
+    /// Day and periods of the day (end exclusive); only with a day model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_period: Option<u32>,
+    /// Local times, when the calendar dates the course as for [`to_gantt`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime>,
+}
+
+/// The classes `instructor_id` teaches in `assignments`, ordered by start slot. Courses that
+/// picked the instructor from their eligible ones count like those they are fixed to.
+pub fn instructor_timetable(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    instructor_id: InstructorId,
+) -> Vec<TimetableEntry> {
+    let input = input.with_durations_in_slots();
+    let calendar: HashMap<Timeslot, DateTime> =
+        input.slot_calendar.iter().map(|entry| (entry.slot, entry.start)).collect();
+    let mut by_instructor = group_by_instructor(assignments, &input);
+    by_instructor
+        .remove(&instructor_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| {
+            let course = input.courses.iter().find(|c| c.id == a.course_id);
+            let duration = course.map_or(1, |c| c.duration_slots);
+            let end_slot = a.start_slot + duration;
+            let period = input.periods_per_day.map(|_| input.period_of(a.start_slot));
+            let times = local_times(&input, &calendar, a.start_slot..end_slot);
+            TimetableEntry {
+                course_id: a.course_id,
+                label: a.label,
+                room_id: a.room_id,
+                combined_rooms: a.combined_rooms,
+                start_slot: a.start_slot,
+                end_slot,
+                day: input.periods_per_day.map(|_| input.day_of(a.start_slot)),
+                start_period: period,
+                end_period: period.map(|period| period + duration),
+                start: times.map(|(start, _)| start),
+                end: times.map(|(_, end)| end),
+            }
+        })
+        .collect()
+}
+
+/// Converts the local times of `tasks` from the zone of the input's slot calendar into `to`.
+/// Slots stay slots.
+pub fn localize_gantt(tasks: &mut [GanttTask], input: &SchedulingInput, to: &TimeZone) {
+    let from = calendar::input_time_zone(input);
+    for task in tasks {
+        for time in [&mut task.start, &mut task.end] {
+            if let GanttTime::Time(local) = time
+                && let Some(zoned) = calendar::convert(*local, &from, to)
+            {
+                *time = GanttTime::Zoned(zoned);
+            }
+        }
+    }
+}
+
+/// Width and height in pixels of one timeslot of one room in [`render_png`], shrunk for long
+/// horizons or many rooms down to [`MIN_CELL_SIZE`].
+const CELL_SIZE: usize = 24;
+const MIN_CELL_SIZE: usize = 4;
+/// Longest side of a rendered timetable in pixels, which bounds its memory to about 50 MB.
+const MAX_IMAGE_SIDE: usize = 4096;
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const GRID_LINE: [u8; 3] = [210, 210, 210];
+const DAY_LINE: [u8; 3] = [90, 90, 90];
+/// Colours of courses without a usable `color`, picked by course id.
+const PALETTE: [[u8; 3]; 10] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [127, 127, 127],
+    [188, 189, 34],
+    [23, 190, 207],
+];
+
+/// Side in pixels of one cell of the PNG timetable of `input`: [`CELL_SIZE`] when the grid
+/// fits in [`MAX_IMAGE_SIDE`], smaller when it does not, and an error when even
+/// [`MIN_CELL_SIZE`] cells would not fit.
+pub fn png_cell_size(input: &SchedulingInput) -> Result<usize, String> {
+    let cells = (input.total_timeslots as usize).max(input.rooms.len()).max(1);
+    let cell_size = ((MAX_IMAGE_SIDE - 1) / cells).min(CELL_SIZE);
+    if cell_size < MIN_CELL_SIZE {
+        let most = (MAX_IMAGE_SIDE - 1) / MIN_CELL_SIZE;
+        return Err(format!(
+            "{} timeslot(s) and {} room(s) are too many to draw; a PNG timetable fits at most {} \
+             of each.",
+            input.total_timeslots,
+            input.rooms.len(),
+            most
+        ));
+    }
+    Ok(cell_size)
+}
+
+/// The schedule as a rooms × timeslots grid in PNG format: one row per room in id order, one
+/// column per slot, and a coloured block for every course over the slots it runs in. Courses
+/// keep their `color` when it is a "#rrggbb" value; day boundaries are drawn darker. Fails as
+/// [`png_cell_size`] does for a grid too large to draw.
+pub fn render_png(output: &SchedulingOutput, input: &SchedulingInput) -> Result<Vec<u8>, String> {
+    let cell_size = png_cell_size(input)?;
+    let occupancy = to_occupancy(output, input);
+    let columns = occupancy.total_timeslots.max(1) as usize;
+    let rows = occupancy.rooms.len().max(1);
+    let mut canvas = Canvas::new(columns * cell_size + 1, rows * cell_size + 1);
+    for column in 0..=columns {
+        let day_boundary = input.periods_per_day.is_some()
+            && (column as u32).is_multiple_of(input.slots_per_day());
+        let colour = if day_boundary { DAY_LINE } else { GRID_LINE };
+        canvas.fill(column * cell_size, 0, 1, canvas.height, colour);
+    }
+    for row in 0..=rows {
+        canvas.fill(0, row * cell_size, canvas.width, 1, GRID_LINE);
+    }
+    for (row, room) in occupancy.rooms.iter().enumerate() {
+        let mut column = 0;
+        for (course_id, length) in &room.runs {
+            let length = *length as usize;
+            if let Some(course_id) = course_id {
+                let course = input.courses.iter().find(|c| c.id == *course_id);
+                let colour = course
+                    .and_then(|c| c.color.as_deref())
+                    .and_then(parse_hex_colour)
+                    .unwrap_or(PALETTE[course_id.0 as usize % PALETTE.len()]);
+                // inset so the grid and the border between two courses stay visible
+                canvas.fill(
+                    column * cell_size + 2,
+                    row * cell_size + 2,
+                    length * cell_size - 3,
+                    cell_size - 3,
+                    colour,
+                );
+            }
+            column += length;
+        }
+    }
+    Ok(canvas.to_png())
+}
+
+/// "#rrggbb" as its red, green and blue components.
+fn parse_hex_colour(colour: &str) -> Option<[u8; 3]> {
+    let hex = colour.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let component = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some([component(0)?, component(2)?, component(4)?])
+}
+
+/// An RGB image that only knows how to fill rectangles.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: BACKGROUND.repeat(width * height),
+        }
+    }
+
+    /// Fills the rectangle at (`x`, `y`), clipped to the canvas.
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, colour: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                let offset = (row * self.width + column) * 3;
+                self.pixels[offset..offset + 3].copy_from_slice(&colour);
+            }
+        }
+    }
+
+    /// Encodes the canvas as an 8-bit RGB PNG.
+    fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // the canvas always matches its header, and writing to a Vec cannot fail
+        let mut writer = encoder.write_header().expect("a PNG header writes to memory");
+        writer.write_image_data(&self.pixels).expect("the pixels match the PNG header");
+        writer.finish().expect("a PNG finishes in memory");
+        png
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(value: serde_json::Value) -> SchedulingInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// A co-taught 2-slot course held at slot 5 and a single-slot course held at slot 0.
+    fn two_courses(periods_per_day: Option<u32>) -> SchedulingInput {
+        input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [
+                {"id": 1, "unavailableSlots": []}, {"id": 2, "unavailableSlots": []}
+            ],
+            "courses": [
+                {
+                    "id": 1, "requiredCapacity": 10, "durationSlots": 2,
+                    "instructorIds": [1, 2], "targetSlot": 5, "targetWeight": 10.0
+                },
+                {
+                    "id": 2, "requiredCapacity": 10, "durationSlots": 1,
+                    "instructorIds": [2], "targetSlot": 0, "targetWeight": 10.0
+                }
+            ],
+            "totalTimeslots": 8,
+            "periodsPerDay": periods_per_day
+        }))
+    }
+
+    fn solved(input: &SchedulingInput) -> SchedulingOutput {
+        let mut output = crate::solver::solve(input).unwrap();
+        output.assignments.sort();
+        output
+    }
+
+    #[test]
+    fn tsv_has_the_header_in_order_and_tabs_between_fields() {
+        let input = two_courses(None);
+        let tsv = to_tsv(&solved(&input), &input);
+        let lines: Vec<&str> = tsv.lines().collect();
+        // no day model, so Day is blank and Start and End are slots
+        assert_eq!(
+            lines,
+            [
+                "Course\tInstructor\tRoom\tDay\tStart\tEnd",
+                "1\t1, 2\t1\t\t5\t7",
+                "2\t2\t1\t\t0\t1"
+            ]
+        );
+
+        let input = two_courses(Some(4));
+        let tsv = to_tsv(&solved(&input), &input);
+        assert_eq!(tsv.lines().nth(1), Some("1\t1, 2\t1\t1\t1\t3"));
+        // the same rows as CSV, where the instructor list needs quoting
+        let csv = to_csv(&solved(&input), &input);
+        assert_eq!(csv.lines().nth(1), Some("1,\"1, 2\",1,1,1,3"));
+    }
+
+    #[test]
+    fn a_multi_slot_course_spans_its_slots_in_the_gantt_chart() {
+        let mut input = two_courses(None);
+        let output = solved(&input);
+        let tasks = to_gantt(&output, &input);
+        let spans: Vec<_> =
+            tasks.iter().map(|t| (t.id, t.start.clone(), t.end.clone())).collect();
+        assert_eq!(
+            spans,
+            [
+                (CourseId(2), GanttTime::Slot(0), GanttTime::Slot(1)),
+                (CourseId(1), GanttTime::Slot(5), GanttTime::Slot(7))
+            ]
+        );
+        assert_eq!(tasks[1].label, "Course 1");
+
+        // with a calendar, the course ends one slot after the start of its last slot
+        input.slot_minutes = Some(50);
+        input.slot_calendar = serde_json::from_value(json!([
+            {"slot": 5, "start": "2025-03-04T09:00"},
+            {"slot": 6, "start": "2025-03-04T10:00"}
+        ]))
+        .unwrap();
+        let tasks = to_gantt(&output, &input);
+        assert_eq!(tasks[0].start, GanttTime::Slot(0));
+        let time = |text: &str| GanttTime::Time(text.parse().unwrap());
+        assert_eq!(tasks[1].start, time("2025-03-04T09:00"));
+        assert_eq!(tasks[1].end, time("2025-03-04T10:50"));
+    }
+
+    #[test]
+    fn the_timetable_renders_as_a_valid_png() {
+        let mut input = two_courses(None);
+        input.courses[0].color = Some("#ff0000".to_string());
+        let png = render_png(&solved(&input), &input).unwrap();
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(frame.color_type, png::ColorType::Rgb);
+        assert_eq!(frame.bit_depth, png::BitDepth::Eight);
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        assert_eq!((width, height), (8 * CELL_SIZE + 1, CELL_SIZE + 1));
+        // the middle of slot 5, where course 1 starts, takes its colour
+        let (x, y) = (5 * CELL_SIZE + CELL_SIZE / 2, CELL_SIZE / 2);
+        let offset = (y * width + x) * 3;
+        assert_eq!(pixels[offset..offset + 3], [0xff, 0, 0]);
+    }
+
+    #[test]
+    fn a_long_horizon_shrinks_the_cells_until_it_no_longer_fits() {
+        let mut input = two_courses(None);
+        assert_eq!(png_cell_size(&input), Ok(CELL_SIZE));
+        input.total_timeslots = 1000;
+        assert_eq!(png_cell_size(&input), Ok(4));
+        input.total_timeslots = 2000;
+        assert!(png_cell_size(&input).unwrap_err().contains("at most 1023"));
+        assert!(render_png(&solved(&two_courses(None)), &input).is_err());
+    }
+
+    #[test]
+    fn an_instructor_timetable_holds_only_their_courses_in_slot_order() {
+        let input = two_courses(Some(4));
+        let output = solved(&input);
+        let courses = |instructor_id| {
+            instructor_timetable(&output.assignments, &input, InstructorId(instructor_id))
+                .iter()
+                .map(|entry| entry.course_id.0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(courses(1), [1]);
+        assert_eq!(courses(2), [2, 1]);
+        assert!(courses(3).is_empty());
+
+        let timetable = instructor_timetable(&output.assignments, &input, InstructorId(1));
+        let entry = &timetable[0];
+        assert_eq!((entry.room_id, entry.start_slot, entry.end_slot), (RoomId(1), 5, 7));
+        assert_eq!((entry.day, entry.start_period, entry.end_period), (Some(1), Some(1), Some(3)));
+    }
+}
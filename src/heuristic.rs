@@ -0,0 +1,312 @@
+use crate::data::{
+    Assignment, Course, CourseId, Instructor, InstructorId, RoomId, SchedulingInput, SectionLink,
+    Timeslot,
+};
+use crate::solver::{possible_assignments, reported_room};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Builds a schedule one course at a time without looking at the objective.
+///
+/// The most constrained courses (fewest candidates, then longest) go first, each into its
+/// earliest free candidate, preferring the smallest room that fits and slots that need no
+/// instructor override. A course with eligible instructors goes to the first of them who is
+/// free. Labs of section links wait for their lectures. Every hard constraint is respected;
+/// optional courses that do not fit are left out.
+/// Returns `None` when a mandatory course cannot be placed.
+pub fn greedy_solve(input: &SchedulingInput) -> Option<Vec<Assignment>> {
+    let input = input.with_durations_in_slots();
+    let mut candidates: HashMap<CourseId, Vec<(RoomId, Timeslot)>> = HashMap::new();
+    for (c_id, r_id, start_slot) in possible_assignments(&input) {
+        candidates.entry(c_id).or_default().push((r_id, start_slot));
+    }
+    let combined_rooms = input.combined_rooms();
+    let room_capacity: HashMap<RoomId, u32> = input
+        .rooms
+        .iter()
+        .chain(combined_rooms.iter().map(|(room, _)| room))
+        .map(|r| (r.id, r.capacity))
+        .collect();
+    let room_members: HashMap<RoomId, Vec<RoomId>> =
+        combined_rooms.iter().map(|(room, room_ids)| (room.id, room_ids.clone())).collect();
+    // the real rooms each candidate room occupies
+    let occupied_rooms: HashMap<RoomId, Vec<RoomId>> = room_capacity
+        .keys()
+        .map(|r_id| (*r_id, room_members.get(r_id).cloned().unwrap_or_else(|| vec![*r_id])))
+        .collect();
+    // student groups and conflicting tags both forbid their courses to overlap
+    let conflict_sets: Vec<Vec<CourseId>> = input
+        .student_groups
+        .iter()
+        .map(|g| g.course_ids.clone())
+        .chain(input.tag_conflicts.iter().map(|tag| {
+            input.courses.iter().filter(|c| c.tags.contains(tag)).map(|c| c.id).collect()
+        }))
+        .collect();
+    let mut start_caps: HashMap<Timeslot, u32> = HashMap::new();
+    for (slot, max_starts) in input.max_courses_in_slot.iter().flatten() {
+        let cap = start_caps.entry(*slot).or_insert(*max_starts);
+        *cap = (*cap).min(*max_starts);
+    }
+
+    let frozen_placements = input.frozen_placements();
+
+    let mut courses: Vec<&Course> = input.courses.iter().filter(|c| !c.is_cancelled()).collect();
+    let links: Vec<&SectionLink> = input
+        .section_links
+        .iter()
+        .filter(|link| {
+            let scheduled = |c_id: CourseId| courses.iter().any(|c| c.id == c_id);
+            scheduled(link.lecture) && scheduled(link.lab)
+        })
+        .collect();
+    courses.sort_by_key(|c| {
+        let options = candidates.get(&c.id).map_or(0, Vec::len);
+        let is_lab = links.iter().any(|link| link.lab == c.id);
+        (c.optional, is_lab, options, Reverse(c.duration_slots), c.id)
+    });
+
+    let mut room_busy: HashSet<(RoomId, Timeslot)> = HashSet::new();
+    let mut instructor_busy: HashSet<(InstructorId, Timeslot)> = HashSet::new();
+    let mut conflict_busy: HashSet<(usize, Timeslot)> = HashSet::new();
+    let mut taught: HashMap<InstructorId, u32> = HashMap::new();
+    let mut group_taught: HashSet<(usize, InstructorId)> = HashSet::new();
+    let mut running = vec![0; input.total_timeslots as usize];
+    let mut started: HashMap<Timeslot, u32> = HashMap::new();
+    let mut back_to_back_pairs = 0;
+    let mut placed: HashMap<CourseId, Range<Timeslot>> = HashMap::new();
+    let mut assignments = Vec::new();
+    for course in courses {
+        // a course that picks its instructor tries each eligible one in turn
+        let frozen_instructor =
+            frozen_placements.get(&course.id).and_then(|placement| placement.instructor_id);
+        let staffings: Vec<Vec<&Instructor>> = match &course.eligible_instructors {
+            Some(_) => input
+                .instructors
+                .iter()
+                .filter(|i| course.possible_instructors().contains(&i.id))
+                .filter(|i| frozen_instructor.is_none_or(|frozen| frozen == i.id))
+                .map(|i| vec![i])
+                .collect(),
+            None => vec![
+                input.instructors.iter().filter(|i| course.instructors().contains(&i.id)).collect(),
+            ],
+        };
+        let sets: Vec<usize> = conflict_sets
+            .iter()
+            .enumerate()
+            .filter(|(_, course_ids)| course_ids.contains(&course.id))
+            .map(|(index, _)| index)
+            .collect();
+        let distinct_groups: Vec<usize> = input
+            .distinct_instructor_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, course_ids)| course_ids.contains(&course.id))
+            .map(|(index, _)| index)
+            .collect();
+        let at_course_cap = |instructors: &[&Instructor]| {
+            instructors.iter().any(|i| {
+                i.max_courses.is_some_and(|max| taught.get(&i.id).copied().unwrap_or(0) >= max)
+                    || distinct_groups.iter().any(|group| group_taught.contains(&(*group, i.id)))
+            })
+        };
+
+        let mut options: Vec<(RoomId, Timeslot, &Vec<&Instructor>)> = candidates
+            .remove(&course.id)
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(r_id, start_slot)| {
+                staffings.iter().map(move |instructors| (r_id, start_slot, instructors))
+            })
+            .collect();
+        // without overrides, candidates only guarantee that one eligible instructor is free
+        options.retain(|(_, start_slot, instructors)| {
+            instructors.iter().all(|i| i.within_hours(&input, *start_slot, course.duration_slots))
+                && (input.allow_override_unavailability
+                    || !needs_override(instructors, *start_slot, course))
+        });
+        // rooms that are too small are only candidates when capacity is relaxed, and a last resort
+        options.sort_by_key(|(r_id, start_slot, instructors)| {
+            (
+                room_capacity[r_id] < course.required_capacity,
+                needs_override(instructors, *start_slot, course),
+                *start_slot,
+                room_capacity[r_id],
+                *r_id,
+            )
+        });
+        let fits = |(r_id, start_slot, instructors): &(RoomId, Timeslot, &Vec<&Instructor>)| {
+            let slots = *start_slot..*start_slot + course.duration_slots;
+            let start_capped = start_caps
+                .get(start_slot)
+                .is_some_and(|max| started.get(start_slot).copied().unwrap_or(0) >= *max);
+            let free = !start_capped
+                && slots.clone().all(|k| {
+                    occupied_rooms[r_id].iter().all(|room_id| !room_busy.contains(&(*room_id, k)))
+                        && instructors.iter().all(|i| !instructor_busy.contains(&(i.id, k)))
+                        && sets.iter().all(|set| !conflict_busy.contains(&(*set, k)))
+                        && input.max_concurrent_courses.is_none_or(|max| running[k as usize] < max)
+                });
+            let linked = links.iter().all(|link| {
+                if link.lab == course.id {
+                    placed
+                        .get(&link.lecture)
+                        .is_some_and(|lecture| link.allows(&input, lecture.clone(), *start_slot))
+                } else if link.lecture == course.id {
+                    placed
+                        .get(&link.lab)
+                        .is_none_or(|lab| link.allows(&input, slots.clone(), lab.start))
+                } else {
+                    true
+                }
+            });
+            let day_start = input.slot_at(input.day_of(*start_slot), 0);
+            let day = day_start..day_start + input.slots_per_day();
+            let within_pair_cap = input.max_back_to_back_pairs.is_none_or(|max| {
+                let pairs = new_back_to_back_pairs(&input, &instructor_busy, instructors, &slots);
+                back_to_back_pairs + pairs <= max
+            });
+            free && linked && within_pair_cap && instructors.iter().all(|i| {
+                let busy = |k| instructor_busy.contains(&(i.id, k)) || slots.contains(&k);
+                i.max_consecutive_slots
+                    .is_none_or(|max| longest_run(day.clone(), busy) <= max)
+                    && i.max_daily_span.is_none_or(|max| daily_span(day.clone(), busy) <= max)
+                    && i.max_slots_per_day
+                        .is_none_or(|max| day.clone().filter(|k| busy(*k)).count() as u32 <= max)
+            })
+        };
+        let placement = options
+            .into_iter()
+            .find(|option| !at_course_cap(option.2) && fits(option));
+
+        let Some((room_id, start_slot, instructors)) = placement else {
+            if course.optional {
+                continue;
+            }
+            return None;
+        };
+        let slots = start_slot..start_slot + course.duration_slots;
+        back_to_back_pairs += new_back_to_back_pairs(&input, &instructor_busy, instructors, &slots);
+        for k in start_slot..start_slot + course.duration_slots {
+            for occupied in &occupied_rooms[&room_id] {
+                room_busy.insert((*occupied, k));
+            }
+            for instructor in instructors {
+                instructor_busy.insert((instructor.id, k));
+            }
+            for set in &sets {
+                conflict_busy.insert((*set, k));
+            }
+            running[k as usize] += 1;
+        }
+        *started.entry(start_slot).or_insert(0) += 1;
+        placed.insert(course.id, start_slot..start_slot + course.duration_slots);
+        for instructor in instructors {
+            *taught.entry(instructor.id).or_insert(0) += 1;
+            for group in &distinct_groups {
+                group_taught.insert((*group, instructor.id));
+            }
+        }
+        let (room_id, combined_rooms) = reported_room(&room_members, room_id);
+        assignments.push(Assignment {
+            course_id: course.id,
+            room_id,
+            start_slot,
+            instructor_id: course.eligible_instructors.as_ref().map(|_| instructors[0].id),
+            combined_rooms,
+            label: course.label.clone(),
+            color: course.color.clone(),
+        });
+    }
+
+    // placing courses one by one cannot aim for these, so only check them
+    if let Some(min_courses) = input.min_courses_per_active_slot
+        && running.iter().any(|count| *count > 0 && *count < min_courses)
+    {
+        return None;
+    }
+    if let Some(min_courses) = input.min_courses_per_active_zone {
+        let mut zone_running: HashMap<(u32, Timeslot), u32> = HashMap::new();
+        for assignment in &assignments {
+            let zone = input.rooms.iter().find(|r| r.id == assignment.room_id).and_then(|r| r.zone);
+            let duration = input
+                .courses
+                .iter()
+                .find(|c| c.id == assignment.course_id)
+                .map_or(1, |c| c.duration_slots);
+            if let Some(zone) = zone {
+                for k in assignment.start_slot..assignment.start_slot + duration {
+                    *zone_running.entry((zone, k)).or_insert(0) += 1;
+                }
+            }
+        }
+        if zone_running.values().any(|count| *count < min_courses) {
+            return None;
+        }
+    }
+    if input.require_all_rooms_used
+        && input.rooms.iter().any(|r| !assignments.iter().any(|a| a.rooms().contains(&r.id)))
+    {
+        return None;
+    }
+    assignments.sort();
+    Some(assignments)
+}
+
+/// Whether any of `instructors` is unavailable while `course` runs from `start_slot`.
+fn needs_override(instructors: &[&Instructor], start_slot: Timeslot, course: &Course) -> bool {
+    let slots = start_slot..start_slot + course.duration_slots;
+    instructors.iter().any(|i| slots.clone().any(|k| i.unavailable_slots.contains(&k)))
+}
+
+/// Slots from the first busy slot of `day` to the end of its last one; 0 for a free day.
+fn daily_span(day: Range<Timeslot>, busy: impl Fn(Timeslot) -> bool) -> u32 {
+    let first = day.clone().find(|k| busy(*k));
+    let last = day.rev().find(|k| busy(*k));
+    match (first, last) {
+        (Some(first), Some(last)) => last + 1 - first,
+        _ => 0,
+    }
+}
+
+/// Back-to-back pairs a course running in `slots` would add: every instructor busy right
+/// before or right after it on the same day. Their classes never overlap, so a busy slot next
+/// to the course belongs to a class that ends or starts there.
+fn new_back_to_back_pairs(
+    input: &SchedulingInput,
+    instructor_busy: &HashSet<(InstructorId, Timeslot)>,
+    instructors: &[&Instructor],
+    slots: &Range<Timeslot>,
+) -> u32 {
+    let day = input.day_of(slots.start);
+    let mut neighbours = vec![slots.end];
+    if slots.start > 0 {
+        neighbours.push(slots.start - 1);
+    }
+    neighbours.retain(|k| *k < input.total_timeslots && input.day_of(*k) == day);
+    let mut pairs = 0;
+    for instructor in instructors {
+        for k in &neighbours {
+            if instructor_busy.contains(&(instructor.id, *k)) {
+                pairs += 1;
+            }
+        }
+    }
+    pairs
+}
+
+/// Length of the longest stretch of consecutive busy slots of `day`.
+fn longest_run(day: Range<Timeslot>, busy: impl Fn(Timeslot) -> bool) -> u32 {
+    let (mut longest, mut current) = (0, 0);
+    for k in day {
+        if busy(k) {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
@@ -0,0 +1,145 @@
+use good_lp::solvers::highs::{HighsOptionValue, HighsProblem};
+use good_lp::{ResolutionError, Solution, SolutionStatus, Variable};
+use highs::HighsModelStatus;
+use highs_sys::HighsInt;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// `kHighsUndefined` of the HiGHS C API: a start value left for HiGHS to fill in.
+const UNDEFINED: f64 = f64::INFINITY;
+
+/// `kHighsCallbackMipImprovingSolution` of the HiGHS C API.
+const MIP_IMPROVING_SOLUTION: HighsInt = 4;
+
+type HighsCallback =
+    unsafe extern "C" fn(c_int, *const c_char, *const c_void, *mut c_void, *mut c_void);
+
+/// Called with the value of every variable of an improving solution.
+pub type ReportIncumbent<'a> = &'a mut dyn FnMut(&HashMap<Variable, f64>);
+
+// callback functions of the HiGHS C API; highs-sys does not bind them, but the library it
+// links has them
+unsafe extern "C" {
+    fn Highs_setCallback(
+        highs: *mut c_void,
+        user_callback: Option<HighsCallback>,
+        user_callback_data: *mut c_void,
+    ) -> HighsInt;
+    fn Highs_startCallback(highs: *mut c_void, callback_type: HighsInt) -> HighsInt;
+    fn Highs_getCallbackDataOutItem(data_out: *const c_void, item_name: *const c_char)
+    -> *const c_void;
+}
+
+/// Status and variable values of a finished HiGHS run; reads like any `good_lp` solution.
+pub struct HighsOutcome {
+    status: SolutionStatus,
+    values: HashMap<Variable, f64>,
+}
+
+impl Solution for HighsOutcome {
+    fn status(&self) -> SolutionStatus {
+        self.status
+    }
+
+    fn value(&self, variable: Variable) -> f64 {
+        self.values[&variable]
+    }
+}
+
+/// What HiGHS hands back to [`on_improving_solution`].
+struct IncumbentCallback<'a> {
+    columns: &'a [Variable],
+    report: ReportIncumbent<'a>,
+}
+
+/// Solves `problem` the way `good_lp` does, but calls `on_incumbent` with the variable values
+/// of every improving MIP solution while HiGHS is still searching. `columns` are the
+/// variables of `problem` in column order (see
+/// [`ModelBuilder::columns`](crate::model::ModelBuilder::columns)).
+///
+/// `good_lp` keeps options and an initial solution to itself and offers no callback, so both
+/// are passed here instead of being set on `problem`. Columns missing from
+/// `initial_solution` are left undefined: HiGHS fixes the given ones and completes the rest
+/// with a small MIP, and drops the start if the given values cannot be completed.
+pub fn run_highs(
+    problem: HighsProblem,
+    columns: &[Variable],
+    options: Vec<(&str, HighsOptionValue)>,
+    initial_solution: Option<&[(Variable, f64)]>,
+    on_incumbent: Option<ReportIncumbent>,
+) -> Result<HighsOutcome, ResolutionError> {
+    let mut model = problem.into_inner();
+    for (option, value) in options {
+        match value {
+            HighsOptionValue::String(value) => model.set_option(option, value.as_str()),
+            HighsOptionValue::Float(value) => model.set_option(option, value),
+            HighsOptionValue::Bool(value) => model.set_option(option, value),
+            HighsOptionValue::Int(value) => model.set_option(option, value),
+        }
+    }
+    if let Some(initial_solution) = initial_solution {
+        let initial: HashMap<Variable, f64> = initial_solution.iter().copied().collect();
+        let values: Vec<f64> =
+            columns.iter().map(|var| initial.get(var).copied().unwrap_or(UNDEFINED)).collect();
+        model.set_solution(Some(&values), None, None, None);
+    }
+    let mut callback = on_incumbent.map(|report| IncumbentCallback { columns, report });
+    if let Some(callback) = &mut callback {
+        // SAFETY: `callback` outlives the solve below, the only time HiGHS calls back into it
+        unsafe {
+            let data = callback as *mut IncumbentCallback as *mut c_void;
+            Highs_setCallback(model.as_mut_ptr(), Some(on_improving_solution), data);
+            Highs_startCallback(model.as_mut_ptr(), MIP_IMPROVING_SOLUTION);
+        }
+    }
+
+    let solved = model.try_solve().map_err(|_| ResolutionError::Other("SolveError"))?;
+    let status = match solved.status() {
+        HighsModelStatus::Infeasible | HighsModelStatus::UnboundedOrInfeasible => {
+            return Err(ResolutionError::Infeasible);
+        }
+        HighsModelStatus::Unbounded => return Err(ResolutionError::Unbounded),
+        HighsModelStatus::NotSet => return Err(ResolutionError::Other("NotSet")),
+        HighsModelStatus::LoadError => return Err(ResolutionError::Other("LoadError")),
+        HighsModelStatus::ModelError => return Err(ResolutionError::Other("ModelError")),
+        HighsModelStatus::PresolveError => return Err(ResolutionError::Other("PresolveError")),
+        HighsModelStatus::SolveError => return Err(ResolutionError::Other("SolveError")),
+        HighsModelStatus::PostsolveError => return Err(ResolutionError::Other("PostsolveError")),
+        HighsModelStatus::ModelEmpty => return Err(ResolutionError::Other("ModelEmpty")),
+        HighsModelStatus::ReachedTimeLimit => SolutionStatus::TimeLimit,
+        _ if solved.mip_gap().is_finite() && solved.mip_gap() > 0.0 => SolutionStatus::GapLimit,
+        _ => SolutionStatus::Optimal,
+    };
+    let values = solved.get_solution().columns().to_vec();
+    Ok(HighsOutcome {
+        status,
+        values: columns.iter().copied().zip(values).collect(),
+    })
+}
+
+unsafe extern "C" fn on_improving_solution(
+    callback_type: c_int,
+    _message: *const c_char,
+    data_out: *const c_void,
+    _data_in: *mut c_void,
+    user_data: *mut c_void,
+) {
+    if callback_type != MIP_IMPROVING_SOLUTION || user_data.is_null() {
+        return;
+    }
+    // SAFETY: user_data is the `IncumbentCallback` registered in `run_highs`, and HiGHS
+    // passes the improving solution with one value per column
+    let (callback, solution) = unsafe {
+        let callback = &mut *(user_data as *mut IncumbentCallback);
+        let solution = Highs_getCallbackDataOutItem(data_out, c"mip_solution".as_ptr());
+        if solution.is_null() {
+            return;
+        }
+        let solution = std::slice::from_raw_parts(solution as *const f64, callback.columns.len());
+        (callback, solution)
+    };
+    let values = callback.columns.iter().copied().zip(solution.iter().copied()).collect();
+    // a panic must not unwind into HiGHS; losing one incumbent is harmless
+    let _ = catch_unwind(AssertUnwindSafe(|| (callback.report)(&values)));
+}
@@ -0,0 +1,388 @@
+use crate::data::{SchedulingInput, SchedulingOutput};
+use crate::solver::{self, IncumbentSink};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+
+pub type JobId = u64;
+
+/// How often the HiGHS log files of a running job are checked for new lines.
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    /// Waiting for a free solver slot.
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// A solve submitted through the job API.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: JobId,
+    pub status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<SchedulingOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct JobEntry {
+    job: Job,
+    abort: Option<AbortHandle>,
+    /// HiGHS log lines captured so far.
+    log: Vec<String>,
+    /// Best schedule the running solve has reported so far.
+    incumbent: Option<SchedulingOutput>,
+}
+
+/// In-memory registry of submitted jobs. At most `max_concurrent` solves run at a time; the
+/// rest wait in `Pending`.
+#[derive(Clone)]
+pub struct JobStore {
+    jobs: Arc<Mutex<HashMap<JobId, JobEntry>>>,
+    next_id: Arc<AtomicU64>,
+    solver_slots: Arc<Semaphore>,
+}
+
+impl JobStore {
+    pub fn new(max_concurrent: usize) -> Self {
+        JobStore {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            solver_slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Queues a solve and returns the pending job straight away.
+    pub fn submit(&self, input: SchedulingInput) -> Job {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let job = Job {
+            id,
+            status: JobStatus::Pending,
+            output: None,
+            error: None,
+        };
+        // held until the abort handle is stored, so the task can't observe a half-made entry
+        let mut jobs = self.jobs.lock().unwrap();
+        let store = self.clone();
+        let task = tokio::spawn(async move {
+            let Ok(permit) = store.solver_slots.clone().acquire_owned().await else {
+                return;
+            };
+            store.update(id, |job| job.status = JobStatus::Running);
+            let log_dir = LogDir::create(id);
+            let solve_log_dir = log_dir.0.clone();
+            let incumbent_store = store.clone();
+            let on_incumbent: IncumbentSink =
+                Arc::new(move |output| incumbent_store.set_incumbent(id, output));
+            // the permit moves into the blocking solve so an aborted job still holds its slot
+            // until HiGHS actually returns
+            let mut solve = tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                solver::solve_logged(&input, &solve_log_dir, on_incumbent)
+            });
+            let mut tail = LogTail::default();
+            let mut poll = tokio::time::interval(LOG_POLL_INTERVAL);
+            let result = loop {
+                tokio::select! {
+                    result = &mut solve => break result,
+                    _ = poll.tick() => store.append_log(id, tail.read_new_lines(&log_dir.0)),
+                }
+            };
+            let mut last_lines = tail.read_new_lines(&log_dir.0);
+            last_lines.extend(tail.finish());
+            store.append_log(id, last_lines);
+            store.update(id, |job| match result {
+                Ok(Ok(output)) => {
+                    job.status = JobStatus::Completed;
+                    job.output = Some(output);
+                }
+                Ok(Err(e)) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(format!("Solver task failed: {}", e));
+                }
+            });
+        });
+        jobs.insert(
+            id,
+            JobEntry {
+                job: job.clone(),
+                abort: Some(task.abort_handle()),
+                log: Vec::new(),
+                incumbent: None,
+            },
+        );
+        job
+    }
+
+    pub fn get(&self, id: JobId) -> Option<Job> {
+        self.jobs.lock().unwrap().get(&id).map(|entry| entry.job.clone())
+    }
+
+    /// The job together with its HiGHS log lines from index `from` on.
+    pub fn log_since(&self, id: JobId, from: usize) -> Option<(Job, Vec<String>)> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(&id)?;
+        let lines = entry.log.get(from..).unwrap_or_default().to_vec();
+        Some((entry.job.clone(), lines))
+    }
+
+    /// The best schedule the job has found so far: the result of a completed job, the latest
+    /// improving schedule of a running one, or `None` while there is none yet. An incumbent
+    /// need not be optimal.
+    pub fn incumbent(&self, id: JobId) -> Option<Option<SchedulingOutput>> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(&id)?;
+        Some(entry.job.output.clone().or_else(|| entry.incumbent.clone()))
+    }
+
+    /// Cancels a pending or running job; finished jobs are returned unchanged.
+    ///
+    /// A pending job never starts. HiGHS cannot be interrupted from outside, so a running solve
+    /// keeps its solver slot until it returns on its own; its result is then discarded.
+    pub fn cancel(&self, id: JobId) -> Option<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get_mut(&id)?;
+        if !entry.job.status.is_finished() {
+            if let Some(abort) = entry.abort.take() {
+                abort.abort();
+            }
+            entry.job.status = JobStatus::Cancelled;
+        }
+        Some(entry.job.clone())
+    }
+
+    fn append_log(&self, id: JobId, lines: Vec<String>) {
+        if lines.is_empty() {
+            return;
+        }
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(&id) {
+            entry.log.extend(lines);
+        }
+    }
+
+    fn set_incumbent(&self, id: JobId, output: SchedulingOutput) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(&id)
+            && !entry.job.status.is_finished()
+        {
+            entry.incumbent = Some(output);
+        }
+    }
+
+    // applies `change` unless the job already finished (e.g. it was cancelled meanwhile)
+    fn update(&self, id: JobId, change: impl FnOnce(&mut Job)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(entry) = jobs.get_mut(&id)
+            && !entry.job.status.is_finished()
+        {
+            change(&mut entry.job);
+            if entry.job.status.is_finished() {
+                entry.abort = None;
+            }
+        }
+    }
+}
+
+/// Temporary directory HiGHS writes a job's log files into; removed when dropped, including
+/// when the job is cancelled.
+struct LogDir(PathBuf);
+
+impl LogDir {
+    fn create(id: JobId) -> Self {
+        let path = std::env::temp_dir()
+            .join(format!("schedule_solver_{}_job_{}", std::process::id(), id));
+        // without the directory HiGHS just logs to the console, so the job still runs
+        let _ = std::fs::create_dir_all(&path);
+        LogDir(path)
+    }
+}
+
+impl Drop for LogDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Follows the log files in a directory as HiGHS appends to them.
+#[derive(Default)]
+struct LogTail {
+    /// Bytes read so far and the unfinished last line of every file.
+    files: HashMap<PathBuf, (u64, String)>,
+}
+
+impl LogTail {
+    /// Lines completed since the last call. Lines of a component log (`part<i>.log`) are
+    /// prefixed with the component, e.g. `[part0]`.
+    fn read_new_lines(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        paths.sort();
+        let mut lines = Vec::new();
+        for path in paths {
+            let (read, partial) = self.files.entry(path.clone()).or_default();
+            let mut chunk = Vec::new();
+            let Ok(mut file) = std::fs::File::open(&path) else {
+                continue;
+            };
+            if file.seek(SeekFrom::Start(*read)).is_err() || file.read_to_end(&mut chunk).is_err() {
+                continue;
+            }
+            *read += chunk.len() as u64;
+            partial.push_str(&String::from_utf8_lossy(&chunk));
+            let Some(end) = partial.rfind('\n') else {
+                continue;
+            };
+            let complete: String = partial.drain(..=end).collect();
+            let prefix = log_prefix(&path);
+            lines.extend(complete.lines().map(|line| format!("{}{}", prefix, line)));
+        }
+        lines
+    }
+
+    /// Whatever is left after the last newline of every file, once the solve is over.
+    fn finish(self) -> Vec<String> {
+        let mut files: Vec<_> = self.files.into_iter().collect();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        files
+            .into_iter()
+            .filter(|(_, (_, partial))| !partial.is_empty())
+            .map(|(path, (_, partial))| format!("{}{}", log_prefix(&path), partial))
+            .collect()
+    }
+}
+
+fn log_prefix(path: &Path) -> String {
+    match path.file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) if stem.starts_with("part") => format!("[{}] ", stem),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn input() -> SchedulingInput {
+        serde_json::from_value(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 2
+        }))
+        .unwrap()
+    }
+
+    async fn finished(store: &JobStore, id: JobId) -> Job {
+        loop {
+            let job = store.get(id).unwrap();
+            if job.status.is_finished() {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn a_queued_job_cancelled_before_it_starts_never_runs() {
+        let store = JobStore::new(1);
+        // take the only solver slot so submitted jobs stay queued
+        let busy = store.solver_slots.clone().acquire_owned().await.unwrap();
+        let queued = store.submit(input());
+        let next = store.submit(input());
+        tokio::task::yield_now().await;
+        assert_eq!(store.get(queued.id).unwrap().status, JobStatus::Pending);
+
+        assert_eq!(store.cancel(queued.id).unwrap().status, JobStatus::Cancelled);
+        drop(busy);
+        let next = finished(&store, next.id).await;
+        assert_eq!(next.status, JobStatus::Completed);
+
+        let cancelled = store.get(queued.id).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert!(cancelled.output.is_none());
+        // a finished job stays as it is
+        assert_eq!(store.cancel(next.id).unwrap().status, JobStatus::Completed);
+        assert!(store.cancel(next.id + 1).is_none());
+    }
+
+    /// Six courses of two instructors over two rooms and a day of eight periods, so HiGHS has
+    /// to search a little.
+    fn nontrivial_input() -> SchedulingInput {
+        let courses: Vec<_> = (1..=6)
+            .map(|id| {
+                json!({
+                    "id": id, "requiredCapacity": 10, "durationSlots": 1 + id % 2,
+                    "instructorIds": [1 + id % 2]
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [
+                {"id": 1, "unavailableSlots": [0]}, {"id": 2, "unavailableSlots": [5]}
+            ],
+            "courses": courses,
+            "totalTimeslots": 8,
+            "periodsPerDay": 8,
+            "weights": {"idleTimeWeight": 0.5}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn improving_schedules_are_reported_as_the_solver_finds_them() {
+        let input = nontrivial_input();
+        let incumbents = Arc::new(Mutex::new(Vec::new()));
+        let sink = incumbents.clone();
+        let on_incumbent: IncumbentSink =
+            Arc::new(move |output| sink.lock().unwrap().push(output));
+        let log_dir = LogDir::create(JobId::MAX);
+        let output = solver::solve_logged(&input, &log_dir.0, on_incumbent).unwrap();
+
+        let incumbents = std::mem::take(&mut *incumbents.lock().unwrap());
+        assert!(!incumbents.is_empty());
+        for incumbent in &incumbents {
+            assert_eq!(incumbent.assignments.len(), input.courses.len());
+            assert!(incumbent.objective_value <= output.objective_value);
+        }
+
+        // a finished job offers its result as the incumbent
+        let store = JobStore::new(1);
+        let job = store.submit(input);
+        let job = finished(&store, job.id).await;
+        let incumbent = store.incumbent(job.id).unwrap().unwrap();
+        assert_eq!(incumbent.assignments, job.output.unwrap().assignments);
+        assert!(store.incumbent(job.id + 1).is_none());
+    }
+}
@@ -1,13 +1,34 @@
+mod alternatives;
+mod bundle;
+mod calendar;
+mod config;
+mod constraints;
 mod data;
+mod decompose;
+mod export;
+mod heuristic;
+mod incumbent;
+mod jobs;
+mod merge;
+mod model;
 mod solver;
 mod server;
+mod validation;
+mod whatif;
 
 #[tokio::main]
 async fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
 
 
-    server::run_server().await;
+    let config = match config::ServerConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    server::run_server(config).await;
 
 
 }
@@ -0,0 +1,340 @@
+use crate::data::{
+    Assignment, CourseId, InstructorId, Room, RoomId, SchedulingInput, SectionLink, StudentGroupId,
+};
+use crate::solver::SolveError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Where an id of one of the merged inputs ended up in the combined problem.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdRemap {
+    pub input_index: usize,
+    pub original_id: u32,
+    pub merged_id: u32,
+}
+
+/// A combined problem together with the id remapping applied to build it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedInput {
+    pub input: SchedulingInput,
+    pub courses: Vec<IdRemap>,
+    pub instructors: Vec<IdRemap>,
+    pub student_groups: Vec<IdRemap>,
+    pub departments: Vec<IdRemap>,
+}
+
+/// Merges separately prepared inputs (one per department) into one problem.
+///
+/// Rooms are the shared resource, so rooms with the same id are the same room and are
+/// combined. Course, instructor, student group and department ids are prefixed with the
+/// input's position: with ids below 1000, id 7 of input 0 becomes 1007 and id 7 of input 1
+/// becomes 2007. The horizon is the longest of the inputs; day length, slot length and the
+/// number of weeks must agree, as must the slot calendars of the inputs that have one. The
+/// remaining global settings are taken from the first input. Course demands are expanded
+/// into their sections first.
+pub fn merge_inputs(inputs: &[SchedulingInput]) -> Result<MergedInput, SolveError> {
+    // sections get their ids before the ids are prefixed
+    let inputs: &[SchedulingInput] = &inputs
+        .iter()
+        .map(SchedulingInput::with_sections_expanded)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(SolveError::InvalidInput)?;
+    let Some(first) = inputs.first() else {
+        return Err(SolveError::InvalidInput("Nothing to merge.".to_string()));
+    };
+    for (index, input) in inputs.iter().enumerate().skip(1) {
+        if input.periods_per_day != first.periods_per_day {
+            return Err(SolveError::InvalidInput(format!(
+                "Input {} uses periodsPerDay {:?} but input 0 uses {:?}.",
+                index, input.periods_per_day, first.periods_per_day
+            )));
+        }
+        if input.weeks != first.weeks {
+            return Err(SolveError::InvalidInput(format!(
+                "Input {} uses weeks {:?} but input 0 uses {:?}.",
+                index, input.weeks, first.weeks
+            )));
+        }
+        if !input.slot_calendar.is_empty()
+            && !first.slot_calendar.is_empty()
+            && input.slot_calendar != first.slot_calendar
+        {
+            return Err(SolveError::InvalidInput(format!(
+                "Input {} has a different slotCalendar than input 0.",
+                index
+            )));
+        }
+        if input.slot_minutes != first.slot_minutes {
+            return Err(SolveError::InvalidInput(format!(
+                "Input {} uses slotMinutes {:?} but input 0 uses {:?}.",
+                index, input.slot_minutes, first.slot_minutes
+            )));
+        }
+    }
+
+    let max_id = inputs
+        .iter()
+        .flat_map(|input| {
+            let courses = input.courses.iter().map(|c| c.id.0);
+            let instructors = input.instructors.iter().map(|i| i.id.0);
+            let groups = input.student_groups.iter().map(|g| g.id.0);
+            let departments = input.courses.iter().filter_map(|c| c.department_id);
+            courses.chain(instructors).chain(groups).chain(departments)
+        })
+        .max()
+        .unwrap_or(0);
+    // smallest power of ten above every id, so the prefix never touches the original digits
+    let mut stride: u32 = 10;
+    while stride <= max_id {
+        stride = stride.checked_mul(10).ok_or_else(too_many_ids)?;
+    }
+    let remap = |index: usize, id: u32| -> Result<u32, SolveError> {
+        u32::try_from(index + 1)
+            .ok()
+            .and_then(|prefix| prefix.checked_mul(stride))
+            .and_then(|prefix| prefix.checked_add(id))
+            .ok_or_else(too_many_ids)
+    };
+
+    let mut merged = SchedulingInput {
+        rooms: Vec::new(),
+        courses: Vec::new(),
+        instructors: Vec::new(),
+        student_groups: Vec::new(),
+        soft_adjacency: Vec::new(),
+        distinct_instructor_groups: Vec::new(),
+        section_links: Vec::new(),
+        warm_start: Vec::new(),
+        frozen_assignments: Vec::new(),
+        room_distances: Vec::new(),
+        combinable: Vec::new(),
+        slot_calendar: inputs
+            .iter()
+            .map(|i| i.slot_calendar.clone())
+            .find(|calendar| !calendar.is_empty())
+            .unwrap_or_default(),
+        total_timeslots: inputs.iter().map(|i| i.total_timeslots).max().unwrap_or(0),
+        ..first.clone()
+    };
+    let mut rooms: BTreeMap<RoomId, (usize, Room)> = BTreeMap::new();
+    let mut course_remaps = Vec::new();
+    let mut instructor_remaps = Vec::new();
+    let mut group_remaps = Vec::new();
+    let mut department_remaps: BTreeMap<(usize, u32), u32> = BTreeMap::new();
+
+    for (index, input) in inputs.iter().enumerate() {
+        for room in &input.rooms {
+            match rooms.get_mut(&room.id) {
+                None => {
+                    rooms.insert(room.id, (index, room.clone()));
+                }
+                Some((first_index, existing)) => {
+                    if existing.capacity != room.capacity {
+                        return Err(SolveError::InvalidInput(format!(
+                            "Room {} has capacity {} in input {} but {} in input {}.",
+                            room.id, existing.capacity, first_index, room.capacity, index
+                        )));
+                    }
+                    existing
+                        .recurring_unavailable
+                        .extend(room.recurring_unavailable.iter().copied());
+                    existing.unavailable_slots.extend(room.unavailable_slots.iter().copied());
+                    existing.unavailable_dates.extend(room.unavailable_dates.iter().cloned());
+                }
+            }
+        }
+        for instructor in &input.instructors {
+            let mut instructor = instructor.clone();
+            let merged_id = remap(index, instructor.id.0)?;
+            instructor_remaps.push(IdRemap {
+                input_index: index,
+                original_id: instructor.id.0,
+                merged_id,
+            });
+            instructor.id = InstructorId(merged_id);
+            merged.instructors.push(instructor);
+        }
+        for course in &input.courses {
+            let mut course = course.clone();
+            let merged_id = remap(index, course.id.0)?;
+            course_remaps.push(IdRemap {
+                input_index: index,
+                original_id: course.id.0,
+                merged_id,
+            });
+            course.id = CourseId(merged_id);
+            course.section_of =
+                course.section_of.map(|c_id| remap(index, c_id.0).map(CourseId)).transpose()?;
+            course.instructor_id = course
+                .instructor_id
+                .map(|i_id| remap(index, i_id.0).map(InstructorId))
+                .transpose()?;
+            course.instructor_ids = course
+                .instructor_ids
+                .iter()
+                .map(|i_id| remap(index, i_id.0).map(InstructorId))
+                .collect::<Result<_, _>>()?;
+            course.eligible_instructors = course
+                .eligible_instructors
+                .map(|eligible| {
+                    eligible
+                        .iter()
+                        .map(|i_id| remap(index, i_id.0).map(InstructorId))
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?;
+            if let Some(department_id) = course.department_id {
+                let merged_department = remap(index, department_id)?;
+                department_remaps.insert((index, department_id), merged_department);
+                course.department_id = Some(merged_department);
+            }
+            merged.courses.push(course);
+        }
+        for group in &input.student_groups {
+            let mut group = group.clone();
+            let merged_id = remap(index, group.id.0)?;
+            group_remaps.push(IdRemap {
+                input_index: index,
+                original_id: group.id.0,
+                merged_id,
+            });
+            group.id = StudentGroupId(merged_id);
+            group.course_ids = group
+                .course_ids
+                .iter()
+                .map(|c_id| remap(index, c_id.0).map(CourseId))
+                .collect::<Result<_, _>>()?;
+            merged.student_groups.push(group);
+        }
+        for (first_id, second_id) in &input.soft_adjacency {
+            merged.soft_adjacency.push((
+                CourseId(remap(index, first_id.0)?),
+                CourseId(remap(index, second_id.0)?),
+            ));
+        }
+        for link in &input.section_links {
+            merged.section_links.push(SectionLink {
+                lecture: CourseId(remap(index, link.lecture.0)?),
+                lab: CourseId(remap(index, link.lab.0)?),
+                ..*link
+            });
+        }
+        let remap_assignment = |assignment: &Assignment| -> Result<Assignment, SolveError> {
+            Ok(Assignment {
+                course_id: CourseId(remap(index, assignment.course_id.0)?),
+                instructor_id: assignment
+                    .instructor_id
+                    .map(|i_id| remap(index, i_id.0).map(InstructorId))
+                    .transpose()?,
+                ..assignment.clone()
+            })
+        };
+        for assignment in &input.warm_start {
+            merged.warm_start.push(remap_assignment(assignment)?);
+        }
+        for assignment in &input.frozen_assignments {
+            merged.frozen_assignments.push(remap_assignment(assignment)?);
+        }
+        for group in &input.distinct_instructor_groups {
+            merged.distinct_instructor_groups.push(
+                group
+                    .iter()
+                    .map(|c_id| remap(index, c_id.0).map(CourseId))
+                    .collect::<Result<_, _>>()?,
+            );
+        }
+        // rooms keep their ids, so their distances and combinations carry over unchanged
+        merged.room_distances.extend(input.room_distances.iter().copied());
+        for room_ids in &input.combinable {
+            let mut room_ids = room_ids.clone();
+            room_ids.sort_unstable();
+            if !merged.combinable.contains(&room_ids) {
+                merged.combinable.push(room_ids);
+            }
+        }
+    }
+    merged.rooms = rooms
+        .into_values()
+        .map(|(_, mut room)| {
+            room.recurring_unavailable.sort_unstable();
+            room.recurring_unavailable.dedup();
+            room.unavailable_slots.sort_unstable();
+            room.unavailable_slots.dedup();
+            room
+        })
+        .collect();
+
+    Ok(MergedInput {
+        input: merged,
+        courses: course_remaps,
+        instructors: instructor_remaps,
+        student_groups: group_remaps,
+        departments: department_remaps
+            .into_iter()
+            .map(|((input_index, original_id), merged_id)| IdRemap {
+                input_index,
+                original_id,
+                merged_id,
+            })
+            .collect(),
+    })
+}
+
+fn too_many_ids() -> SolveError {
+    SolveError::InvalidInput("Ids are too large to be prefixed without overflowing.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn department(rooms: serde_json::Value, total_timeslots: u32) -> SchedulingInput {
+        serde_json::from_value(json!({
+            "rooms": rooms,
+            "instructors": [{"id": 1, "unavailableSlots": [0]}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "studentGroups": [{"id": 1, "courseIds": [1]}],
+            "totalTimeslots": total_timeslots
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn overlapping_ids_are_prefixed_and_shared_rooms_combined() {
+        let merged = merge_inputs(&[
+            department(json!([{"id": 1, "capacity": 30}]), 4),
+            department(json!([{"id": 1, "capacity": 30}, {"id": 2, "capacity": 20}]), 6),
+        ])
+        .unwrap();
+        let input = &merged.input;
+        assert_eq!(input.total_timeslots, 6);
+        assert_eq!(input.rooms.iter().map(|r| r.id.0).collect::<Vec<_>>(), [1, 2]);
+        let courses: Vec<_> =
+            input.courses.iter().map(|c| (c.id.0, c.instructors()[0].0)).collect();
+        assert_eq!(courses, [(11, 11), (21, 21)]);
+        assert!(input.instructors.iter().all(|i| i.unavailable_slots == [0]));
+        assert_eq!(input.student_groups[1].course_ids, [CourseId(21)]);
+
+        let remapped: Vec<_> = merged
+            .courses
+            .iter()
+            .map(|remap| (remap.input_index, remap.original_id, remap.merged_id))
+            .collect();
+        assert_eq!(remapped, [(0, 1, 11), (1, 1, 21)]);
+        assert_eq!(crate::solver::solve(input).unwrap().assignments.len(), 2);
+    }
+
+    #[test]
+    fn inputs_with_different_days_are_not_merged() {
+        let mut other = department(json!([{"id": 1, "capacity": 30}]), 4);
+        other.periods_per_day = Some(2);
+        let first = department(json!([{"id": 1, "capacity": 30}]), 4);
+        assert!(matches!(merge_inputs(&[first, other]), Err(SolveError::InvalidInput(_))));
+        assert!(merge_inputs(&[]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use good_lp::{
+    Expression, IntoAffineExpression, ProblemVariables, SolverModel, Variable, VariableDefinition,
+    constraint,
+};
+
+/// A linear constraint stored as `expression <= 0` or `expression == 0`.
+#[derive(Debug, Clone)]
+struct LinearConstraint {
+    expression: Expression,
+    is_equality: bool,
+}
+
+/// Records the variables and constraints of the ILP so the same structure can be
+/// replayed into a fresh `good_lp` model every time it is solved.
+#[derive(Default)]
+pub struct ModelBuilder {
+    problem: ProblemVariables,
+    definitions: Vec<VariableDefinition>,
+    constraints: Vec<LinearConstraint>,
+}
+
+impl ModelBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_variable(&mut self, definition: VariableDefinition) -> Variable {
+        self.definitions.push(definition.clone());
+        self.problem.add(definition)
+    }
+
+    pub fn add_vector(&mut self, definition: VariableDefinition, len: usize) -> Vec<Variable> {
+        (0..len).map(|_| self.add_variable(definition.clone())).collect()
+    }
+
+    /// lhs <= rhs
+    pub fn add_leq(&mut self, lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) {
+        self.constraints.push(LinearConstraint {
+            expression: lhs.into_expression() - rhs.into_expression(),
+            is_equality: false,
+        });
+    }
+
+    /// lhs >= rhs
+    pub fn add_geq(&mut self, lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) {
+        self.add_leq(rhs, lhs);
+    }
+
+    /// lhs == rhs
+    pub fn add_eq(&mut self, lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) {
+        self.constraints.push(LinearConstraint {
+            expression: lhs.into_expression() - rhs.into_expression(),
+            is_equality: true,
+        });
+    }
+
+    /// A fresh set of problem variables whose indices match the recorded variables.
+    pub fn variables(&self) -> ProblemVariables {
+        let mut problem = ProblemVariables::new();
+        for definition in &self.definitions {
+            problem.add(definition.clone());
+        }
+        problem
+    }
+
+    /// Every recorded variable in the order HiGHS numbers its columns.
+    pub fn columns(&self) -> Vec<Variable> {
+        self.problem.iter_variables_with_def().map(|(variable, _)| variable).collect()
+    }
+
+    pub fn apply_constraints<M: SolverModel>(&self, model: &mut M) {
+        for linear in &self.constraints {
+            let expression = linear.expression.clone();
+            if linear.is_equality {
+                model.add_constraint(constraint!(expression == 0));
+            } else {
+                model.add_constraint(constraint!(expression <= 0));
+            }
+        }
+    }
+}
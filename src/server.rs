@@ -1,23 +1,959 @@
-use axum::{routing::post, Router, Json};
-use crate::data::{SchedulingInput, SchedulingOutput};
-use crate::solver;
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRef, FromRequest, Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::{routing::{get, post}, Router, Json};
+use futures_util::stream::{self, Stream, StreamExt};
+use jiff::tz::TimeZone;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::convert::Infallible;
+use std::time::{Duration, Instant};
+use crate::alternatives::{self, AlternativesRequest};
+use crate::bundle;
+use crate::config::ServerConfig;
+use crate::constraints;
+use crate::data::{self, InstructorId, SchedulingInput, SchedulingOutput};
+use crate::export::{self, InstructorTimetableRequest};
+use crate::jobs::{JobId, JobStore};
+use crate::merge;
+use crate::solver::{self, SolveError};
+use crate::validation;
+use crate::whatif::{self, WhatIfRequest};
 
-async fn solve_handler(Json(input): Json<SchedulingInput>) -> Result<Json<SchedulingOutput>, (axum::http::StatusCode, String)> {
+/// Shared by every handler; each extracts the part it needs.
+#[derive(Clone)]
+struct AppState {
+    jobs: JobStore,
+    config: Arc<ServerConfig>,
+}
+
+impl FromRef<AppState> for JobStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.jobs.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ServerConfig> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+/// Query parameters accepted by the solve endpoint.
+#[derive(Debug, Deserialize)]
+struct SolveParams {
+    /// indent the JSON response for easier reading
+    #[serde(default)]
+    pretty: bool,
+    /// maximum number of assignments to return
+    limit: Option<usize>,
+    /// number of assignments to skip
+    offset: Option<usize>,
+    /// return assignments as a map keyed by room or instructor id
+    group_by: Option<GroupBy>,
+    /// set to false to leave out the unmet soft constraints; the score is unaffected
+    #[serde(default = "default_include_unmet")]
+    include_unmet: bool,
+    #[serde(default)]
+    format: ResponseFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ResponseFormat {
+    /// a list (or, with `group_by`, a map) of assignments
+    #[default]
+    Assignments,
+    /// run-length encoded room timelines instead of assignments
+    Occupancy,
+}
+
+fn default_include_unmet() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum GroupBy {
+    Room,
+    Instructor,
+}
+
+/// Query parameters accepted by the Gantt endpoint.
+#[derive(Debug, Deserialize)]
+struct GanttParams {
+    #[serde(default)]
+    pretty: bool,
+    /// IANA time zone to convert calendar times into, e.g. `America/New_York`
+    tz: Option<String>,
+}
+
+/// Query parameters accepted by the validate, constraints, merge, what-if and job endpoints.
+#[derive(Debug, Deserialize)]
+struct ValidateParams {
+    #[serde(default)]
+    pretty: bool,
+}
+
+/// Formats a solve request body may be written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputFormat::Json => write!(f, "JSON"),
+            InputFormat::Yaml => write!(f, "YAML"),
+            InputFormat::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+impl InputFormat {
+    /// The format a `Content-Type` names, ignoring parameters such as the charset.
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" => Some(InputFormat::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+                Some(InputFormat::Yaml)
+            }
+            "application/toml" | "text/toml" => Some(InputFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first non-blank line: JSON opens an object, TOML starts
+    /// with a table header or a `key = value` pair, anything else is taken for YAML.
+    fn sniff(body: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(body);
+        let first_line = text.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+        let is_toml_pair = first_line.split_once('=').is_some_and(|(key, _)| {
+            let key = key.trim();
+            !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+        if first_line.starts_with('{') {
+            InputFormat::Json
+        } else if first_line.starts_with('[') || is_toml_pair {
+            InputFormat::Toml
+        } else {
+            InputFormat::Yaml
+        }
+    }
+
+    fn parse(self, body: &[u8]) -> Result<SchedulingInput, String> {
+        let parsed = match self {
+            InputFormat::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            InputFormat::Yaml => serde_yaml::from_slice(body).map_err(|e| e.to_string()),
+            InputFormat::Toml => std::str::from_utf8(body)
+                .map_err(|e| e.to_string())
+                .and_then(|text| toml::from_str(text).map_err(|e| e.to_string())),
+        };
+        parsed.map_err(|e| format!("Failed to parse the request body as {}: {}", self, e))
+    }
+}
+
+/// Reads a [`SchedulingInput`] written in JSON, YAML or TOML. The declared `Content-Type`
+/// comes first; when it is missing, unknown, or does not parse, the format is sniffed from
+/// the body, since some clients label everything as JSON.
+fn parse_input(body: &[u8], content_type: Option<&str>) -> Result<SchedulingInput, String> {
+    let sniffed = InputFormat::sniff(body);
+    let format = content_type.and_then(InputFormat::from_content_type).unwrap_or(sniffed);
+    match format.parse(body) {
+        Err(e) if format != sniffed => {
+            debug!("{}; trying {} instead", e, sniffed);
+            // the declared format's error explains more when neither parses
+            sniffed.parse(body).map_err(|_| e)
+        }
+        parsed => parsed,
+    }
+}
+
+/// A [`SchedulingInput`] request body in any format [`parse_input`] reads; every endpoint
+/// that takes a bare input extracts it this way.
+struct InputBody(SchedulingInput);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequest<S> for InputBody {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(|e| (e.status(), e.body_text()))?;
+        let input = parse_input(&body, content_type.as_deref())
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        Ok(InputBody(input))
+    }
+}
+
+async fn solve_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<SolveParams>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    if params.format == ResponseFormat::Occupancy && params.group_by.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "group_by cannot be combined with format=occupancy.".to_string(),
+        ));
+    }
     match solver::solve(&input) {
-        Ok(output) => Ok(Json(output)),
-        Err(e) => Err((axum::http::StatusCode::BAD_REQUEST, e)),
+        Ok(mut output) => {
+            if params.limit.is_some() || params.offset.is_some() {
+                output.paginate(params.offset.unwrap_or(0), params.limit);
+            }
+            if params.group_by.is_none()
+                && params.include_unmet
+                && params.format == ResponseFormat::Assignments
+            {
+                return Ok(json_response(&output, params.pretty));
+            }
+            let shaped = shaped_output(&output, &input, &params)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Ok(json_response(&shaped, params.pretty))
+        }
+        Err(e) => Err(error_response(e)),
     }
 }
 
-pub async fn run_server() {
-    let app = Router::new()
-        .route("/v1/schedule/solve", post(solve_handler));
+async fn solve_csv_handler(
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    match solver::solve(&input) {
+        Ok(output) => Ok((
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            export::to_csv(&output, &input),
+        )
+            .into_response()),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn solve_tsv_handler(
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    match solver::solve(&input) {
+        Ok(output) => Ok((
+            [(header::CONTENT_TYPE, "text/tab-separated-values; charset=utf-8")],
+            export::to_tsv(&output, &input),
+        )
+            .into_response()),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn solve_png_handler(
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    // a grid too large to draw should fail before the solve, not after it
+    export::png_cell_size(&input).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    match solver::solve(&input) {
+        Ok(output) => {
+            let png = export::render_png(&output, &input)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+        }
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn solve_gantt_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<GanttParams>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    // a bad zone should fail before the solve, not after it
+    let time_zone = params
+        .tz
+        .as_deref()
+        .map(|name| {
+            TimeZone::get(name)
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Unknown time zone {:?}.", name)))
+        })
+        .transpose()?;
+    match solver::solve(&input) {
+        Ok(output) => {
+            let mut tasks = export::to_gantt(&output, &input);
+            if let Some(time_zone) = &time_zone {
+                export::localize_gantt(&mut tasks, &input, time_zone);
+            }
+            Ok(json_response(&tasks, params.pretty))
+        }
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn solve_bundle_handler(
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(input);
+    match solver::solve(&input) {
+        Ok(output) => Ok((
+            [(header::CONTENT_TYPE, "application/json")],
+            bundle::to_bundle(&input, &output),
+        )
+            .into_response()),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+// same shape as the output, except that "assignments" may become a map of id -> assignments
+// or be replaced by "occupancy", and "unmetSoftConstraints" may be dropped
+fn shaped_output(
+    output: &SchedulingOutput,
+    input: &SchedulingInput,
+    params: &SolveParams,
+) -> serde_json::Result<serde_json::Value> {
+    let mut shaped = serde_json::to_value(output)?;
+    match params.group_by {
+        Some(GroupBy::Room) => {
+            shaped["assignments"] =
+                serde_json::to_value(data::group_by_room(&output.assignments, input))?;
+        }
+        Some(GroupBy::Instructor) => {
+            shaped["assignments"] =
+                serde_json::to_value(data::group_by_instructor(&output.assignments, input))?;
+        }
+        None => {}
+    }
+    if params.format == ResponseFormat::Occupancy
+        && let Some(fields) = shaped.as_object_mut()
+    {
+        fields.remove("assignments");
+        fields.insert(
+            "occupancy".to_string(),
+            serde_json::to_value(data::to_occupancy(output, input))?,
+        );
+    }
+    if !params.include_unmet
+        && let Some(fields) = shaped.as_object_mut()
+    {
+        fields.remove("unmetSoftConstraints");
+    }
+    Ok(shaped)
+}
+
+async fn instructor_timetable_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Path(instructor_id): Path<InstructorId>,
+    Query(params): Query<ValidateParams>,
+    Json(request): Json<InstructorTimetableRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(request.input);
+    if !input.instructors.iter().any(|i| i.id == instructor_id) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No instructor with id {}.", instructor_id),
+        ));
+    }
+    let assignments = match request.assignments {
+        Some(assignments) => assignments,
+        None => solver::solve(&input).map_err(error_response)?.assignments,
+    };
+    let timetable = export::instructor_timetable(&assignments, &input, instructor_id);
+    Ok(json_response(&timetable, params.pretty))
+}
+
+async fn model_lp_handler(
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Result<Response, (StatusCode, String)> {
+    match solver::to_lp_format(&config.apply_defaults(input)) {
+        Ok(lp) => Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], lp).into_response()),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn validate_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<ValidateParams>,
+    InputBody(input): InputBody,
+) -> Response {
+    let input = config.apply_defaults(input);
+    json_response(&validation::validate_input(&input), params.pretty)
+}
+
+async fn constraints_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<ValidateParams>,
+    InputBody(input): InputBody,
+) -> Response {
+    let input = config.apply_defaults(input);
+    json_response(&constraints::describe_constraints(&input), params.pretty)
+}
+
+async fn merge_handler(
+    Query(params): Query<ValidateParams>,
+    Json(inputs): Json<Vec<SchedulingInput>>,
+) -> Result<Response, (StatusCode, String)> {
+    match merge::merge_inputs(&inputs) {
+        Ok(merged) => Ok(json_response(&merged, params.pretty)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn what_if_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<ValidateParams>,
+    Json(request): Json<WhatIfRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let input = config.apply_defaults(request.input);
+    match whatif::what_if(&input, &request.patch) {
+        Ok(result) => Ok(json_response(&result, params.pretty)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn alternatives_handler(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<ValidateParams>,
+    Json(mut request): Json<AlternativesRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    request.input = config.apply_defaults(request.input);
+    match alternatives::alternatives(&request) {
+        Ok(alternatives) => Ok(json_response(&alternatives, params.pretty)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Build and solver backend of the running server.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionInfo {
+    crate_version: &'static str,
+    solver: &'static str,
+    solver_version: String,
+}
+
+async fn version_handler() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        solver: "HiGHS",
+        solver_version: solver::solver_version(),
+    })
+}
+
+async fn submit_job_handler(
+    State(jobs): State<JobStore>,
+    State(config): State<Arc<ServerConfig>>,
+    InputBody(input): InputBody,
+) -> Response {
+    let job = jobs.submit(config.apply_defaults(input));
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+async fn get_job_handler(
+    State(jobs): State<JobStore>,
+    Path(id): Path<JobId>,
+    Query(params): Query<ValidateParams>,
+) -> Result<Response, (StatusCode, String)> {
+    match jobs.get(id) {
+        Some(job) => Ok(json_response(&job, params.pretty)),
+        None => Err(job_not_found(id)),
+    }
+}
+
+async fn cancel_job_handler(
+    State(jobs): State<JobStore>,
+    Path(id): Path<JobId>,
+) -> Result<Response, (StatusCode, String)> {
+    match jobs.cancel(id) {
+        Some(job) => Ok(Json(job).into_response()),
+        None => Err(job_not_found(id)),
+    }
+}
+
+async fn job_incumbent_handler(
+    State(jobs): State<JobStore>,
+    Path(id): Path<JobId>,
+    Query(params): Query<ValidateParams>,
+) -> Result<Response, (StatusCode, String)> {
+    match jobs.incumbent(id) {
+        Some(Some(output)) => Ok(json_response(&output, params.pretty)),
+        Some(None) => Ok(StatusCode::NO_CONTENT.into_response()),
+        None => Err(job_not_found(id)),
+    }
+}
+
+/// Streams the HiGHS log of a job as `log` events, one per line, followed by a single `status`
+/// event with the finished job. Lines logged before the client connected are sent first.
+async fn job_events_handler(
+    State(jobs): State<JobStore>,
+    Path(id): Path<JobId>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    if jobs.get(id).is_none() {
+        return Err(job_not_found(id));
+    }
+    let events = stream::unfold(Some(0), move |next_line| {
+        let jobs = jobs.clone();
+        async move {
+            let mut next_line = next_line?;
+            loop {
+                let (job, lines) = jobs.log_since(id, next_line)?;
+                if !lines.is_empty() {
+                    next_line += lines.len();
+                    let events: Vec<_> = lines
+                        .into_iter()
+                        .map(|line| Event::default().event("log").data(line))
+                        .collect();
+                    return Some((events, Some(next_line)));
+                }
+                if job.status.is_finished() {
+                    let status = Event::default()
+                        .event("status")
+                        .data(serde_json::to_string(&job).unwrap_or_default());
+                    return Some((vec![status], None));
+                }
+                tokio::time::sleep(EVENT_POLL_INTERVAL).await;
+            }
+        }
+    });
+    let events = events.flat_map(|batch| stream::iter(batch.into_iter().map(Ok)));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn job_not_found(id: JobId) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("No job with id {}.", id))
+}
+
+fn error_response(error: SolveError) -> (StatusCode, String) {
+    let status = match error {
+        SolveError::InvalidInput(_) | SolveError::Infeasible(_) => StatusCode::BAD_REQUEST,
+        SolveError::SolverFailure(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, error.to_string())
+}
+
+// compact by default; pretty output bypasses the Json wrapper
+fn json_response<T: Serialize>(value: &T, pretty: bool) -> Response {
+    if !pretty {
+        return Json(value).into_response();
+    }
+    match serde_json::to_string_pretty(value) {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Set to `1` or `true` to log every request body at debug level. Bodies may hold
+/// sensitive data, so this is off by default, and requests refused for a missing or wrong
+/// token are never logged.
+const LOG_BODIES_ENV: &str = "SCHEDULE_SOLVER_LOG_BODIES";
+
+/// Upper bound for buffering a body to log it; matches axum's default body limit.
+const MAX_LOGGED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// How often an event stream checks its job for new log lines.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// When set, every route but `/healthz` requires `Authorization: Bearer <token>` with this
+/// token. Unset, the server is open to anyone who can reach it.
+const TOKEN_ENV: &str = "SCHEDULE_SOLVER_TOKEN";
+
+/// Routes a load balancer must reach without the token.
+const UNAUTHENTICATED_PATHS: &[&str] = &["/healthz"];
+
+// rejects requests without the configured bearer token; `None` lets everything through
+async fn require_token(
+    State(token): State<Option<Arc<str>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(token) = token
+        && !UNAUTHENTICATED_PATHS.contains(&request.uri().path())
+    {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !presented.is_some_and(|presented| tokens_match(presented, &token)) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                "Missing or invalid bearer token",
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+// compares every byte, so the time taken does not reveal how much of a guess was right
+fn tokens_match(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+async fn health_handler() -> &'static str {
+    "ok"
+}
+
+// logs every request; the body is buffered and put back so the handler can still read it
+async fn log_requests(State(log_bodies): State<bool>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let request = if log_bodies {
+        let (parts, body) = request.into_parts();
+        let bytes = match axum::body::to_bytes(body, MAX_LOGGED_BODY_BYTES).await {
+            Ok(bytes) => bytes,
+            Err(e) => return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response(),
+        };
+        debug!("{} {} body: {}", method, path, String::from_utf8_lossy(&bytes));
+        Request::from_parts(parts, Body::from(bytes))
+    } else {
+        request
+    };
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    info!(
+        "{} {} -> {} in {:.2?}",
+        method,
+        path,
+        response.status(),
+        started.elapsed()
+    );
+    response
+}
+
+/// Every route with its middleware; `token` and `log_bodies` as read from [`TOKEN_ENV`] and
+/// [`LOG_BODIES_ENV`].
+fn router(config: ServerConfig, token: Option<Arc<str>>, log_bodies: bool) -> Router {
+    let max_concurrent_solves = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Router::new()
+        .route("/healthz", get(health_handler))
+        .route("/version", get(version_handler))
+        .route("/v1/schedule/solve", post(solve_handler))
+        .route("/v1/schedule/solve.csv", post(solve_csv_handler))
+        .route("/v1/schedule/solve.tsv", post(solve_tsv_handler))
+        .route("/v1/schedule/solve.png", post(solve_png_handler))
+        .route("/v1/schedule/solve/gantt", post(solve_gantt_handler))
+        .route("/v1/schedule/solve/bundle", post(solve_bundle_handler))
+        .route(
+            "/v1/schedule/solve/by-instructor/:instructor_id",
+            post(instructor_timetable_handler),
+        )
+        .route("/v1/schedule/model.lp", post(model_lp_handler))
+        .route("/v1/schedule/validate", post(validate_handler))
+        .route("/v1/schedule/constraints", post(constraints_handler))
+        .route("/v1/schedule/merge", post(merge_handler))
+        .route("/v1/schedule/whatif", post(what_if_handler))
+        .route("/v1/schedule/alternatives", post(alternatives_handler))
+        .route("/v1/schedule/jobs", post(submit_job_handler))
+        .route(
+            "/v1/schedule/jobs/:id",
+            get(get_job_handler).delete(cancel_job_handler),
+        )
+        .route("/v1/schedule/jobs/:id/incumbent", get(job_incumbent_handler))
+        .route("/v1/schedule/solve/events/:id", get(job_events_handler))
+        .with_state(AppState {
+            jobs: JobStore::new(max_concurrent_solves),
+            config: Arc::new(config),
+        })
+        .layer(middleware::from_fn_with_state(log_bodies, log_requests))
+        // outermost, so a body is never buffered or logged before its token is checked
+        .layer(middleware::from_fn_with_state(token, require_token))
+}
+
+pub async fn run_server(config: ServerConfig) {
+    let log_bodies = std::env::var(LOG_BODIES_ENV)
+        .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+    let token: Option<Arc<str>> =
+        std::env::var(TOKEN_ENV).ok().filter(|token| !token.is_empty()).map(Arc::from);
+    if token.is_some() {
+        info!("Requests must carry the bearer token from {}", TOKEN_ENV);
+    }
+    let app = router(config, token, log_bodies);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:8080")
         .await
         .unwrap();
 
     println!("Server running at http://{}", listener.local_addr().unwrap());
-    
+
     axum::serve(listener, app).await.unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::Service;
+
+    const JSON_INPUT: &str = r#"{"rooms": [{"id": 1, "capacity": 30}],
+        "instructors": [{"id": 1, "unavailableSlots": []}],
+        "courses": [{"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]}],
+        "totalTimeslots": 2}"#;
+
+    const YAML_INPUT: &str = "rooms:
+  - id: 1
+    capacity: 30
+instructors:
+  - id: 1
+    unavailableSlots: []
+courses:
+  - id: 1
+    requiredCapacity: 10
+    durationSlots: 1
+    instructorIds: [1]
+totalTimeslots: 2
+";
+
+    const TOML_INPUT: &str = "totalTimeslots = 2
+
+[[rooms]]
+id = 1
+capacity = 30
+
+[[instructors]]
+id = 1
+unavailableSlots = []
+
+[[courses]]
+id = 1
+requiredCapacity = 10
+durationSlots = 1
+instructorIds = [1]
+";
+
+    /// Sends one request to a fresh router and returns the status and body.
+    async fn send(
+        router: Router,
+        method: &str,
+        uri: &str,
+        headers: &[(header::HeaderName, &str)],
+        body: &str,
+    ) -> (StatusCode, String) {
+        let (status, _, body) = send_for_headers(router, method, uri, headers, body).await;
+        (status, body)
+    }
+
+    /// Sends one request to a fresh router and returns the status, headers and body.
+    async fn send_for_headers(
+        mut router: Router,
+        method: &str,
+        uri: &str,
+        headers: &[(header::HeaderName, &str)],
+        body: &str,
+    ) -> (StatusCode, header::HeaderMap, String) {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = router.call(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn open_router() -> Router {
+        router(ServerConfig::default(), None, false)
+    }
+
+    #[tokio::test]
+    async fn every_input_format_is_read_with_or_without_its_content_type() {
+        let formats = [
+            (JSON_INPUT, "application/json"),
+            (YAML_INPUT, "application/yaml"),
+            (TOML_INPUT, "application/toml"),
+        ];
+        for (body, content_type) in formats {
+            for declared in [Some(content_type), Some("application/json"), None] {
+                let headers: Vec<_> =
+                    declared.map(|value| (header::CONTENT_TYPE, value)).into_iter().collect();
+                for uri in ["/v1/schedule/solve", "/v1/schedule/solve.csv"] {
+                    let (status, response) =
+                        send(open_router(), "POST", uri, &headers, body).await;
+                    assert_eq!(status, StatusCode::OK, "{} as {:?}: {}", uri, declared, response);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_body_is_a_bad_request() {
+        let headers = [(header::CONTENT_TYPE, "application/json")];
+        let (status, response) =
+            send(open_router(), "POST", "/v1/schedule/validate", &headers, "{ nope").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("JSON"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn pretty_true_indents_the_json_and_the_default_stays_compact() {
+        let json = [(header::CONTENT_TYPE, "application/json")];
+        let uri = "/v1/schedule/solve?pretty=true";
+        let (status, headers, response) =
+            send_for_headers(open_router(), "POST", uri, &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        assert!(response.contains("\n  \"assignments\": ["));
+
+        let (status, headers, response) =
+            send_for_headers(open_router(), "POST", "/v1/schedule/solve", &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        assert!(!response.contains('\n'));
+        let parsed: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert!(parsed["assignments"].is_array());
+    }
+
+    #[tokio::test]
+    async fn version_names_the_crate_and_the_linked_highs() {
+        let (status, response) = send(open_router(), "GET", "/version", &[], "").await;
+        assert_eq!(status, StatusCode::OK);
+        let version: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(version["crateVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(version["solver"], "HiGHS");
+        let solver_version = version["solverVersion"].as_str().unwrap();
+        let parts: Vec<&str> = solver_version.split('.').collect();
+        assert!(parts.len() >= 2, "{}", solver_version);
+        assert!(parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())));
+    }
+
+    #[tokio::test]
+    async fn a_timetable_too_large_to_draw_is_a_bad_request() {
+        let uri = "/v1/schedule/solve.png";
+        let json = [(header::CONTENT_TYPE, "application/json")];
+        let (status, _) = send(open_router(), "POST", uri, &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK);
+        let body = JSON_INPUT.replace("\"totalTimeslots\": 2", "\"totalTimeslots\": 100000");
+        let (status, response) = send(open_router(), "POST", uri, &json, &body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("too many to draw"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn a_configured_token_is_required_except_for_the_health_check() {
+        let secured = || router(ServerConfig::default(), Some("secret".into()), false);
+        let solve = "/v1/schedule/solve";
+
+        let (status, _) = send(secured(), "POST", solve, &[], JSON_INPUT).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let wrong = [(header::AUTHORIZATION, "Bearer secreT")];
+        let (status, _) = send(secured(), "POST", solve, &wrong, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let right = [
+            (header::AUTHORIZATION, "Bearer secret"),
+            (header::CONTENT_TYPE, "application/json"),
+        ];
+        let (status, response) = send(secured(), "POST", solve, &right, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK, "{}", response);
+
+        let (status, response) = send(secured(), "GET", "/healthz", &[], "").await;
+        assert_eq!((status, response.as_str()), (StatusCode::OK, "ok"));
+    }
+
+    #[tokio::test]
+    async fn a_logged_body_still_reaches_the_handler() {
+        let router = router(ServerConfig::default(), None, true);
+        let json = [(header::CONTENT_TYPE, "application/json")];
+        let (status, response) =
+            send(router, "POST", "/v1/schedule/solve.csv", &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK, "{}", response);
+        assert!(response.lines().count() > 1, "{}", response);
+    }
+
+    #[tokio::test]
+    async fn an_anonymous_body_is_refused_before_it_is_logged() {
+        let router = router(ServerConfig::default(), Some("secret".into()), true);
+        // larger than the logging limit, so reaching the logger would give 413 rather than 401
+        let body = " ".repeat(MAX_LOGGED_BODY_BYTES + 1);
+        let (status, _) = send(router, "POST", "/v1/schedule/solve", &[], &body).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn include_unmet_false_drops_the_field_but_keeps_the_score() {
+        // two courses of one instructor in two slots; only one of them can start in the morning
+        let body = serde_json::json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [
+                {"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]},
+                {"id": 2, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]}
+            ],
+            "totalTimeslots": 2
+        })
+        .to_string();
+        let solve = |query: &'static str| {
+            let body = body.clone();
+            async move {
+                let uri = format!("/v1/schedule/solve{}", query);
+                let json = [(header::CONTENT_TYPE, "application/json")];
+                let (status, response) = send(open_router(), "POST", &uri, &json, &body).await;
+                assert_eq!(status, StatusCode::OK, "{}", response);
+                serde_json::from_str::<serde_json::Value>(&response).unwrap()
+            }
+        };
+        let full = solve("").await;
+        assert!(!full["unmetSoftConstraints"].as_array().unwrap().is_empty());
+
+        let trimmed = solve("?include_unmet=false").await;
+        assert!(trimmed.get("unmetSoftConstraints").is_none());
+        assert_eq!(trimmed["score"], full["score"]);
+        assert_eq!(trimmed["assignments"], full["assignments"]);
+    }
+
+    #[tokio::test]
+    async fn a_job_streams_its_log_and_then_its_status() {
+        let router = open_router();
+        let json = [(header::CONTENT_TYPE, "application/json")];
+        let (status, job) =
+            send(router.clone(), "POST", "/v1/schedule/jobs", &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{}", job);
+        let id = serde_json::from_str::<serde_json::Value>(&job).unwrap()["id"].clone();
+
+        let uri = format!("/v1/schedule/solve/events/{}", id);
+        let events = send(router.clone(), "GET", &uri, &[], "");
+        let (status, events) = tokio::time::timeout(Duration::from_secs(60), events).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let first_status = events.find("event: status").expect(&events);
+        let first_log = events.find("event: log").expect(&events);
+        assert!(first_log < first_status, "{}", events);
+        assert!(events.contains(r#""status":"completed""#), "{}", events);
+
+        let (status, _) = send(router, "GET", "/v1/schedule/solve/events/999", &[], "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn an_unknown_gantt_time_zone_is_a_bad_request() {
+        let json = [(header::CONTENT_TYPE, "application/json")];
+        let uri = "/v1/schedule/solve/gantt?tz=Mars/Olympus_Mons";
+        let (status, response) = send(open_router(), "POST", uri, &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("Mars/Olympus_Mons"), "{}", response);
+
+        let uri = "/v1/schedule/solve/gantt?tz=America/New_York";
+        let (status, response) = send(open_router(), "POST", uri, &json, JSON_INPUT).await;
+        assert_eq!(status, StatusCode::OK, "{}", response);
+    }
+}
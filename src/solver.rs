@@ -1,308 +1,4804 @@
 use crate::data::{
-    Assignment, Course, CourseId, Instructor, InstructorId, Room, RoomId, SchedulingInput,
-    SchedulingOutput, Timeslot, UnmetSoftConstraint,
+    input_fingerprint, free_slots, AdjacencyStatus, Assignment, CohortRoomChanges, Course,
+    CourseId, CourseSections, DayOverflow, DepartmentSpan, Instructor, InstructorId,
+    InstructorIdleSlots, InstructorLatestEnd, InstructorRoomDistance, ObjectiveMode,
+    PrefilterStats, ReservedRoomUsage, Room, RoomId, SchedulingInput, SchedulingOutput,
+    SoftConstraintSummary, SolveMethod, SolveMode, SolveStatus, SolverWeights, StudentGroupId,
+    TargetDeviation, Timeslot, UnavailabilityOverride, UnmetSoftConstraint,
+    DEFAULT_OVERRIDE_COST, DEFAULT_SOLUTION_TOLERANCE,
 };
+use crate::calendar;
+use crate::decompose;
+use crate::heuristic::greedy_solve;
+use crate::incumbent::{ReportIncumbent, run_highs};
+use crate::model::ModelBuilder;
+use crate::validation::validate_input;
 use good_lp::variable;
 use good_lp::{
-    Expression, ProblemVariables, Solution, SolverModel, Variable, constraint, default_solver,
+    Expression, IntoAffineExpression, ResolutionError, Solution, SolutionStatus, SolverModel,
+    Variable, default_solver,
 };
+use good_lp::solvers::highs::HighsOptionValue;
 use itertools::Itertools;
-use log::{info, trace};
-use std::collections::{HashMap, HashSet};
+use log::{info, trace, warn};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+/// Errors that can occur while building or solving a scheduling model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    /// The input is malformed or internally inconsistent.
+    InvalidInput(String),
+    /// No schedule satisfies all hard constraints.
+    Infeasible(String),
+    /// The ILP solver failed for a reason other than infeasibility.
+    SolverFailure(String),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            SolveError::Infeasible(msg) => write!(f, "Infeasible: {}", msg),
+            SolveError::SolverFailure(msg) => write!(f, "Solver failure: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// A candidate (course, room, start slot) together with the variable that selects it.
+type CandidateVar = ((CourseId, RoomId, Timeslot), Variable);
+
+/// Per candidate of a course with eligible instructors, one variable per instructor who could
+/// teach it there.
+type InstructorVarsMap = HashMap<(CourseId, RoomId, Timeslot), Vec<(InstructorId, Variable)>>;
+
+/// The unweighted soft constraint terms of the objective. Per-entity weights (such as an
+/// instructor's back-to-back weight) are already folded in; the global weights are not.
+#[derive(Default)]
+struct ObjectiveTerms {
+    morning: Expression,
+    back_to_back: Expression,
+    target_deviation: Expression,
+    preferred_day: Expression,
+    /// Days an instructor with `avoid_single_course_days` teaches exactly one course.
+    single_course_days: Expression,
+    home_room: Expression,
+    department_span: Expression,
+    adjacency: Expression,
+    capacity_slack: Expression,
+    day_preference: Expression,
+    /// Weighted courses beyond the `day_soft_caps`.
+    day_overflow: Expression,
+    reserved_room_use: Expression,
+    unavailability_overrides: Expression,
+    /// Latest end slot of any course.
+    makespan: Expression,
+    /// Number of optional courses that are scheduled.
+    optional_scheduled: Expression,
+    /// Number of timeslots running exactly one course; only modelled when the session's
+    /// input has a nonzero `isolation_weight`.
+    isolated_slots: Option<Expression>,
+    /// Room distance between every two courses of an instructor; only modelled with a
+    /// nonzero `room_clustering_weight`.
+    room_clustering: Option<Expression>,
+    /// Idle slots between the classes of every instructor's days; only modelled with a
+    /// nonzero `idle_time_weight`.
+    idle_time: Option<Expression>,
+    /// Rooms beyond the first each student group uses on each day; only modelled with a
+    /// nonzero `cohort_room_changes_weight`.
+    cohort_room_changes: Option<Expression>,
+    /// Instructors who teach at least one course; only modelled with a nonzero
+    /// `minimize_instructors_weight`.
+    instructors_used: Option<Expression>,
+    /// Duration times morning reward of every course starting in the morning.
+    long_course_morning: Expression,
+    /// Replaces the weighted objective in `MinimizeLatestInstructorSlot` mode.
+    latest_instructor_end: Option<Expression>,
+    /// Same-day exams of student groups, scaled so a single one outweighs any earliness, and
+    /// the start day of every exam weighted by its difficulty; replaces the weighted objective
+    /// in `ExamSpread` mode.
+    exam_spread: Option<(Expression, Expression)>,
+    /// Warm start assignments that are not kept; replaces the weighted objective, in either
+    /// mode, when `repair_warm_start` is set.
+    warm_start_changes: Option<Expression>,
+    /// Penalised violations of every relaxed hard constraint, keyed by its name.
+    relaxed_violations: Vec<(&'static str, Expression)>,
+}
+
+impl ObjectiveTerms {
+    /// Name of a weight that `weights` sets but whose term was left out of the model, since
+    /// the session's input had it at zero; `None` when the weighted objective is not in use.
+    fn unmodelled_weight(&self, weights: &SolverWeights) -> Option<&'static str> {
+        if self.warm_start_changes.is_some()
+            || self.latest_instructor_end.is_some()
+            || self.exam_spread.is_some()
+        {
+            return None;
+        }
+        [
+            ("isolationWeight", weights.isolation_weight, self.isolated_slots.is_some()),
+            (
+                "roomClusteringWeight",
+                weights.room_clustering_weight,
+                self.room_clustering.is_some(),
+            ),
+            ("idleTimeWeight", weights.idle_time_weight, self.idle_time.is_some()),
+            (
+                "cohortRoomChangesWeight",
+                weights.cohort_room_changes_weight,
+                self.cohort_room_changes.is_some(),
+            ),
+            (
+                "minimizeInstructorsWeight",
+                weights.minimize_instructors_weight,
+                self.instructors_used.is_some(),
+            ),
+        ]
+        .into_iter()
+        .find(|(_, weight, modelled)| *weight != 0.0 && !modelled)
+        .map(|(name, _, _)| name)
+    }
+
+    fn objective(&self, weights: &SolverWeights) -> Expression {
+        self.weighted_terms(weights)
+            .into_iter()
+            .map(|(_, term)| term)
+            .sum()
+    }
+
+    /// The signed, weighted contribution of every term, keyed by its name in the output.
+    fn weighted_terms(&self, weights: &SolverWeights) -> Vec<(&'static str, Expression)> {
+        // these stand in for hard constraints, so they apply in every objective mode
+        let constraint_terms = [
+            ("unavailabilityOverrides", -self.unavailability_overrides.clone()),
+            ("optionalCourses", weights.optional_course * self.optional_scheduled.clone()),
+        ]
+        .into_iter()
+        .chain(self.relaxed_violations.iter().map(|(name, penalty)| (*name, -penalty.clone())));
+        if let Some(changes) = &self.warm_start_changes {
+            let mut terms = vec![("warmStartChanges", -changes.clone())];
+            terms.extend(constraint_terms);
+            return terms;
+        }
+        if let Some(latest_end) = &self.latest_instructor_end {
+            let mut terms = vec![("latestInstructorEnd", -latest_end.clone())];
+            terms.extend(constraint_terms);
+            return terms;
+        }
+        if let Some((clustering, earliness)) = &self.exam_spread {
+            let mut terms =
+                vec![("examClustering", -clustering.clone()), ("examEarliness", -earliness.clone())];
+            terms.extend(constraint_terms);
+            return terms;
+        }
+        let mut terms = vec![
+            ("morningPreference", weights.morning_preference * self.morning.clone()),
+            ("backToBackPenalty", -weights.back_to_back_penalty * self.back_to_back.clone()),
+            ("targetDeviation", -self.target_deviation.clone()),
+            ("preferredDay", weights.preferred_day * self.preferred_day.clone()),
+            ("singleCourseDays", -weights.single_course_day * self.single_course_days.clone()),
+            ("homeRoom", weights.home_room * self.home_room.clone()),
+            (
+                "departmentCompactness",
+                -weights.department_compactness * self.department_span.clone(),
+            ),
+            ("adjacency", weights.adjacency * self.adjacency.clone()),
+            ("dayWeights", self.day_preference.clone()),
+            ("daySoftCaps", -self.day_overflow.clone()),
+            ("reservePreference", -self.reserved_room_use.clone()),
+        ];
+        terms.extend(constraint_terms);
+        // touches every assignment variable, so leave it out entirely when unused
+        if weights.rightsizing != 0.0 {
+            terms.push(("rightsizing", -weights.rightsizing * self.capacity_slack.clone()));
+        }
+        if weights.compact_schedule != 0.0 {
+            terms.push(("compactSchedule", -weights.compact_schedule * self.makespan.clone()));
+        }
+        if let Some(isolated_slots) = &self.isolated_slots
+            && weights.isolation_weight != 0.0
+        {
+            terms.push(("isolatedSlots", -weights.isolation_weight * isolated_slots.clone()));
+        }
+        if let Some(room_clustering) = &self.room_clustering
+            && weights.room_clustering_weight != 0.0
+        {
+            terms.push((
+                "roomClustering",
+                -weights.room_clustering_weight * room_clustering.clone(),
+            ));
+        }
+        if let Some(idle_time) = &self.idle_time
+            && weights.idle_time_weight != 0.0
+        {
+            terms.push(("idleTime", -weights.idle_time_weight * idle_time.clone()));
+        }
+        if let Some(room_changes) = &self.cohort_room_changes
+            && weights.cohort_room_changes_weight != 0.0
+        {
+            terms.push((
+                "cohortRoomChanges",
+                -weights.cohort_room_changes_weight * room_changes.clone(),
+            ));
+        }
+        if let Some(instructors_used) = &self.instructors_used
+            && weights.minimize_instructors_weight != 0.0
+        {
+            terms.push((
+                "instructorsUsed",
+                -weights.minimize_instructors_weight * instructors_used.clone(),
+            ));
+        }
+        if weights.long_course_morning_weight != 0.0 {
+            terms.push((
+                "longCourseMorning",
+                weights.long_course_morning_weight * self.long_course_morning.clone(),
+            ));
+        }
+        terms
+    }
+}
+
+/// Largest denominator a coefficient is approximated with by [`scale_to_integers`], so
+/// weights such as 0.1, 0.25 or 1/3 stay exact.
+const MAX_COEFFICIENT_DENOMINATOR: u64 = 1000;
+/// Largest factor [`scale_to_integers`] scales by; beyond it coefficients are rounded.
+const MAX_OBJECTIVE_SCALE: u64 = 1_000_000;
+
+/// Multiplies the objective by the least common denominator of its coefficients and rounds
+/// them, so every coefficient is an integer. Returns the scaled objective and the factor.
+fn scale_to_integers(objective: &Expression) -> (Expression, f64) {
+    let mut scale: u64 = 1;
+    let values = objective
+        .linear_coefficients()
+        .map(|(_, coefficient)| coefficient)
+        .chain([objective.constant()]);
+    for value in values {
+        let denominator = fraction_denominator(value);
+        let combined = scale / gcd(scale, denominator) * denominator;
+        if combined > MAX_OBJECTIVE_SCALE {
+            warn!(
+                "Objective coefficients have no common denominator up to {}; rounding them.",
+                MAX_OBJECTIVE_SCALE
+            );
+            scale = MAX_OBJECTIVE_SCALE;
+            break;
+        }
+        scale = combined;
+    }
+    let factor = scale as f64;
+    let mut scaled = Expression::from((objective.constant() * factor).round());
+    for (var, coefficient) in objective.linear_coefficients() {
+        scaled.add_mul((coefficient * factor).round(), var);
+    }
+    (scaled, factor)
+}
+
+/// Denominator of the closest fraction to `value` with a denominator of at most
+/// [`MAX_COEFFICIENT_DENOMINATOR`], from its continued fraction expansion.
+fn fraction_denominator(value: f64) -> u64 {
+    let mut remainder = value.abs().fract();
+    let (mut previous, mut current): (u64, u64) = (0, 1);
+    while remainder > 1e-9 {
+        let inverse = 1.0 / remainder;
+        let next = inverse.floor() as u64 * current + previous;
+        if next > MAX_COEFFICIENT_DENOMINATOR {
+            break;
+        }
+        (previous, current) = (current, next);
+        remainder = inverse - inverse.floor();
+    }
+    current
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Version of the HiGHS library linked into this build, e.g. "1.11.0".
+pub fn solver_version() -> String {
+    // SAFETY: Highs_version returns a pointer to a static, NUL-terminated string
+    let version = unsafe { CStr::from_ptr(highs_sys::Highs_version()) };
+    version.to_string_lossy().into_owned()
+}
+
+/// Receives the best schedule found so far while a solve is still running.
+pub type IncumbentSink = Arc<dyn Fn(SchedulingOutput) + Send + Sync>;
+
+/// solves the scheduling problem using the HiGHs ILP solver.
+pub fn solve(input: &SchedulingInput) -> Result<SchedulingOutput, SolveError> {
+    solve_with_log(input, None, None)
+}
+
+/// Like [`solve`], but HiGHS also writes its log into `log_dir` as it goes: `solve.log`, or
+/// one `part<i>.log` per component when the input is decomposed. Every improving schedule
+/// HiGHS finds on the way is handed to `on_incumbent`; a decomposed input only reports once
+/// each component has a schedule.
+pub fn solve_logged(
+    input: &SchedulingInput,
+    log_dir: &Path,
+    on_incumbent: IncumbentSink,
+) -> Result<SchedulingOutput, SolveError> {
+    solve_with_log(input, Some(log_dir), Some(on_incumbent))
+}
+
+fn solve_with_log(
+    input: &SchedulingInput,
+    log_dir: Option<&Path>,
+    on_incumbent: Option<IncumbentSink>,
+) -> Result<SchedulingOutput, SolveError> {
+    info!("Solving input {}", input_fingerprint(input));
+    if input.decompose {
+        // errors should refer to the whole input, not to whichever component hits them first
+        if let Some(error) = validate_input(input).errors.into_iter().next() {
+            return Err(SolveError::InvalidInput(error));
+        }
+        // sections have to be split like any course, not copied into every component
+        let input = &input.with_sections_expanded().map_err(SolveError::InvalidInput)?;
+        let resolved = calendar::with_dates_as_slots(&input.with_durations_in_slots());
+        let candidates = possible_assignments(&resolved);
+        if let Some(parts) = decompose::split_components(input, &candidates) {
+            info!("Solving {} independent components in parallel", parts.len());
+            // the latest schedule of every component, merged once each has one
+            let latest: Arc<Mutex<Vec<Option<SchedulingOutput>>>> =
+                Arc::new(Mutex::new(vec![None; parts.len()]));
+            let part_sink = |index: usize| -> Option<IncumbentSink> {
+                let on_incumbent = on_incumbent.clone()?;
+                let latest = latest.clone();
+                Some(Arc::new(move |output| {
+                    let mut latest = latest.lock().unwrap();
+                    latest[index] = Some(output);
+                    if let Some(outputs) = latest.iter().cloned().collect::<Option<Vec<_>>>() {
+                        on_incumbent(decompose::merge_outputs(outputs));
+                    }
+                }))
+            };
+            let outputs = std::thread::scope(|scope| {
+                let handles: Vec<_> = parts
+                    .iter()
+                    .enumerate()
+                    .map(|(index, part)| {
+                        let log_file = log_dir.map(|dir| dir.join(format!("part{}.log", index)));
+                        let on_incumbent = part_sink(index);
+                        scope.spawn(move || solve_session(part, log_file, on_incumbent))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle.join().unwrap_or_else(|_| {
+                            Err(SolveError::SolverFailure("A component solve panicked.".to_string()))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+            return Ok(decompose::merge_outputs(outputs));
+        }
+    }
+    solve_session(input, log_dir.map(|dir| dir.join("solve.log")), on_incumbent)
+}
+
+fn solve_session(
+    input: &SchedulingInput,
+    log_file: Option<PathBuf>,
+    on_incumbent: Option<IncumbentSink>,
+) -> Result<SchedulingOutput, SolveError> {
+    let weights = input.weights.clone().unwrap_or_default();
+    let mut session = SolverSession::new(input)?;
+    session.log_file = log_file;
+    session.incumbent_sink = on_incumbent.clone();
+    let output = session.resolve_with_weights(weights)?;
+    // the final schedule is the last incumbent, even when HiGHS never reported one, e.g.
+    // because presolve solved the model outright
+    if let Some(on_incumbent) = on_incumbent {
+        on_incumbent(output.clone());
+    }
+    Ok(output)
+}
+
+/// Builds the model for `input` and renders it in CPLEX LP format without solving it.
+pub fn to_lp_format(input: &SchedulingInput) -> Result<String, SolveError> {
+    let weights = input.weights.clone().unwrap_or_default();
+    SolverSession::new(input)?.to_lp_format(&weights)
+}
+
+/// A built scheduling model that can be re-solved with different objective weights.
+///
+/// Pre-filtering and constraint generation happen once in [`SolverSession::new`]; each call
+/// to [`SolverSession::resolve_with_weights`] only swaps the objective and runs the solver,
+/// warm-started from the previous solution. Anything that changes the structure of the
+/// problem (rooms, courses, instructors, availability, groups, ...) requires a fresh session,
+/// and so does turning on a weight whose term needs extra variables (isolation, room
+/// clustering, idle time, cohort room changes, instructors used) when the session's input had
+/// it at zero.
+pub struct SolverSession {
+    input: SchedulingInput,
+    model: ModelBuilder,
+    assignment_vars_map: HashMap<(CourseId, RoomId, Timeslot), Variable>,
+    instructor_vars_map: InstructorVarsMap,
+    /// Rooms each virtual room of a `combinable` set stands for.
+    room_members: HashMap<RoomId, Vec<RoomId>>,
+    objective_terms: ObjectiveTerms,
+    cancelled_courses: Vec<CourseId>,
+    prefilter_stats: PrefilterStats,
+    last_solution: Option<Vec<(Variable, f64)>>,
+    /// File HiGHS writes its log to instead of the console.
+    log_file: Option<PathBuf>,
+    /// Receives every improving schedule while HiGHS is still searching.
+    incumbent_sink: Option<IncumbentSink>,
+    /// Random tie-break added to the objective the solver sees, but left out of every
+    /// reported value; see [`SolverSession::perturb_objective`].
+    perturbation: Option<Expression>,
+    /// Why no model was built: the candidates exceed `max_variables`, so every solve goes
+    /// straight to the heuristic.
+    oversized: Option<String>,
+}
+
+impl SolverSession {
+    /// Pre-filters the candidate assignments and builds every variable and constraint.
+    pub fn new(input: &SchedulingInput) -> Result<Self, SolveError> {
+        let report = validate_input(input);
+        for warning in &report.warnings {
+            warn!("{}", warning);
+        }
+        if let Some(error) = report.errors.into_iter().next() {
+            return Err(SolveError::InvalidInput(error));
+        }
+        let expanded = input.with_sections_expanded().map_err(SolveError::InvalidInput)?;
+        let mut input = calendar::with_dates_as_slots(&expanded.with_durations_in_slots());
+        // satisficing: any schedule that reaches the target will do
+        if input.min_objective.is_some() {
+            input.solve_mode = SolveMode::FirstFeasible;
+        }
+        // business rule: under-enrolled courses are cancelled before any variable exists
+        let cancelled_courses: Vec<CourseId> =
+            input.courses.iter().filter(|c| c.is_cancelled()).map(|c| c.id).collect();
+        if !cancelled_courses.is_empty() {
+            info!("Cancelling under-enrolled courses {:?}", cancelled_courses);
+            input.courses.retain(|c| !c.is_cancelled());
+            input.soft_adjacency.retain(|(first_id, second_id)| {
+                !cancelled_courses.contains(first_id) && !cancelled_courses.contains(second_id)
+            });
+            input.section_links.retain(|link| {
+                !cancelled_courses.contains(&link.lecture) && !cancelled_courses.contains(&link.lab)
+            });
+        }
+        // a frozen course is kept even when it is optional
+        let frozen_courses: Vec<CourseId> = input.frozen_placements().into_keys().collect();
+        for course in &mut input.courses {
+            if frozen_courses.contains(&course.id) {
+                course.optional = false;
+            }
+        }
+        let input = &input;
+        let frozen_placements = input.frozen_placements();
+        // lookups
+        let course_map: HashMap<CourseId, &Course> =
+            input.courses.iter().map(|c| (c.id, c)).collect();
+        let instructor_map: HashMap<InstructorId, &Instructor> =
+            input.instructors.iter().map(|i| (i.id, i)).collect();
+        let group_windows = group_windows(input);
+        let combined_rooms = input.combined_rooms();
+        let room_members: HashMap<RoomId, Vec<RoomId>> =
+            combined_rooms.iter().map(|(room, room_ids)| (room.id, room_ids.clone())).collect();
+        let all_rooms: Vec<&Room> =
+            input.rooms.iter().chain(combined_rooms.iter().map(|(room, _)| room)).collect();
+        // whether a candidate in room r_id occupies room_id, directly or as part of a
+        // combined room
+        let occupies = |r_id: &RoomId, room_id: RoomId| {
+            *r_id == room_id || room_members.get(r_id).is_some_and(|ids| ids.contains(&room_id))
+        };
+
+        //model setup
+        info!(
+            "Setting up ILP model with {} courses, {} rooms, and {} timeslots...",
+            input.courses.len(),
+            input.rooms.len(),
+            input.total_timeslots
+        );
+        let mut model = ModelBuilder::new();
+
+        // x_crt =  1 if course c is in room r at time t
+        //          0 otherwise
+        let all_possible_assignments = possible_assignments(input);
+        let prefilter_stats = PrefilterStats::new(
+            all_possible_assignments.len(),
+            input.courses.len() * input.rooms.len() * input.total_timeslots as usize,
+        );
+        trace!(
+            "Generated {} potential assignment variables out of a theoretical maximum of {}.",
+            prefilter_stats.candidates,
+            prefilter_stats.theoretical_max
+        );
+        // resource guard: refuse to build a model too large for the machine
+        if let Some(max_variables) = input.max_variables
+            && all_possible_assignments.len() > max_variables
+        {
+            let message = format!(
+                "Pre-filtering left {} candidate assignments, more than maxVariables ({}).",
+                all_possible_assignments.len(),
+                max_variables
+            );
+            if !input.fallback_to_heuristic {
+                return Err(SolveError::InvalidInput(message));
+            }
+            return Ok(SolverSession {
+                input: input.clone(),
+                model: ModelBuilder::new(),
+                assignment_vars_map: HashMap::new(),
+                instructor_vars_map: HashMap::new(),
+                room_members,
+                objective_terms: ObjectiveTerms::default(),
+                cancelled_courses,
+                prefilter_stats,
+                last_solution: None,
+                log_file: None,
+                incumbent_sink: None,
+                perturbation: None,
+                oversized: Some(message),
+            });
+        }
+
+        if all_possible_assignments.is_empty() {
+            return Err(SolveError::Infeasible("No possible assignments found after pre-filtering. The problem might be too constrained.".to_string()));
+        }
+        // an optional course that fits nowhere is just left unscheduled
+        for course in input.courses.iter().filter(|c| !c.optional) {
+            if !all_possible_assignments.iter().any(|(c_id, _, _)| *c_id == course.id) {
+                let mut message =
+                    format!("Course {} has no feasible room and start slot.", course.id);
+                if frozen_placements.contains_key(&course.id) {
+                    message.push_str(" Its frozen assignment no longer fits.");
+                }
+                if let Some(windows) = group_windows.get(&course.id) {
+                    message.push_str(&format!(
+                        " It must fit within the student group daily window(s) {:?}.",
+                        windows
+                    ));
+                }
+                return Err(SolveError::Infeasible(message));
+            }
+        }
+        if input.require_all_rooms_used {
+            if input.rooms.len() > input.courses.len() {
+                return Err(SolveError::Infeasible(format!(
+                    "requireAllRoomsUsed needs a course in each of the {} rooms, but only {} course(s) can be scheduled.",
+                    input.rooms.len(),
+                    input.courses.len()
+                )));
+            }
+            for room in &input.rooms {
+                if !all_possible_assignments.iter().any(|(_, r_id, _)| occupies(r_id, room.id)) {
+                    return Err(SolveError::Infeasible(format!(
+                        "requireAllRoomsUsed needs a course in room {}, but no course fits it.",
+                        room.id
+                    )));
+                }
+            }
+        }
+
+        // decision map
+        let mut assignment_vars_map: HashMap<(CourseId, RoomId, Timeslot), Variable> =
+            HashMap::new();
+        let assignment_vars_vec =
+            model.add_vector(variable().binary(), all_possible_assignments.len());
+
+        for (i, (course_id, room_id, start_slot)) in all_possible_assignments.iter().enumerate() {
+            assignment_vars_map.insert((*course_id, *room_id, *start_slot), assignment_vars_vec[i]);
+        }
+
+        // y_icrt = 1 if instructor i teaches course c in room r at time t, for courses that
+        // pick one of their eligible instructors; only instructors free at t get a variable
+        let mut instructor_vars_map: InstructorVarsMap = HashMap::new();
+        for (course_id, room_id, start_slot) in &all_possible_assignments {
+            let course = course_map[course_id];
+            if course.eligible_instructors.is_none() {
+                continue;
+            }
+            let frozen_instructor =
+                frozen_placements.get(course_id).and_then(|placement| placement.instructor_id);
+            let choices = course
+                .possible_instructors()
+                .into_iter()
+                .filter(|i_id| frozen_instructor.is_none_or(|frozen| frozen == *i_id))
+                .filter(|i_id| {
+                    instructor_map
+                        .get(i_id)
+                        .is_some_and(|i| instructor_available(i, course, *start_slot, input))
+                })
+                .map(|i_id| (i_id, model.add_variable(variable().binary())))
+                .collect();
+            instructor_vars_map.insert((*course_id, *room_id, *start_slot), choices);
+        }
+        // every variable that puts a course in front of an instructor: x_crt for fixed
+        // instructors, y_icrt for chosen ones
+        let mut instructor_assignments: HashMap<InstructorId, Vec<CandidateVar>> = HashMap::new();
+        for (key, var) in &assignment_vars_map {
+            for instructor_id in course_map[&key.0].instructors() {
+                instructor_assignments.entry(instructor_id).or_default().push((*key, *var));
+            }
+        }
+        for (key, choices) in &instructor_vars_map {
+            for (instructor_id, var) in choices {
+                instructor_assignments.entry(*instructor_id).or_default().push((*key, *var));
+            }
+        }
+
+        //begin soft constraints
+        let mut back_to_back_links = Vec::new();
+        if input.total_timeslots > 1 {
+            for (instructor_id, candidates) in &instructor_assignments {
+                // a cap counts the pairs of every instructor, even those the objective ignores
+                let weight = match instructor_map.get(instructor_id) {
+                    Some(instructor)
+                        if instructor.back_to_back_weight != 0.0
+                            || input.max_back_to_back_pairs.is_some() =>
+                    {
+                        instructor.back_to_back_weight
+                    }
+                    _ => continue,
+                };
+                for k in 0..(input.total_timeslots - 1) {
+                    if input.day_of(k) != input.day_of(k + 1) {
+                        continue; // the last class of a day and the first of the next are not back-to-back
+                    }
+                    let starts_at_k_plus_1: Expression = candidates
+                        .iter()
+                        .filter(|((_, _, start_slot), _)| *start_slot == k + 1)
+                        .map(|(_, var)| *var)
+                        .sum();
+                    let ends_at_k: Expression = candidates
+                        .iter()
+                        .filter(|((c_id, _, start_slot), _)| {
+                            let course = course_map.get(c_id).unwrap();
+                            (*start_slot + course.duration_slots - 1) == k
+                        })
+                        .map(|(_, var)| *var)
+                        .sum();
+
+                    let penalty_var = model.add_variable(variable().binary());
+                    back_to_back_links.push((starts_at_k_plus_1, ends_at_k, penalty_var, weight));
+                }
+            }
+        }
+        // a pair is adjacent when one course ends at k - 1 and the other starts at k
+        let mut adjacency_links = Vec::new();
+        for (first_id, second_id) in &input.soft_adjacency {
+            for k in 1..input.total_timeslots {
+                if input.day_of(k - 1) != input.day_of(k) {
+                    continue;
+                }
+                for (before, after) in [(first_id, second_id), (second_id, first_id)] {
+                    let ends_at_k_minus_1: Expression = assignment_vars_map
+                        .iter()
+                        .filter(|((c_id, _, start_slot), _)| {
+                            c_id == before
+                                && *start_slot + course_map.get(c_id).unwrap().duration_slots == k
+                        })
+                        .map(|(_, var)| *var)
+                        .sum();
+                    let starts_at_k: Expression = assignment_vars_map
+                        .iter()
+                        .filter(|((c_id, _, start_slot), _)| c_id == after && *start_slot == k)
+                        .map(|(_, var)| *var)
+                        .sum();
+                    let reward_var = model.add_variable(variable().binary());
+                    adjacency_links.push((starts_at_k, ends_at_k_minus_1, reward_var));
+                }
+            }
+        }
+        // [first, last) bounds every course of a department; their difference is the span
+        let department_courses: HashMap<u32, Vec<&Course>> = input
+            .courses
+            .iter()
+            .filter_map(|c| c.department_id.map(|d| (d, c)))
+            .into_group_map();
+        let mut department_bounds = Vec::new();
+        for courses in department_courses.values().filter(|courses| courses.len() > 1) {
+            let first = model.add_variable(variable().min(0).max(input.total_timeslots));
+            let last = model.add_variable(variable().min(0).max(input.total_timeslots));
+            let starts: Vec<(Expression, Expression, u32)> = courses
+                .iter()
+                .map(|course| {
+                    let course_vars = assignment_vars_map
+                        .iter()
+                        .filter(|((c_id, _, _), _)| *c_id == course.id);
+                    let start: Expression = course_vars
+                        .clone()
+                        .map(|((_, _, start_slot), var)| *start_slot as f64 * *var)
+                        .sum();
+                    let scheduled: Expression = course_vars.map(|(_, var)| *var).sum();
+                    (start, scheduled, course.duration_slots)
+                })
+                .collect();
+            department_bounds.push((first, last, starts));
+        }
+        // soft constraints
+        // global weights are applied per solve, see ObjectiveTerms::objective
+        let morning: Expression = assignment_vars_map
+            .iter()
+            .filter(|((_, _, start_slot), _)| input.is_morning(*start_slot))
+            .map(|((c_id, _, _), var)| {
+                let course = course_map.get(c_id).unwrap();
+                morning_reward(course, input) as f64 * *var
+            })
+            .sum();
+        let long_course_morning: Expression = assignment_vars_map
+            .iter()
+            .filter(|((_, _, start_slot), _)| input.is_morning(*start_slot))
+            .map(|((c_id, _, _), var)| {
+                let course = course_map[c_id];
+                (course.duration_slots as f64 * morning_reward(course, input) as f64) * *var
+            })
+            .sum();
+        let back_to_back: Expression = back_to_back_links
+            .iter()
+            .map(|(_, _, var, weight)| *weight * *var)
+            .sum();
+        // start slot is a one-hot sum over the x_crt, so |start - target| is linear in them
+        let target_deviation: Expression = assignment_vars_map
+            .iter()
+            .filter_map(|((c_id, _, start_slot), var)| {
+                let course = course_map.get(c_id).unwrap();
+                course.target_slot.map(|target| {
+                    course.target_weight * start_slot.abs_diff(target) as f64 * *var
+                })
+            })
+            .sum();
+        // counted once per co-instructor whose preferred day it is
+        let preferred_day: Expression = instructor_assignments
+            .iter()
+            .filter_map(|(i_id, candidates)| Some((instructor_map.get(i_id)?, candidates)))
+            .flat_map(|(instructor, candidates)| {
+                candidates.iter().filter(|((_, _, start_slot), _)| {
+                    instructor.preferred_days.contains(&input.weekday_of(*start_slot))
+                })
+            })
+            .map(|(_, var)| *var)
+            .sum();
+        // single_{i,d} = any_{i,d} - many_{i,d} as for isolated slots below, over the courses
+        // instructor i starts on day d
+        let mut single_course_days = Expression::from(0);
+        for (i_id, candidates) in &instructor_assignments {
+            if !instructor_map.get(i_id).is_some_and(|i| i.avoid_single_course_days) {
+                continue;
+            }
+            let by_day = candidates
+                .iter()
+                .into_group_map_by(|((_, _, start_slot), _)| input.day_of(*start_slot));
+            for day_candidates in by_day.values() {
+                let max_courses =
+                    day_candidates.iter().map(|((c_id, _, _), _)| c_id).unique().count() as f64;
+                let taught: Expression = day_candidates.iter().map(|(_, var)| *var).sum();
+                let any = model.add_variable(variable().binary());
+                let many = model.add_variable(variable().binary());
+                model.add_leq(taught.clone(), any + (max_courses - 1.0) * many);
+                model.add_geq(taught, 2.0 * many);
+                model.add_leq(many, any);
+                single_course_days += any - many;
+            }
+        }
+        // likewise once per co-instructor whose home room it is
+        let home_room: Expression = instructor_assignments
+            .iter()
+            .filter_map(|(i_id, candidates)| Some((instructor_map.get(i_id)?, candidates)))
+            .flat_map(|(instructor, candidates)| {
+                candidates
+                    .iter()
+                    .filter(|((_, r_id, _), _)| instructor.home_room == Some(*r_id))
+            })
+            .map(|(_, var)| *var)
+            .sum();
+        let department_span: Expression = department_bounds
+            .iter()
+            .map(|(first, last, _)| *last - *first)
+            .sum();
+        let adjacency: Expression = adjacency_links.iter().map(|(_, _, var)| *var).sum();
+        let room_capacity: HashMap<RoomId, u32> =
+            all_rooms.iter().map(|r| (r.id, r.capacity)).collect();
+        let capacity_slack: Expression = assignment_vars_map
+            .iter()
+            .map(|((c_id, r_id, _), var)| {
+                let course = course_map.get(c_id).unwrap();
+                room_capacity[r_id].saturating_sub(course.required_capacity) as f64 * *var
+            })
+            .sum();
+        // penalty_c for every course c in a room with fewer seats than it requires
+        let mut relaxed_violations = Vec::new();
+        if let Some(penalty) = input.relaxations.capacity {
+            let overfilled: Expression = assignment_vars_map
+                .iter()
+                .filter(|((c_id, r_id, _), _)| {
+                    room_capacity[r_id] < course_map[c_id].required_capacity
+                })
+                .map(|(_, var)| *var)
+                .sum();
+            relaxed_violations.push(("roomCapacity", penalty * overfilled));
+        }
+        let day_preference: Expression = match &input.day_weights {
+            Some(day_weights) => assignment_vars_map
+                .iter()
+                .map(|((_, _, start_slot), var)| {
+                    day_weights[input.day_of(*start_slot) as usize] * *var
+                })
+                .sum(),
+            None => Expression::from(0),
+        };
+        // overflow_d >= starts on day d - cap, and the objective keeps it down
+        let mut day_overflow = Expression::from(0);
+        for (day, soft_cap, weight) in &input.day_soft_caps {
+            let starts: Expression = assignment_vars_map
+                .iter()
+                .filter(|((_, _, start_slot), _)| input.day_of(*start_slot) == *day)
+                .map(|(_, var)| *var)
+                .sum();
+            let overflow = model.add_variable(variable().min(0));
+            model.add_geq(overflow - starts, -(*soft_cap as f64));
+            day_overflow += *weight * overflow;
+        }
+        let reserved_room_use: Expression = assignment_vars_map
+            .iter()
+            .filter_map(|((_, r_id, _), var)| {
+                let room = all_rooms.iter().find(|r| r.id == *r_id)?;
+                (room.reserve_preference > 0.0).then(|| room.reserve_preference * *var)
+            })
+            .sum();
+        // only non-zero when allow_override_unavailability let such candidates through
+        let unavailability_overrides: Expression = instructor_assignments
+            .iter()
+            .filter_map(|(i_id, candidates)| Some((instructor_map.get(i_id)?, candidates)))
+            .flat_map(|(instructor, candidates)| {
+                candidates.iter().map(|((c_id, _, start_slot), var)| {
+                    let course = course_map[c_id];
+                    let cost = unavailability_override(instructor, course, *start_slot, input)
+                        .map_or(0.0, |o| o.cost);
+                    cost * *var
+                })
+            })
+            .sum();
+        let optional_scheduled: Expression = assignment_vars_map
+            .iter()
+            .filter(|((c_id, _, _), _)| course_map[c_id].optional)
+            .map(|(_, var)| *var)
+            .sum();
+        // makespan >= end slot of every course
+        let mut latest_end_bounds = Vec::new();
+        let makespan = model.add_variable(variable().min(0).max(input.total_timeslots));
+        for course in &input.courses {
+            let end_slot: Expression = assignment_vars_map
+                .iter()
+                .filter(|((c_id, _, _), _)| *c_id == course.id)
+                .map(|((_, _, start_slot), var)| (start_slot + course.duration_slots) as f64 * *var)
+                .sum();
+            latest_end_bounds.push((makespan, end_slot));
+        }
+        // isolated_k = any_k - many_k is 1 exactly when slot k runs a single course:
+        // many_k can only be set with two or more courses running, and any_k has to be set
+        // whenever a course runs that many_k does not cover
+        let isolation_weight = input.weights.as_ref().map_or(0.0, |w| w.isolation_weight);
+        let isolated_slots = (isolation_weight != 0.0).then(|| {
+            let mut isolated = Expression::from(0);
+            for k in 0..input.total_timeslots {
+                let running: Vec<Variable> = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, start_slot), _)| {
+                        let course = course_map.get(c_id).unwrap();
+                        k >= *start_slot && k < *start_slot + course.duration_slots
+                    })
+                    .map(|(_, var)| *var)
+                    .collect();
+                let max_running =
+                    max_running_courses(input, input.rooms.len(), running.len()) as f64;
+                let running: Expression = running.into_iter().sum();
+                let any = model.add_variable(variable().binary());
+                let many = model.add_variable(variable().binary());
+                model.add_leq(running.clone(), any + (max_running - 1.0) * many);
+                model.add_geq(running, 2.0 * many);
+                model.add_leq(many, any);
+                isolated += any - many;
+            }
+            isolated
+        });
+        // both_{c,r,c',r'} >= in_{c,r} + in_{c',r'} - 1 for every two courses c, c' of an
+        // instructor, where in_{c,r} sums the instructor's variables of c in room r; only the
+        // lower bound is needed since the objective keeps it down
+        let clustering_weight = input.weights.as_ref().map_or(0.0, |w| w.room_clustering_weight);
+        let room_clustering = (clustering_weight != 0.0).then(|| {
+            let distances = room_distance_map(input);
+            let mut travel = Expression::from(0);
+            if distances.is_empty() {
+                return travel;
+            }
+            for candidates in instructor_assignments.values() {
+                let mut in_room: BTreeMap<CourseId, BTreeMap<RoomId, Expression>> =
+                    BTreeMap::new();
+                for ((c_id, r_id, _), var) in candidates {
+                    *in_room.entry(*c_id).or_default().entry(*r_id).or_default() += *var;
+                }
+                let courses: Vec<_> = in_room.values().collect();
+                for (index, first_rooms) in courses.iter().enumerate() {
+                    for second_rooms in &courses[index + 1..] {
+                        for (first_room, in_first) in first_rooms.iter() {
+                            for (second_room, in_second) in second_rooms.iter() {
+                                let Some(distance) = distances
+                                    .get(&(*first_room, *second_room))
+                                    .filter(|d| **d > 0.0)
+                                else {
+                                    continue;
+                                };
+                                let both = model.add_variable(variable().min(0).max(1));
+                                model.add_geq(both, in_first.clone() + in_second.clone() - 1);
+                                travel += *distance * both;
+                            }
+                        }
+                    }
+                }
+            }
+            travel
+        });
+        // idle_{i,d} >= last_{i,d} - first_{i,d} - busy_{i,d}, where first and last are
+        // pushed below every start and above every end of instructor i on day d; on a free
+        // day last - first is negative, so idle_{i,d} stays at 0
+        let idle_weight = input.weights.as_ref().map_or(0.0, |w| w.idle_time_weight);
+        let idle_time = (idle_weight != 0.0).then(|| {
+            let mut idle_time = Expression::from(0);
+            for candidates in instructor_assignments.values() {
+                let by_day = candidates
+                    .iter()
+                    .into_group_map_by(|((_, _, start_slot), _)| input.day_of(*start_slot));
+                for (day, day_candidates) in by_day {
+                    let day_start = input.slot_at(day, 0);
+                    let day_end = day_start + input.slots_per_day();
+                    let first = model.add_variable(variable().min(day_start).max(day_end));
+                    let last = model.add_variable(variable().min(day_start).max(day_end));
+                    let idle = model.add_variable(variable().min(0));
+                    let mut busy = Expression::from(0);
+                    for ((c_id, _, start_slot), var) in day_candidates {
+                        let duration = course_map[c_id].duration_slots;
+                        let end_slot = *start_slot + duration;
+                        model.add_leq(first + (day_end - *start_slot) as f64 * *var, day_end);
+                        model.add_geq(last - (end_slot - day_start) as f64 * *var, day_start);
+                        busy += duration as f64 * *var;
+                    }
+                    model.add_geq(idle + first + busy, last);
+                    idle_time += idle;
+                }
+            }
+            idle_time
+        });
+        // changes_{g,d} >= sum over rooms r of used_{g,d,r} - 1, where used_{g,d,r} covers
+        // every candidate of group g's courses in room r on day d
+        let room_changes_weight =
+            input.weights.as_ref().map_or(0.0, |w| w.cohort_room_changes_weight);
+        let cohort_room_changes = (room_changes_weight != 0.0).then(|| {
+            let mut room_changes = Expression::from(0);
+            for group in &input.student_groups {
+                let by_day = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, _), _)| group.course_ids.contains(c_id))
+                    .into_group_map_by(|((_, _, start_slot), _)| input.day_of(*start_slot));
+                for day_candidates in by_day.values() {
+                    let by_room =
+                        day_candidates.iter().into_group_map_by(|((_, r_id, _), _)| *r_id);
+                    let mut rooms_used = Expression::from(0);
+                    for room_candidates in by_room.values() {
+                        let used = model.add_variable(variable().min(0).max(1));
+                        for (_, var) in room_candidates {
+                            model.add_geq(used, **var);
+                        }
+                        rooms_used += used;
+                    }
+                    let changes = model.add_variable(variable().min(0));
+                    model.add_geq(changes + 1, rooms_used);
+                    room_changes += changes;
+                }
+            }
+            room_changes
+        });
+        // used_i >= every variable that puts a course in front of instructor i
+        let instructors_weight =
+            input.weights.as_ref().map_or(0.0, |w| w.minimize_instructors_weight);
+        let instructors_used = (instructors_weight != 0.0).then(|| {
+            let mut instructors_used = Expression::from(0);
+            for candidates in instructor_assignments.values() {
+                let used = model.add_variable(variable().binary());
+                for (_, var) in candidates {
+                    model.add_geq(used, *var);
+                }
+                instructors_used += used;
+            }
+            instructors_used
+        });
+        // latest >= latest_i >= period the course ends in, for every course of instructor i
+        let latest_instructor_end = if input.objective_mode
+            == ObjectiveMode::MinimizeLatestInstructorSlot
+        {
+            let day_length = input.slots_per_day();
+            let latest = model.add_variable(variable().min(0).max(day_length));
+            let mut per_instructor_sum = Expression::from(0);
+            for candidates in instructor_assignments.values() {
+                let instructor_latest = model.add_variable(variable().min(0).max(day_length));
+                let by_course = candidates.iter().into_group_map_by(|((c_id, _, _), _)| *c_id);
+                for (c_id, course_vars) in by_course {
+                    let course = course_map.get(&c_id).unwrap();
+                    let end_period: Expression = course_vars
+                        .into_iter()
+                        .map(|((_, _, start_slot), var)| {
+                            (input.period_of(*start_slot) + course.duration_slots) as f64 * *var
+                        })
+                        .sum();
+                    latest_end_bounds.push((instructor_latest, end_period));
+                }
+                latest_end_bounds.push((latest, instructor_latest.into()));
+                per_instructor_sum += instructor_latest;
+            }
+            // small enough that the whole sum never outweighs one period of the maximum
+            let tie_break =
+                1.0 / (instructor_assignments.len() as f64 * day_length as f64 + 1.0);
+            Some(latest + tie_break * per_instructor_sum)
+        } else {
+            None
+        };
+        // excess_{g,d} >= exams of student group g on day d - 1; every excess exam costs more
+        // than the earliness of all exams together, so spreading always comes first
+        let exam_spread = (input.objective_mode == ObjectiveMode::ExamSpread).then(|| {
+            let difficulty = |c_id: &CourseId| course_map[c_id].difficulty.unwrap_or(0) as f64;
+            let slots_per_day = input.slots_per_day() as f64;
+            // start day including the fraction of the day that has passed
+            let earliness: Expression = assignment_vars_map
+                .iter()
+                .map(|((c_id, _, start_slot), var)| {
+                    difficulty(c_id) * *start_slot as f64 / slots_per_day * *var
+                })
+                .sum();
+            let num_days = input.total_timeslots as f64 / slots_per_day;
+            let scale =
+                1.0 + input.courses.iter().map(|c| difficulty(&c.id)).sum::<f64>() * num_days;
+            let mut clustering = Expression::from(0);
+            for group in &input.student_groups {
+                let by_day = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, _), _)| group.course_ids.contains(c_id))
+                    .into_group_map_by(|((_, _, start_slot), _)| input.day_of(*start_slot));
+                for day_candidates in by_day.values() {
+                    let exams: Expression = day_candidates.iter().map(|(_, var)| **var).sum();
+                    let excess = model.add_variable(variable().min(0));
+                    model.add_geq(excess + 1, exams);
+                    clustering += scale * excess;
+                }
+            }
+            (clustering, earliness)
+        });
+        // changed_s = 1 - kept_s for every warm start assignment s of a scheduled course
+        let warm_start_changes = input.repair_warm_start.then(|| {
+            let mut changes = Expression::from(0);
+            for entry in &input.warm_start {
+                if let Some(course) = course_map.get(&entry.course_id) {
+                    changes += 1;
+                    let kept = warm_start_var(
+                        entry,
+                        course,
+                        &assignment_vars_map,
+                        &instructor_vars_map,
+                        &room_members,
+                    );
+                    if let Some(var) = kept {
+                        changes -= var;
+                    }
+                }
+            }
+            changes
+        });
+        let mut objective_terms = ObjectiveTerms {
+            morning,
+            back_to_back,
+            target_deviation,
+            preferred_day,
+            single_course_days,
+            home_room,
+            department_span,
+            adjacency,
+            capacity_slack,
+            day_preference,
+            day_overflow,
+            reserved_room_use,
+            unavailability_overrides,
+            optional_scheduled,
+            isolated_slots,
+            room_clustering,
+            idle_time,
+            cohort_room_changes,
+            instructors_used,
+            long_course_morning,
+            makespan: makespan.into(),
+            latest_instructor_end,
+            exam_spread,
+            warm_start_changes,
+            relaxed_violations,
+        };
+        // begin hard constraints
+
+        if let Some(max_pairs) = input.max_back_to_back_pairs {
+            info!("Limiting the schedule to {} back-to-back pairs...", max_pairs);
+            let pairs: Expression = back_to_back_links.iter().map(|(_, _, var, _)| *var).sum();
+            model.add_leq(pairs, max_pairs);
+        }
+
+        // tie each back-to-back or adjacency indicator to "ends at k AND starts at k + 1"
+        info!("Linking back-to-back and adjacency indicators...");
+        let indicator_links = back_to_back_links
+            .into_iter()
+            .map(|(starts, ends, var, _)| (starts, ends, var))
+            .chain(adjacency_links);
+        for (starts_at_k_plus_1, ends_at_k, var) in indicator_links {
+            model.add_geq(var, starts_at_k_plus_1.clone() + ends_at_k.clone() - 1);
+            model.add_leq(var, starts_at_k_plus_1);
+            model.add_leq(var, ends_at_k);
+        }
+
+        for (bound, end) in latest_end_bounds {
+            model.add_geq(bound, end);
+        }
+
+        // an unscheduled optional course leaves both bounds free
+        for (first, last, starts) in department_bounds {
+            for (start, scheduled, duration) in starts {
+                let horizon = input.total_timeslots as f64;
+                model.add_leq(first, start.clone() + horizon - horizon * scheduled.clone());
+                model.add_geq(last, start + duration as f64 * scheduled);
+            }
+        }
+
+        // sanity check so course schedule makes sense
+        info!("Adding 'course scheduled once' constraints...");
+        for course in &input.courses {
+            let scheduled_once: Expression = assignment_vars_map
+                .iter()
+                .filter(|((c_id, _, _), _)| *c_id == course.id)
+                .map(|(_, var)| *var)
+                .sum();
+            if course.optional {
+                model.add_leq(scheduled_once, 1);
+            } else {
+                model.add_eq(scheduled_once, 1);
+            }
+        }
+
+        // a course picking its instructor is taught by exactly one of them wherever it runs
+        for (key, choices) in &instructor_vars_map {
+            let taught: Expression = choices.iter().map(|(_, var)| *var).sum();
+            model.add_eq(taught, assignment_vars_map[key]);
+        }
+
+        // contractual cap on the number of courses per instructor
+        for instructor in &input.instructors {
+            if let (Some(max_courses), Some(candidates)) =
+                (instructor.max_courses, instructor_assignments.get(&instructor.id))
+            {
+                let taught: Expression = candidates.iter().map(|(_, var)| *var).sum();
+                model.add_leq(taught, max_courses);
+            }
+        }
+
+        // an instructor teaches at most one course of every distinct instructor group
+        for group in &input.distinct_instructor_groups {
+            for candidates in instructor_assignments.values() {
+                let taught: Expression = candidates
+                    .iter()
+                    .filter(|((c_id, _, _), _)| group.contains(c_id))
+                    .map(|(_, var)| *var)
+                    .sum();
+                model.add_leq(taught, 1);
+            }
+        }
+
+        // no room double-booking, counting combined rooms against each of their rooms; when
+        // relaxed, excess_rk takes every course beyond the first
+        info!("Adding 'no room overlap' constraints...");
+        let mut room_excess = Expression::from(0);
+        for room in &input.rooms {
+            for k in 0..input.total_timeslots {
+                let room_occupied: Expression = assignment_vars_map
+                    .iter()
+                    .filter(|((_, r_id, _), _)| occupies(r_id, room.id))
+                    .filter(|((c_id, _, start_slot), _)| {
+                        let course = course_map.get(c_id).unwrap();
+                        // Check if the course occupies the room at timeslot k
+                        k >= *start_slot && k < *start_slot + course.duration_slots
+                    })
+                    .map(|(_, var)| *var)
+                    .sum();
+                if input.relaxations.room_overlap.is_some() {
+                    let excess = model.add_variable(variable().min(0));
+                    model.add_leq(room_occupied, excess + 1);
+                    room_excess += excess;
+                } else {
+                    model.add_leq(room_occupied, 1);
+                }
+            }
+        }
+        if let Some(penalty) = input.relaxations.room_overlap {
+            objective_terms.relaxed_violations.push(("noRoomOverlap", penalty * room_excess));
+        }
+
+        // every room hosts at least one course
+        if input.require_all_rooms_used {
+            info!("Requiring every room to be used...");
+            for room in &input.rooms {
+                let room_used: Expression = assignment_vars_map
+                    .iter()
+                    .filter(|((_, r_id, _), _)| occupies(r_id, room.id))
+                    .map(|(_, var)| *var)
+                    .sum();
+                model.add_geq(room_used, 1);
+            }
+        }
+
+        // a slot is either empty or runs at least min_courses courses
+        if let Some(min_courses) = input.min_courses_per_active_slot.filter(|k| *k > 1) {
+            info!("Requiring at least {} courses in every active slot...", min_courses);
+            for k in 0..input.total_timeslots {
+                let running: Vec<Variable> = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, start_slot), _)| {
+                        let course = course_map.get(c_id).unwrap();
+                        k >= *start_slot && k < *start_slot + course.duration_slots
+                    })
+                    .map(|(_, var)| *var)
+                    .collect();
+                let max_courses = max_running_courses(input, input.rooms.len(), running.len());
+                let running: Expression = running.into_iter().sum();
+                let active = model.add_variable(variable().binary());
+                model.add_geq(running.clone(), min_courses as f64 * active);
+                model.add_leq(running, max_courses as f64 * active);
+            }
+        }
+
+        // likewise per zone: a zone is either empty at k or runs at least min_courses courses
+        if let Some(min_courses) = input.min_courses_per_active_zone.filter(|k| *k > 1) {
+            let zones: HashMap<u32, Vec<RoomId>> = input
+                .rooms
+                .iter()
+                .filter_map(|r| r.zone.map(|zone| (zone, r.id)))
+                .into_group_map();
+            info!(
+                "Requiring at least {} courses in every active slot of {} zone(s)...",
+                min_courses,
+                zones.len()
+            );
+            for zone_rooms in zones.values() {
+                for k in 0..input.total_timeslots {
+                    let running: Vec<Variable> = assignment_vars_map
+                        .iter()
+                        .filter(|((c_id, r_id, start_slot), _)| {
+                            let course = course_map.get(c_id).unwrap();
+                            zone_rooms.contains(r_id)
+                                && k >= *start_slot
+                                && k < *start_slot + course.duration_slots
+                        })
+                        .map(|(_, var)| *var)
+                        .collect();
+                    let max_courses = max_running_courses(input, zone_rooms.len(), running.len());
+                    let running: Expression = running.into_iter().sum();
+                    let active = model.add_variable(variable().binary());
+                    model.add_geq(running.clone(), min_courses as f64 * active);
+                    model.add_leq(running, max_courses as f64 * active);
+                }
+            }
+        }
+
+        // building-wide cap on simultaneous courses, e.g. from the fire code
+        if let Some(max_concurrent) = input.max_concurrent_courses {
+            info!("Limiting every slot to {} concurrent courses...", max_concurrent);
+            for k in 0..input.total_timeslots {
+                let running: Expression = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, start_slot), _)| {
+                        let course = course_map.get(c_id).unwrap();
+                        k >= *start_slot && k < *start_slot + course.duration_slots
+                    })
+                    .map(|(_, var)| *var)
+                    .sum();
+                model.add_leq(running, max_concurrent);
+            }
+        }
+        // per-slot caps on starts, e.g. to spread out the first period of the day
+        for (slot, max_starts) in input.max_courses_in_slot.iter().flatten() {
+            let starting: Expression = assignment_vars_map
+                .iter()
+                .filter(|((_, _, start_slot), _)| start_slot == slot)
+                .map(|(_, var)| *var)
+                .sum();
+            model.add_leq(starting, *max_starts);
+        }
+
+        // a lab starting at u needs its lecture at one of the starts that allow u
+        for link in &input.section_links {
+            let lecture_duration = course_map[&link.lecture].duration_slots;
+            let lab_slots: BTreeSet<Timeslot> = all_possible_assignments
+                .iter()
+                .filter(|(c_id, _, _)| *c_id == link.lab)
+                .map(|(_, _, start_slot)| *start_slot)
+                .collect();
+            for u in lab_slots {
+                let lab_starts: Expression = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, start_slot), _)| *c_id == link.lab && *start_slot == u)
+                    .map(|(_, var)| *var)
+                    .sum();
+                let allowed_lectures: Expression = assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, start_slot), _)| {
+                        *c_id == link.lecture
+                            && link.allows(input, *start_slot..*start_slot + lecture_duration, u)
+                    })
+                    .map(|(_, var)| *var)
+                    .sum();
+                model.add_leq(lab_starts, allowed_lectures);
+            }
+        }
+
+        // no instructor overlap
+        info!("Adding 'no instructor overlap' constraints...");
+        let mut instructor_excess = Expression::from(0);
+        for instructor in &input.instructors {
+            if let Some(candidates) = instructor_assignments.get(&instructor.id) {
+                let mut busy_by_slot = Vec::with_capacity(input.total_timeslots as usize);
+                for k in 0..input.total_timeslots {
+                    let instructor_busy: Expression = candidates
+                        .iter()
+                        .filter(|((c_id, _, start_slot), _)| {
+                            let course = course_map.get(c_id).unwrap();
+                            k >= *start_slot && k < *start_slot + course.duration_slots
+                        })
+                        .map(|(_, var)| *var)
+                        .sum();
+                    if input.relaxations.instructor_overlap.is_some() {
+                        let excess = model.add_variable(variable().min(0));
+                        model.add_leq(instructor_busy.clone(), excess + 1);
+                        instructor_excess += excess;
+                    } else {
+                        model.add_leq(instructor_busy.clone(), 1);
+                    }
+                    busy_by_slot.push(instructor_busy);
+                }
+
+                // no window of max_consecutive + 1 slots of a day may be fully occupied; the
+                // last period of a day and the first of the next are not consecutive
+                if let Some(max_consecutive) = instructor.max_consecutive_slots {
+                    info!(
+                        "Limiting instructor {} to {} consecutive slots...",
+                        instructor.id, max_consecutive
+                    );
+                    let windows = busy_by_slot
+                        .chunks(input.slots_per_day() as usize)
+                        .flat_map(|day| day.windows(max_consecutive as usize + 1));
+                    for window in windows {
+                        let occupied: Expression = window.iter().cloned().sum();
+                        model.add_leq(occupied, max_consecutive);
+                    }
+                }
+
+                // courses never cross a day boundary, so each day's slots add up its occupancy
+                if let Some(max_slots) = instructor.max_slots_per_day {
+                    info!(
+                        "Limiting instructor {} to {} slots per day...",
+                        instructor.id, max_slots
+                    );
+                    for day in busy_by_slot.chunks(input.slots_per_day() as usize) {
+                        let occupied: Expression = day.iter().cloned().sum();
+                        model.add_leq(occupied, max_slots);
+                    }
+                }
+
+                // [first, last) bounds the instructor's courses of a day; an empty day leaves
+                // first after last
+                if let Some(max_span) = instructor.max_daily_span {
+                    info!(
+                        "Limiting instructor {} to a daily span of {} slots...",
+                        instructor.id, max_span
+                    );
+                    let num_days = input.total_timeslots / input.slots_per_day();
+                    for day in 0..num_days {
+                        let day_start = input.slot_at(day, 0);
+                        let day_end = day_start + input.slots_per_day();
+                        let first = model.add_variable(variable().min(day_start).max(day_end));
+                        let last = model.add_variable(variable().min(day_start).max(day_end));
+                        for ((c_id, _, start_slot), var) in candidates
+                            .iter()
+                            .filter(|((_, _, start_slot), _)| input.day_of(*start_slot) == day)
+                        {
+                            let end_slot = *start_slot + course_map[c_id].duration_slots;
+                            model.add_leq(first + (day_end - *start_slot) as f64 * *var, day_end);
+                            model.add_geq(last - (end_slot - day_start) as f64 * *var, day_start);
+                        }
+                        model.add_leq(last - first, max_span);
+                    }
+                }
+            }
+        }
+
+        if let Some(penalty) = input.relaxations.instructor_overlap {
+            let violations = penalty * instructor_excess;
+            objective_terms.relaxed_violations.push(("noInstructorOverlap", violations));
+        }
+
+        // no overlap between courses attended by the same student group
+        info!("Adding 'no student group overlap' constraints...");
+        for group in &input.student_groups {
+            add_no_overlap_constraints(
+                &mut model,
+                &group.course_ids,
+                &assignment_vars_map,
+                &course_map,
+                input,
+            );
+        }
+
+        // no overlap between courses sharing a conflicting tag
+        info!("Adding 'no tag conflict' constraints...");
+        for tag in &input.tag_conflicts {
+            let tagged: Vec<CourseId> = input
+                .courses
+                .iter()
+                .filter(|c| c.tags.contains(tag))
+                .map(|c| c.id)
+                .collect();
+            if tagged.len() > 1 {
+                add_no_overlap_constraints(
+                    &mut model,
+                    &tagged,
+                    &assignment_vars_map,
+                    &course_map,
+                    input,
+                );
+            }
+        }
+
+        Ok(SolverSession {
+            input: input.clone(),
+            model,
+            assignment_vars_map,
+            instructor_vars_map,
+            room_members,
+            objective_terms,
+            cancelled_courses,
+            prefilter_stats,
+            last_solution: None,
+            log_file: None,
+            incumbent_sink: None,
+            perturbation: None,
+            oversized: None,
+        })
+    }
+
+    /// Solves the prepared model with the given weights, keeping the structure from `new`.
+    pub fn resolve_with_weights(
+        &mut self,
+        weights: SolverWeights,
+    ) -> Result<SchedulingOutput, SolveError> {
+        if let Some(message) = &self.oversized {
+            return self.fall_back(&weights, SolveError::InvalidInput(message.clone()));
+        }
+        if let Some(name) = self.objective_terms.unmodelled_weight(&weights) {
+            return Err(SolveError::InvalidInput(format!(
+                "weights.{} was 0 when the session was built, so its term is not in the model; start a new session with it set.",
+                name
+            )));
+        }
+        let start_time = Instant::now();
+        let input = &self.input;
+
+        let mut objective = self.objective_terms.objective(&weights);
+        let mut objective_scale = 1.0;
+        if input.integer_objective {
+            (objective, objective_scale) = scale_to_integers(&objective);
+            info!("Objective scaled by {} to integer coefficients.", objective_scale);
+        }
+        info!("Objective function defined with morning preference and back-to-back penalties.");
+
+        let perturbed = objective.clone() + self.perturbation.clone().unwrap_or_default();
+        let mut model = self.model.variables().maximise(perturbed).using(default_solver);
+        let mut options: Vec<(&str, HighsOptionValue)> = vec![
+            ("threads", 1.into()), // limit to 1 thread for reproducibility
+            ("random_seed", 1234.into()), //set seed for reproducibility
+            ("log_to_console", "true".into()),
+        ];
+        if self.input.solve_mode == SolveMode::FirstFeasible {
+            // any incumbent is within this gap, so the search stops at the first schedule;
+            // a solution limit would be cleaner, but the bindings panic on that status
+            options.push(("mip_abs_gap", 1e30.into()));
+        }
+        if let Some(seconds) = self.input.time_limit_seconds {
+            options.push(("time_limit", seconds.into()));
+        }
+        if let Some(log_file) = &self.log_file {
+            // HiGHS is silent unless output is on; the file takes the place of the console
+            options.push(("output_flag", true.into()));
+            options.push(("log_to_console", false.into()));
+            options.push(("log_file", log_file.to_string_lossy().into_owned().into()));
+        }
+        let initial_solution: Option<Vec<(Variable, f64)>> = if let Some(previous) =
+            &self.last_solution
+        {
+            Some(previous.clone())
+        } else if !input.warm_start.is_empty() {
+            let kept = input.warm_start.iter().filter_map(|entry| {
+                let course = input.courses.iter().find(|c| c.id == entry.course_id)?;
+                warm_start_var(
+                    entry,
+                    course,
+                    &self.assignment_vars_map,
+                    &self.instructor_vars_map,
+                    &self.room_members,
+                )
+            });
+            Some(kept.map(|var| (var, 1.0)).collect())
+        } else {
+            None
+        };
+        self.model.apply_constraints(&mut model);
+        if let Some(min_objective) = input.min_objective {
+            model.add_constraint(objective.clone().geq(min_objective * objective_scale));
+        }
+
+        //solve
+        info!("Starting ILP solver...");
+        let mut report_incumbent = |values: &HashMap<Variable, f64>| {
+            if let Some(sink) = &self.incumbent_sink
+                && let Some(output) =
+                    self.incumbent_output(values, &weights, &objective, objective_scale)
+            {
+                sink(output);
+            }
+        };
+        let on_incumbent: Option<ReportIncumbent> = match self.incumbent_sink {
+            Some(_) => Some(&mut report_incumbent),
+            None => None,
+        };
+        let columns = self.model.columns();
+        let result =
+            run_highs(model, &columns, options, initial_solution.as_deref(), on_incumbent);
+        let solution = match result {
+            Ok(s) => s,
+            Err(ResolutionError::Infeasible) => {
+                let message = match input.min_objective {
+                    Some(min_objective) => format!(
+                        "No schedule satisfies the hard constraints with an objective of at least {}.",
+                        min_objective
+                    ),
+                    None => "No solution found. The problem might be too constrained.".to_string(),
+                };
+                return self.fall_back(&weights, SolveError::Infeasible(message));
+            }
+            Err(e) => {
+                return self.fall_back(
+                    &weights,
+                    SolveError::SolverFailure(format!("No solution found. Solver error: {}", e)),
+                );
+            }
+        };
+        let duration = start_time.elapsed();
+        info!("Solution found in {:.2?}", duration);
+
+        // get assignments from solution
+        let tolerance = input.solution_tolerance.unwrap_or(DEFAULT_SOLUTION_TOLERANCE);
+        let assignments = self.chosen_assignments(|var| solution.value(var), tolerance);
+        if matches!(solution.status(), SolutionStatus::TimeLimit)
+            && input
+                .courses
+                .iter()
+                .any(|c| !c.optional && !assignments.iter().any(|a| a.course_id == c.id))
+        {
+            return self.fall_back(
+                &weights,
+                SolveError::SolverFailure(format!(
+                    "The time limit of {} s was reached before a complete schedule was found.",
+                    input.time_limit_seconds.unwrap_or_default()
+                )),
+            );
+        }
+        if let Some(problem) =
+            self.fractional_choice(&assignments, |var| solution.value(var), tolerance)
+        {
+            return self.fall_back(&weights, SolveError::SolverFailure(problem));
+        }
+        let last_solution = self
+            .assignment_vars_map
+            .values()
+            .chain(self.instructor_vars_map.values().flatten().map(|(_, var)| var))
+            .map(|var| (*var, solution.value(*var).round()))
+            .collect();
+
+        let objective_breakdown = input.explain_objective.then(|| {
+            self.objective_terms
+                .weighted_terms(&weights)
+                .into_iter()
+                // + 0.0 turns the -0.0 of unused penalty terms into 0.0
+                .map(|(name, term)| (name.to_string(), solution.eval(&term) + 0.0))
+                .collect()
+        });
+        let mut objective_value = solution.eval(&objective);
+        if input.integer_objective {
+            // every coefficient is an integer, so this only removes solver round-off
+            objective_value = objective_value.round();
+        }
+        let objective_value = objective_value / objective_scale + 0.0;
+        let solve_status = SolveStatus {
+            mode: input.solve_mode,
+            method: SolveMethod::Ilp,
+            // HiGHS reports a solution limit like an optimum, so only trust it in Optimize
+            proven_optimal: input.solve_mode == SolveMode::Optimize
+                && matches!(solution.status(), SolutionStatus::Optimal),
+        };
+        self.last_solution = Some(last_solution);
+        let mut output =
+            self.build_output(assignments, &weights, solve_status, objective_breakdown);
+        output.objective_value = Some(objective_value);
+        Ok(output)
+    }
+
+    /// Rules out the schedule of the last solve, so the next one has to move, add or drop at
+    /// least one course. Returns false when there is no solution to rule out yet.
+    pub fn exclude_last_solution(&mut self) -> bool {
+        let Some(last_solution) = &self.last_solution else {
+            return false;
+        };
+        let chosen: HashSet<Variable> = last_solution
+            .iter()
+            .filter(|(_, value)| *value > 0.5)
+            .map(|(var, _)| *var)
+            .collect();
+        let chosen: Vec<Variable> = self
+            .assignment_vars_map
+            .values()
+            .filter(|var| chosen.contains(var))
+            .copied()
+            .collect();
+        let count = chosen.len() as f64;
+        let placed: Expression = chosen.into_iter().sum();
+        self.model.add_leq(placed, count - 1.0);
+        true
+    }
+
+    /// Adds a small random reward to every assignment from now on, drawn from `seed`, so the
+    /// solver settles ties differently. Each course takes one assignment, so the rewards sum
+    /// to less than 1 (in units of the integer objective when `integer_objective` is set).
+    pub fn perturb_objective(&mut self, seed: u64) {
+        let mut state = seed;
+        let amplitude = 1.0 / (self.input.courses.len() + 1) as f64;
+        // sorted, since the order of the map changes from run to run
+        let perturbation = self
+            .assignment_vars_map
+            .iter()
+            .sorted_by_key(|(key, _)| **key)
+            .map(|(_, var)| amplitude * unit_random(&mut state) * *var)
+            .sum();
+        self.perturbation = Some(perturbation);
+    }
+
+    /// The schedule of an improving solution HiGHS found while still searching, given the
+    /// value of every variable; `None` when it does not place every course cleanly.
+    fn incumbent_output(
+        &self,
+        values: &HashMap<Variable, f64>,
+        weights: &SolverWeights,
+        objective: &Expression,
+        objective_scale: f64,
+    ) -> Option<SchedulingOutput> {
+        let tolerance = self.input.solution_tolerance.unwrap_or(DEFAULT_SOLUTION_TOLERANCE);
+        let value = |var: Variable| values.get(&var).copied().unwrap_or(0.0);
+        let assignments = self.chosen_assignments(value, tolerance);
+        if self.fractional_choice(&assignments, value, tolerance).is_some() {
+            return None;
+        }
+        let solve_status = SolveStatus {
+            mode: self.input.solve_mode,
+            method: SolveMethod::Ilp,
+            proven_optimal: false,
+        };
+        let objective_value = values.eval(objective);
+        let mut output = self.build_output(assignments, weights, solve_status, None);
+        output.objective_value = Some(objective_value / objective_scale + 0.0);
+        Some(output)
+    }
+
+    /// The assignments whose variables are within `tolerance` of 1 in a solution with the
+    /// given values.
+    fn chosen_assignments(
+        &self,
+        value: impl Fn(Variable) -> f64,
+        tolerance: f64,
+    ) -> Vec<Assignment> {
+        let threshold = 1.0 - tolerance;
+        let mut assignments = Vec::new();
+        for ((course_id, room_id, start_slot), var) in &self.assignment_vars_map {
+            if value(*var) >= threshold {
+                let course = self.input.courses.iter().find(|c| c.id == *course_id);
+                let instructor_id = self
+                    .instructor_vars_map
+                    .get(&(*course_id, *room_id, *start_slot))
+                    .and_then(|choices| choices.iter().find(|(_, var)| value(*var) >= threshold))
+                    .map(|(instructor_id, _)| *instructor_id);
+                let (room_id, combined_rooms) = reported_room(&self.room_members, *room_id);
+                assignments.push(Assignment {
+                    course_id: *course_id,
+                    room_id,
+                    start_slot: *start_slot,
+                    instructor_id,
+                    combined_rooms,
+                    label: course.and_then(|c| c.label.clone()),
+                    color: course.and_then(|c| c.color.clone()),
+                });
+            }
+        }
+        assignments.sort();
+        assignments
+    }
+
+    /// Describes the first course the solution does not place cleanly: a mandatory course
+    /// without a variable within `tolerance` of 1, a course with several, or a course that
+    /// picks its instructor without a chosen one. Such values point to numerical trouble
+    /// rather than a schedule, so they must not silently drop the course.
+    fn fractional_choice(
+        &self,
+        assignments: &[Assignment],
+        value: impl Fn(Variable) -> f64,
+        tolerance: f64,
+    ) -> Option<String> {
+        for course in &self.input.courses {
+            let chosen: Vec<&Assignment> =
+                assignments.iter().filter(|a| a.course_id == course.id).collect();
+            if chosen.len() > 1 {
+                return Some(format!(
+                    "Course {} has {} assignment variables within {} of 1; the solution is fractional, so try a smaller solutionTolerance.",
+                    course.id,
+                    chosen.len(),
+                    tolerance
+                ));
+            }
+            if let Some(assignment) = chosen.first()
+                && course.eligible_instructors.is_some()
+                && assignment.instructor_id.is_none()
+            {
+                return Some(format!(
+                    "Course {} has no instructor variable within {} of 1 in room {} at slot {}; the solution is fractional.",
+                    course.id, tolerance, assignment.room_id, assignment.start_slot
+                ));
+            }
+            if chosen.is_empty() && !course.optional {
+                let largest = self
+                    .assignment_vars_map
+                    .iter()
+                    .filter(|((c_id, _, _), _)| *c_id == course.id)
+                    .map(|((_, r_id, start_slot), var)| (value(*var), *r_id, *start_slot))
+                    .max_by(|a, b| a.0.total_cmp(&b.0));
+                let detail = largest.map_or(String::new(), |(largest, r_id, start_slot)| {
+                    format!(" (largest {:.3}, in room {} at slot {})", largest, r_id, start_slot)
+                });
+                return Some(format!(
+                    "Course {} has no assignment variable within {} of 1{}; the solution is fractional, so a larger solutionTolerance may accept it.",
+                    course.id, tolerance, detail
+                ));
+            }
+        }
+        None
+    }
+
+    /// Replaces a failed solve with a greedy schedule when `fallback_to_heuristic` is set;
+    /// returns `error` otherwise, when the heuristic finds nothing either, or when a
+    /// `min_objective` asks for more than the heuristic can promise.
+    fn fall_back(
+        &self,
+        weights: &SolverWeights,
+        error: SolveError,
+    ) -> Result<SchedulingOutput, SolveError> {
+        if !self.input.fallback_to_heuristic || self.input.min_objective.is_some() {
+            return Err(error);
+        }
+        warn!("{} Falling back to the greedy heuristic.", error);
+        let Some(assignments) = greedy_solve(&self.input) else {
+            return Err(error);
+        };
+        let solve_status = SolveStatus {
+            mode: self.input.solve_mode,
+            method: SolveMethod::Heuristic,
+            proven_optimal: false,
+        };
+        // the objective terms need the auxiliary variables, which the heuristic never sets
+        Ok(self.build_output(assignments, weights, solve_status, None))
+    }
+
+    /// Derives every report of the output from the chosen assignments.
+    fn build_output(
+        &self,
+        assignments: Vec<Assignment>,
+        weights: &SolverWeights,
+        solve_status: SolveStatus,
+        objective_breakdown: Option<BTreeMap<String, f64>>,
+    ) -> SchedulingOutput {
+        // reports treat a course that picked its instructor like one taught by them
+        let input = &self.input.with_chosen_instructors(&assignments);
+        let course_map: HashMap<CourseId, &Course> =
+            input.courses.iter().map(|c| (c.id, c)).collect();
+
+        // get score
+        let (score, unmet_soft_constraints, soft_constraint_summary) =
+            calculate_score_and_unmet_constraints(&assignments, input, &course_map);
+        let target_deviations = target_deviations(&assignments, &course_map);
+        let department_spans = department_spans(&assignments, &course_map);
+        let adjacencies = adjacency_statuses(&assignments, input, &course_map);
+        let unavailability_overrides = assignments
+            .iter()
+            .flat_map(|a| unavailability_overrides(course_map[&a.course_id], a.start_slot, input))
+            .collect();
+        let makespan = assignments
+            .iter()
+            .filter_map(|a| course_map.get(&a.course_id).map(|c| a.start_slot + c.duration_slots))
+            .max();
+        let isolated_slots = if weights.isolation_weight != 0.0 {
+            isolated_slots(&assignments, input, &course_map)
+        } else {
+            Vec::new()
+        };
+        let unscheduled_courses = input
+            .courses
+            .iter()
+            .filter(|c| !assignments.iter().any(|a| a.course_id == c.id))
+            .map(|c| c.id)
+            .collect();
+        let reserved_rooms = input
+            .rooms
+            .iter()
+            .filter(|r| r.reserve_preference > 0.0)
+            .map(|room| {
+                let course_count =
+                    assignments.iter().filter(|a| a.rooms().contains(&room.id)).count() as u32;
+                ReservedRoomUsage {
+                    room_id: room.id,
+                    used: course_count > 0,
+                    course_count,
+                }
+            })
+            .collect();
+        let free_slots = if input.include_free_slots {
+            free_slots(&assignments, input)
+        } else {
+            Vec::new()
+        };
+        let binding_constraints = if input.include_binding {
+            binding_constraints(&assignments, input, &course_map)
+        } else {
+            Vec::new()
+        };
+        let constraint_violations = if input.relaxations.is_empty() {
+            Vec::new()
+        } else {
+            constraint_violations(&assignments, input, &course_map)
+        };
+        let mut sections: BTreeMap<CourseId, Vec<CourseId>> = BTreeMap::new();
+        for course in &input.courses {
+            if let Some(template_id) = course.section_of {
+                sections.entry(template_id).or_default().push(course.id);
+            }
+        }
+        let course_sections = sections
+            .into_iter()
+            .map(|(template_id, section_ids)| CourseSections {
+                template_id,
+                section_ids,
+            })
+            .collect();
+        let courses_per_day = if input.day_weights.is_some() {
+            let num_days = input.total_timeslots / input.slots_per_day();
+            let mut counts = vec![0; num_days as usize];
+            for assignment in &assignments {
+                counts[input.day_of(assignment.start_slot) as usize] += 1;
+            }
+            counts
+        } else {
+            Vec::new()
+        };
+        let day_overflows = input
+            .day_soft_caps
+            .iter()
+            .map(|(day, soft_cap, _)| {
+                let course_count = assignments
+                    .iter()
+                    .filter(|a| input.day_of(a.start_slot) == *day)
+                    .count() as u32;
+                DayOverflow {
+                    day: *day,
+                    course_count,
+                    soft_cap: *soft_cap,
+                    overflow: course_count.saturating_sub(*soft_cap),
+                }
+            })
+            .collect();
+        let instructor_latest_ends =
+            if input.objective_mode == ObjectiveMode::MinimizeLatestInstructorSlot {
+                instructor_latest_ends(&assignments, input, &course_map)
+            } else {
+                Vec::new()
+            };
+        let instructor_room_distances = if weights.room_clustering_weight != 0.0 {
+            instructor_room_distances(&assignments, input, &course_map)
+        } else {
+            Vec::new()
+        };
+        // cancelled courses still count, since the schedule no longer has them
+        let warm_start_changes = (!self.input.warm_start.is_empty()).then(|| {
+            self.input
+                .warm_start
+                .iter()
+                .filter(|entry| {
+                    course_map.contains_key(&entry.course_id)
+                        || self.cancelled_courses.contains(&entry.course_id)
+                })
+                .filter(|entry| {
+                    !assignments.iter().any(|a| {
+                        a.course_id == entry.course_id
+                            && a.rooms() == entry.rooms()
+                            && a.start_slot == entry.start_slot
+                            && a.instructor_id == entry.instructor_id
+                    })
+                })
+                .count() as u32
+        });
+        let instructor_idle_slots = if weights.idle_time_weight != 0.0 {
+            instructor_idle_slots(&assignments, input, &course_map)
+        } else {
+            Vec::new()
+        };
+        let cohort_room_changes = if weights.cohort_room_changes_weight != 0.0 {
+            cohort_room_changes(&assignments, input)
+        } else {
+            Vec::new()
+        };
+        let instructors_used = if weights.minimize_instructors_weight != 0.0 {
+            // chosen instructors are on the assignment, fixed ones on the course
+            assignments
+                .iter()
+                .flat_map(|a| {
+                    let fixed = course_map.get(&a.course_id).map(|c| c.instructors());
+                    fixed.unwrap_or_default().into_iter().chain(a.instructor_id)
+                })
+                .sorted_unstable()
+                .dedup()
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // build the final output
+        SchedulingOutput {
+            assignments,
+            score,
+            objective_value: None,
+            unmet_soft_constraints,
+            soft_constraint_summary,
+            solve_status,
+            target_deviations,
+            department_spans,
+            adjacencies,
+            instructor_latest_ends,
+            instructor_room_distances,
+            instructor_idle_slots,
+            cohort_room_changes,
+            instructors_used,
+            warm_start_changes,
+            cancelled_courses: self.cancelled_courses.clone(),
+            unscheduled_courses,
+            makespan,
+            isolated_slots,
+            unavailability_overrides,
+            reserved_rooms,
+            free_slots,
+            binding_constraints,
+            constraint_violations,
+            course_sections,
+            courses_per_day,
+            day_overflows,
+            objective_breakdown,
+            prefilter_stats: input.include_stats.then(|| self.prefilter_stats.clone()),
+            total_count: None,
+        }
+    }
+
+    /// The model as it would be handed to HiGHS, in CPLEX LP format, without solving it.
+    pub fn to_lp_format(&self, weights: &SolverWeights) -> Result<String, SolveError> {
+        if let Some(message) = &self.oversized {
+            return Err(SolveError::InvalidInput(message.clone()));
+        }
+        let mut objective = self.objective_terms.objective(weights);
+        if self.input.integer_objective {
+            objective = scale_to_integers(&objective).0;
+        }
+        let mut model = self.model.variables().maximise(objective).using(default_solver);
+        self.model.apply_constraints(&mut model);
+        let mut highs = model.into_inner();
+
+        // HiGHS can only write models to files; the format follows the extension
+        static NEXT_FILE: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "schedule_solver_{}_{}.lp",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let failure = |e: &dyn fmt::Display| {
+            SolveError::SolverFailure(format!("Could not write the LP model: {}", e))
+        };
+        let c_path = CString::new(path.to_string_lossy().into_owned()).map_err(|e| failure(&e))?;
+        // SAFETY: the model pointer is live for the duration of the call and c_path is
+        // NUL-terminated
+        let status = unsafe { highs_sys::Highs_writeModel(highs.as_mut_ptr(), c_path.as_ptr()) };
+        let written = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        if status == highs_sys::STATUS_ERROR {
+            return Err(failure(&"HiGHS reported an error"));
+        }
+        written.map_err(|e| failure(&e))
+    }
+}
+
+// at most one of the given courses may occupy any slot
+fn add_no_overlap_constraints(
+    model: &mut ModelBuilder,
+    course_ids: &[CourseId],
+    assignment_vars_map: &HashMap<(CourseId, RoomId, Timeslot), Variable>,
+    course_map: &HashMap<CourseId, &Course>,
+    input: &SchedulingInput,
+) {
+    for k in 0..input.total_timeslots {
+        let group_busy: Expression = assignment_vars_map
+            .iter()
+            .filter(|((c_id, _, _), _)| course_ids.contains(c_id))
+            .filter(|((c_id, _, start_slot), _)| {
+                let course = course_map.get(c_id).unwrap();
+                k >= *start_slot && k < *start_slot + course.duration_slots
+            })
+            .map(|(_, var)| *var)
+            .sum();
+        model.add_leq(group_busy, 1);
+    }
+}
+
+// daily windows of every student group attending each course
+fn group_windows(input: &SchedulingInput) -> HashMap<CourseId, Vec<(Timeslot, Timeslot)>> {
+    input
+        .student_groups
+        .iter()
+        .filter_map(|g| g.daily_window.map(|window| (g, window)))
+        .flat_map(|(g, window)| g.course_ids.iter().map(move |c_id| (*c_id, window)))
+        .into_group_map()
+}
+
+/// Every (course, room, start slot) that survives the pre-filter; `input` has to have its
+/// durations and its date ranges in slots already. Combined rooms appear under the ids of
+/// their virtual rooms (see [`SchedulingInput::combined_rooms`]).
+pub(crate) fn possible_assignments(input: &SchedulingInput) -> Vec<(CourseId, RoomId, Timeslot)> {
+    let instructor_map: HashMap<InstructorId, &Instructor> =
+        input.instructors.iter().map(|i| (i.id, i)).collect();
+    let group_windows = group_windows(input);
+    let combined_rooms = input.combined_rooms();
+    let rooms: Vec<&Room> =
+        input.rooms.iter().chain(combined_rooms.iter().map(|(room, _)| room)).collect();
+    let room_unavailable: HashMap<RoomId, HashSet<Timeslot>> = rooms
+        .iter()
+        .map(|r| {
+            let slots = r
+                .recurring_unavailable
+                .iter()
+                .flat_map(|(day, period)| input.weekly_slots(*day, *period))
+                .chain(r.unavailable_slots.iter().copied())
+                .chain(input.maintenance_windows.iter().flat_map(|w| w.slots.iter().copied()))
+                .collect();
+            (r.id, slots)
+        })
+        .collect();
+
+    let room_members: HashMap<RoomId, &Vec<RoomId>> =
+        combined_rooms.iter().map(|(room, room_ids)| (room.id, room_ids)).collect();
+    let frozen_placements = input.frozen_placements();
+    let frozen_slots: HashSet<Timeslot> = input.frozen_slots.iter().copied().collect();
+
+    // pre-filter for performance; implicitly handle some hard constraints
+    let mut possible = Vec::new();
+    for course in &input.courses {
+        for room in &rooms {
+            for start_slot in 0..input.total_timeslots {
+                // a frozen course keeps its placement, and no other course enters the frozen
+                // slots
+                let respects_freeze = match frozen_placements.get(&course.id) {
+                    Some(placement) => {
+                        let room_ids = match room_members.get(&room.id) {
+                            Some(room_ids) => room_ids.as_slice(),
+                            None => std::slice::from_ref(&room.id),
+                        };
+                        let mut placed_in = placement.rooms().to_vec();
+                        placed_in.sort_unstable();
+                        placement.start_slot == start_slot && placed_in == room_ids
+                    }
+                    None => {
+                        let end_slot = start_slot
+                            .saturating_add(course.duration_slots)
+                            .min(input.total_timeslots);
+                        (start_slot..end_slot).all(|k| !frozen_slots.contains(&k))
+                    }
+                };
+                if respects_freeze
+                    && is_assignment_possible(
+                        course,
+                        room,
+                        start_slot,
+                        input,
+                        &instructor_map,
+                        &group_windows,
+                        &room_unavailable,
+                    )
+                {
+                    possible.push((course.id, room.id, start_slot));
+                }
+            }
+        }
+    }
+    possible
+}
+
+// implicitly checks the hard constraints on overlap and capacity
+fn is_assignment_possible(
+    course: &Course,
+    room: &Room,
+    start_slot: Timeslot,
+    input: &SchedulingInput,
+    instructor_map: &HashMap<InstructorId, &Instructor>,
+    group_windows: &HashMap<CourseId, Vec<(Timeslot, Timeslot)>>,
+    room_unavailable: &HashMap<RoomId, HashSet<Timeslot>>,
+) -> bool {
+    // course fits in remaining timeslots; every later sum is bounded by end_slot
+    let Some(end_slot) = start_slot.checked_add(course.duration_slots) else {
+        return false;
+    };
+    if end_slot > input.total_timeslots {
+        return false;
+    }
+
+    // course may not run past the end of its day
+    let first_period = input.period_of(start_slot);
+    if first_period + course.duration_slots > input.slots_per_day() {
+        return false;
+    }
+
+    // course pinned to a day
+    if let Some(required_day) = course.required_day
+        && input.day_of(start_slot) != required_day
+    {
+        return false;
+    }
+
+    // exams and classes keep to the slots of their kind
+    if (start_slot..end_slot).any(|k| !input.slot_mode(k).allows(course)) {
+        return false;
+    }
+
+    // course locked to a room or a start slot
+    if course.fixed_room.is_some_and(|r_id| r_id != room.id)
+        || course.fixed_slot.is_some_and(|k| k != start_slot)
+    {
+        return false;
+    }
+
+    // course may touch but not pass through its boundary slots
+    let last_slot = end_slot.saturating_sub(1);
+    if course
+        .no_cross_slots
+        .iter()
+        .any(|boundary| start_slot < *boundary && *boundary < last_slot)
+    {
+        return false;
+    }
+
+    // course has to fit the daily window of every student group attending it
+    if let Some(windows) = group_windows.get(&course.id) {
+        let fits = windows.iter().all(|(window_start, window_end)| {
+            first_period >= *window_start && first_period + course.duration_slots <= *window_end
+        });
+        if !fits {
+            return false;
+        }
+    }
+
+    // room has capacity, unless the objective pays for placements in smaller rooms
+    if room.capacity < course.required_capacity && input.relaxations.capacity.is_none() {
+        return false;
+    }
+
+    let required_slots: HashSet<Timeslot> =
+        (start_slot..end_slot).collect();
+
+    // room must not be blocked by a recurring reservation
+    if let Some(blocked) = room_unavailable.get(&room.id)
+        && !required_slots.is_disjoint(blocked)
+    {
+        return false;
+    }
+
+    // every fixed instructor has to be available, or at least one of the eligible ones
+    let available = |instructor_id: &InstructorId| {
+        instructor_map
+            .get(instructor_id)
+            .is_some_and(|i| instructor_available(i, course, start_slot, input))
+    };
+    match &course.eligible_instructors {
+        Some(eligible) => eligible.iter().any(available),
+        None => {
+            let instructors = course.instructors();
+            !instructors.is_empty() && instructors.iter().all(available)
+        }
+    }
+}
+
+// whether `instructor` may teach `course` starting at `start_slot`; with overrides allowed
+// the objective pays for unavailable slots instead, but the daily hours always hold
+fn instructor_available(
+    instructor: &Instructor,
+    course: &Course,
+    start_slot: Timeslot,
+    input: &SchedulingInput,
+) -> bool {
+    instructor.within_hours(input, start_slot, course.duration_slots)
+        && (input.allow_override_unavailability
+            || !(start_slot..start_slot + course.duration_slots)
+                .any(|k| instructor.unavailable_slots.contains(&k)))
+}
+
+// unavailable slots each instructor of `course` would teach when it starts at `start_slot`
+fn unavailability_overrides(
+    course: &Course,
+    start_slot: Timeslot,
+    input: &SchedulingInput,
+) -> Vec<UnavailabilityOverride> {
+    input
+        .instructors
+        .iter()
+        .filter(|i| course.instructors().contains(&i.id))
+        .filter_map(|instructor| unavailability_override(instructor, course, start_slot, input))
+        .collect()
+}
+
+// the unavailable slots `instructor` would teach `course` in when it starts at `start_slot`
+fn unavailability_override(
+    instructor: &Instructor,
+    course: &Course,
+    start_slot: Timeslot,
+    input: &SchedulingInput,
+) -> Option<UnavailabilityOverride> {
+    if !input.allow_override_unavailability {
+        return None;
+    }
+    let course_slots = start_slot..start_slot + course.duration_slots;
+    let mut slots: Vec<Timeslot> = instructor
+        .unavailable_slots
+        .iter()
+        .copied()
+        .filter(|slot| course_slots.contains(slot))
+        .collect();
+    if slots.is_empty() {
+        return None;
+    }
+    slots.sort_unstable();
+    slots.dedup();
+    let cost = instructor.override_cost.unwrap_or(DEFAULT_OVERRIDE_COST);
+    Some(UnavailabilityOverride {
+        instructor_id: instructor.id,
+        course_id: course.id,
+        cost: cost * slots.len() as f64,
+        slots,
+    })
+}
+
+/// Names the maintenance windows that closed a start slot `preferred` accepts for `course`,
+/// for the description of an unmet soft constraint; `None` when no window is to blame.
+fn maintenance_note(
+    course: &Course,
+    input: &SchedulingInput,
+    preferred: impl Fn(Timeslot) -> bool,
+) -> Option<String> {
+    let duration = course.duration_slots.max(1);
+    let preferred_starts: Vec<Timeslot> = (0..input.total_timeslots)
+        .filter(|k| preferred(*k) && input.day_of(*k) == input.day_of(k + duration - 1))
+        .collect();
+    let reasons: Vec<&str> = input
+        .maintenance_windows
+        .iter()
+        .filter(|w| {
+            preferred_starts.iter().any(|start| {
+                w.slots.iter().any(|k| (*start..start + duration).contains(k))
+            })
+        })
+        .map(|w| w.reason.as_str())
+        .collect();
+    if reasons.is_empty() {
+        return None;
+    }
+    Some(format!("Preferred slots were closed for maintenance: {}.", reasons.join("; ")))
+}
+
+// weight of a single course in the morning preference, shared by the objective and the score
+fn morning_reward(course: &Course, input: &SchedulingInput) -> i32 {
+    if input.scale_morning_by_duration {
+        i32::try_from(course.duration_slots).unwrap_or(i32::MAX)
+    } else {
+        1
+    }
+}
+
+/// Which slots of the day of `slot` count as morning, for unmet morning preferences.
+fn morning_description(input: &SchedulingInput, slot: Timeslot) -> String {
+    let cutoff = input.morning_cutoff().min(input.slots_per_day());
+    let day = input.day_of(slot);
+    if cutoff == 0 {
+        return "The morning cutoff is period 0, so no slot is in the morning".to_string();
+    }
+    format!(
+        "Morning is the first {} period(s) of each day, slots {} to {} on day {}",
+        cutoff,
+        input.slot_at(day, 0),
+        input.slot_at(day, cutoff - 1),
+        day
+    )
+}
+
+fn target_deviations(
+    assignments: &[Assignment],
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<TargetDeviation> {
+    assignments
+        .iter()
+        .filter_map(|assignment| {
+            let target_slot = course_map.get(&assignment.course_id)?.target_slot?;
+            Some(TargetDeviation {
+                course_id: assignment.course_id,
+                target_slot,
+                start_slot: assignment.start_slot,
+                deviation: assignment.start_slot.abs_diff(target_slot),
+            })
+        })
+        .collect()
+}
+
+/// Most courses that can run at once in `rooms` rooms when `candidates` variables cover the
+/// slot. A relaxed room overlap lifts the one-course-per-room bound, so only the candidates
+/// limit it then.
+fn max_running_courses(input: &SchedulingInput, rooms: usize, candidates: usize) -> usize {
+    if input.relaxations.room_overlap.is_some() { candidates } else { rooms }
+}
+
+/// Timeslots in which exactly one course is running.
+fn isolated_slots(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<Timeslot> {
+    let mut running = vec![0; input.total_timeslots as usize];
+    for assignment in assignments {
+        let duration = course_map.get(&assignment.course_id).map_or(1, |c| c.duration_slots);
+        for k in assignment.start_slot..assignment.start_slot + duration {
+            if let Some(count) = running.get_mut(k as usize) {
+                *count += 1;
+            }
+        }
+    }
+    (0..input.total_timeslots).filter(|k| running[*k as usize] == 1).collect()
+}
+
+fn department_spans(
+    assignments: &[Assignment],
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<DepartmentSpan> {
+    let mut bounds: HashMap<u32, (Timeslot, Timeslot)> = HashMap::new();
+    for assignment in assignments {
+        let Some(course) = course_map.get(&assignment.course_id) else {
+            continue;
+        };
+        let Some(department_id) = course.department_id else {
+            continue;
+        };
+        let end_slot = assignment.start_slot + course.duration_slots;
+        let entry = bounds
+            .entry(department_id)
+            .or_insert((assignment.start_slot, end_slot));
+        entry.0 = entry.0.min(assignment.start_slot);
+        entry.1 = entry.1.max(end_slot);
+    }
+    bounds
+        .into_iter()
+        .sorted_by_key(|(department_id, _)| *department_id)
+        .map(|(department_id, (first_slot, end_slot))| DepartmentSpan {
+            department_id,
+            first_slot,
+            end_slot,
+            span: end_slot - first_slot,
+        })
+        .collect()
+}
+
+fn instructor_latest_ends(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<InstructorLatestEnd> {
+    let mut latest: HashMap<InstructorId, u32> = HashMap::new();
+    for assignment in assignments {
+        if let Some(course) = course_map.get(&assignment.course_id) {
+            let end_period = input.period_of(assignment.start_slot) + course.duration_slots;
+            for instructor_id in course.instructors() {
+                let entry = latest.entry(instructor_id).or_insert(end_period);
+                *entry = (*entry).max(end_period);
+            }
+        }
+    }
+    latest
+        .into_iter()
+        .sorted_by_key(|(instructor_id, _)| *instructor_id)
+        .map(|(instructor_id, latest_end_period)| InstructorLatestEnd {
+            instructor_id,
+            latest_end_period,
+        })
+        .collect()
+}
+
+// total room distance between every two courses of each instructor who teaches any
+fn instructor_room_distances(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<InstructorRoomDistance> {
+    let distances = room_distance_map(input);
+    let mut rooms: BTreeMap<InstructorId, Vec<RoomId>> = BTreeMap::new();
+    for assignment in assignments {
+        if let Some(course) = course_map.get(&assignment.course_id) {
+            for instructor_id in course.instructors() {
+                rooms.entry(instructor_id).or_default().push(assignment.room_id);
+            }
+        }
+    }
+    rooms
+        .into_iter()
+        .map(|(instructor_id, rooms)| InstructorRoomDistance {
+            instructor_id,
+            // + 0.0 turns the -0.0 of an empty sum into 0.0
+            total_distance: rooms
+                .iter()
+                .tuple_combinations()
+                .filter_map(|(first, second)| distances.get(&(*first, *second)))
+                .sum::<f64>()
+                + 0.0,
+        })
+        .collect()
+}
+
+/// Room and combined rooms an assignment to candidate room `r_id` reports: the first room of
+/// a virtual room and all of its rooms, or `r_id` on its own.
+pub(crate) fn reported_room(
+    room_members: &HashMap<RoomId, Vec<RoomId>>,
+    r_id: RoomId,
+) -> (RoomId, Vec<RoomId>) {
+    match room_members.get(&r_id) {
+        Some(room_ids) => (room_ids[0], room_ids.clone()),
+        None => (r_id, Vec::new()),
+    }
+}
+
+/// Variable that keeps `entry` of a warm start: its course in the same room and start slot,
+/// with the same `instructor_id`. `None` when no candidate does, e.g. because the room is now
+/// blocked then.
+fn warm_start_var(
+    entry: &Assignment,
+    course: &Course,
+    assignment_vars_map: &HashMap<(CourseId, RoomId, Timeslot), Variable>,
+    instructor_vars_map: &InstructorVarsMap,
+    room_members: &HashMap<RoomId, Vec<RoomId>>,
+) -> Option<Variable> {
+    let room_id = if entry.combined_rooms.is_empty() {
+        entry.room_id
+    } else {
+        let mut room_ids = entry.combined_rooms.clone();
+        room_ids.sort_unstable();
+        *room_members.iter().find(|(_, members)| **members == room_ids)?.0
+    };
+    let key = (entry.course_id, room_id, entry.start_slot);
+    match (&course.eligible_instructors, entry.instructor_id) {
+        (Some(_), Some(instructor_id)) => instructor_vars_map
+            .get(&key)?
+            .iter()
+            .find(|(i_id, _)| *i_id == instructor_id)
+            .map(|(_, var)| *var),
+        (None, None) => assignment_vars_map.get(&key).copied(),
+        _ => None,
+    }
+}
+
+fn instructor_idle_slots(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<InstructorIdleSlots> {
+    let mut days: BTreeMap<(InstructorId, u32), Vec<(Timeslot, u32)>> = BTreeMap::new();
+    for assignment in assignments {
+        if let Some(course) = course_map.get(&assignment.course_id) {
+            for instructor_id in course.instructors() {
+                days.entry((instructor_id, input.day_of(assignment.start_slot)))
+                    .or_default()
+                    .push((assignment.start_slot, course.duration_slots));
+            }
+        }
+    }
+    let mut idle: BTreeMap<InstructorId, u32> = BTreeMap::new();
+    for ((instructor_id, _), classes) in days {
+        let first = classes.iter().map(|(start_slot, _)| *start_slot).min().unwrap_or(0);
+        let last = classes.iter().map(|(start_slot, duration)| start_slot + duration).max();
+        let busy: u32 = classes.iter().map(|(_, duration)| duration).sum();
+        // an instructor never teaches two classes at once, so the span covers them all
+        let span = last.unwrap_or(0) - first;
+        *idle.entry(instructor_id).or_insert(0) += span.saturating_sub(busy);
+    }
+    idle.into_iter()
+        .map(|(instructor_id, idle_slots)| InstructorIdleSlots {
+            instructor_id,
+            idle_slots,
+        })
+        .collect()
+}
+
+// the room-overlap and instructor-overlap constraints of every occupied slot, and the
+// capacity of every room a course fills exactly; named like the hard constraints
+fn binding_constraints(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<String> {
+    let capacities: HashMap<RoomId, u32> =
+        input.rooms.iter().map(|r| (r.id, r.capacity)).collect();
+    let mut room_slots: BTreeMap<RoomId, BTreeSet<Timeslot>> = BTreeMap::new();
+    let mut instructor_slots: BTreeMap<InstructorId, BTreeSet<Timeslot>> = BTreeMap::new();
+    let mut full_rooms = Vec::new();
+    for assignment in assignments {
+        let Some(course) = course_map.get(&assignment.course_id) else {
+            continue;
+        };
+        let slots = assignment.start_slot..assignment.start_slot + course.duration_slots;
+        for room_id in assignment.rooms() {
+            room_slots.entry(*room_id).or_default().extend(slots.clone());
+        }
+        for instructor_id in course.instructors() {
+            instructor_slots.entry(instructor_id).or_default().extend(slots.clone());
+        }
+        let capacity: u32 =
+            assignment.rooms().iter().filter_map(|r_id| capacities.get(r_id)).sum();
+        if capacity == course.required_capacity {
+            let rooms = match assignment.rooms() {
+                [room_id] => format!("room {}", room_id),
+                room_ids => format!("combined rooms {:?}", room_ids),
+            };
+            full_rooms.push(format!(
+                "roomCapacity: course {} fills all {} seat(s) of {}",
+                course.id, capacity, rooms
+            ));
+        }
+    }
+    let slot_list = |slots: BTreeSet<Timeslot>| slots.iter().join(", ");
+    room_slots
+        .into_iter()
+        .map(|(room_id, slots)| {
+            format!("noRoomOverlap: room {} is booked in slot(s) {}", room_id, slot_list(slots))
+        })
+        .chain(instructor_slots.into_iter().map(|(instructor_id, slots)| {
+            format!(
+                "noInstructorOverlap: instructor {} teaches in slot(s) {}",
+                instructor_id,
+                slot_list(slots)
+            )
+        }))
+        .chain(full_rooms)
+        .collect()
+}
+
+// every overlap and overfilled room in the schedule; only relaxed constraints can have any
+fn constraint_violations(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<String> {
+    let capacities: HashMap<RoomId, u32> =
+        input.rooms.iter().map(|r| (r.id, r.capacity)).collect();
+    let mut room_courses: BTreeMap<(RoomId, Timeslot), Vec<CourseId>> = BTreeMap::new();
+    let mut instructor_courses: BTreeMap<(InstructorId, Timeslot), Vec<CourseId>> =
+        BTreeMap::new();
+    let mut overfilled = Vec::new();
+    for assignment in assignments {
+        let Some(course) = course_map.get(&assignment.course_id) else {
+            continue;
+        };
+        for k in assignment.start_slot..assignment.start_slot + course.duration_slots {
+            for room_id in assignment.rooms() {
+                room_courses.entry((*room_id, k)).or_default().push(course.id);
+            }
+            for instructor_id in course.instructors() {
+                instructor_courses.entry((instructor_id, k)).or_default().push(course.id);
+            }
+        }
+        let capacity: u32 =
+            assignment.rooms().iter().filter_map(|r_id| capacities.get(r_id)).sum();
+        if capacity < course.required_capacity {
+            let rooms = match assignment.rooms() {
+                [room_id] => format!("room {} has", room_id),
+                room_ids => format!("combined rooms {:?} have", room_ids),
+            };
+            overfilled.push(format!(
+                "roomCapacity: course {} needs {} seat(s) but {} {}",
+                course.id, course.required_capacity, rooms, capacity
+            ));
+        }
+    }
+    let course_list = |course_ids: &[CourseId]| course_ids.iter().sorted().join(", ");
+    room_courses
+        .into_iter()
+        .filter(|(_, course_ids)| course_ids.len() > 1)
+        .map(|((room_id, k), course_ids)| {
+            format!(
+                "noRoomOverlap: room {} holds courses {} in slot {}",
+                room_id,
+                course_list(&course_ids),
+                k
+            )
+        })
+        .chain(
+            instructor_courses
+                .into_iter()
+                .filter(|(_, course_ids)| course_ids.len() > 1)
+                .map(|((instructor_id, k), course_ids)| {
+                    format!(
+                        "noInstructorOverlap: instructor {} teaches courses {} in slot {}",
+                        instructor_id,
+                        course_list(&course_ids),
+                        k
+                    )
+                }),
+        )
+        .chain(overfilled)
+        .collect()
+}
+
+fn cohort_room_changes(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+) -> Vec<CohortRoomChanges> {
+    let mut rooms: BTreeMap<(StudentGroupId, u32), HashSet<&[RoomId]>> = BTreeMap::new();
+    for group in &input.student_groups {
+        for assignment in assignments.iter().filter(|a| group.course_ids.contains(&a.course_id)) {
+            rooms
+                .entry((group.id, input.day_of(assignment.start_slot)))
+                .or_default()
+                .insert(assignment.rooms());
+        }
+    }
+    rooms
+        .into_iter()
+        .map(|((student_group_id, day), rooms)| CohortRoomChanges {
+            student_group_id,
+            day,
+            room_changes: rooms.len() as u32 - 1,
+        })
+        .collect()
+}
+
+/// Next number of the SplitMix64 sequence, scaled to [0, 1). Written out here so a seed picks
+/// the same numbers on every build.
+fn unit_random(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// room_distances in both directions; a pair listed twice keeps its last distance
+fn room_distance_map(input: &SchedulingInput) -> HashMap<(RoomId, RoomId), f64> {
+    input
+        .room_distances
+        .iter()
+        .flat_map(|(first, second, distance)| {
+            [((*first, *second), *distance), ((*second, *first), *distance)]
+        })
+        .collect()
+}
+
+// whether two placed courses run back-to-back, in either order, on the same day
+fn is_adjacent(
+    first: &Assignment,
+    second: &Assignment,
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> bool {
+    let ends_right_before = |before: &Assignment, after: &Assignment| {
+        course_map.get(&before.course_id).is_some_and(|course| {
+            before.start_slot + course.duration_slots == after.start_slot
+                && input.day_of(before.start_slot) == input.day_of(after.start_slot)
+        })
+    };
+    ends_right_before(first, second) || ends_right_before(second, first)
+}
+
+fn adjacency_statuses(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> Vec<AdjacencyStatus> {
+    input
+        .soft_adjacency
+        .iter()
+        .map(|(first_id, second_id)| {
+            let first = assignments.iter().find(|a| a.course_id == *first_id);
+            let second = assignments.iter().find(|a| a.course_id == *second_id);
+            let satisfied = match (first, second) {
+                (Some(first), Some(second)) => is_adjacent(first, second, input, course_map),
+                _ => false,
+            };
+            AdjacencyStatus {
+                course_ids: (*first_id, *second_id),
+                satisfied,
+            }
+        })
+        .collect()
+}
+
+fn calculate_score_and_unmet_constraints(
+    assignments: &[Assignment],
+    input: &SchedulingInput,
+    course_map: &HashMap<CourseId, &Course>,
+) -> (f64, Vec<UnmetSoftConstraint>, Vec<SoftConstraintSummary>) {
+    let mut score = 0.0;
+    let mut unmet = Vec::new();
+    let mut summary: Vec<SoftConstraintSummary> = Vec::new();
+    let mut record = |constraint_type: &str, met: bool| {
+        match summary.iter_mut().find(|s| s.constraint_type == constraint_type) {
+            Some(entry) => entry.record(met),
+            None => {
+                let mut entry = SoftConstraintSummary::new(constraint_type);
+                entry.record(met);
+                summary.push(entry);
+            }
+        }
+    };
+    // prefer morning slots.
+    for assignment in assignments {
+        let reward = course_map
+            .get(&assignment.course_id)
+            .map_or(1, |course| morning_reward(course, input));
+        record("Prefer Mornings", input.is_morning(assignment.start_slot));
+        if input.is_morning(assignment.start_slot) {
+            score += reward as f64; //add score if met
+        } else {
+            score -= reward as f64; //penalize if not met
+            unmet.push(UnmetSoftConstraint {
+                constraint_type: "Prefer Mornings".to_string(),
+                description: format!(
+                    "Course {} is scheduled at slot {}, which is not in the morning. {}{}",
+                    assignment.course_id,
+                    assignment.start_slot,
+                    morning_description(input, assignment.start_slot),
+                    course_map
+                        .get(&assignment.course_id)
+                        .and_then(|course| maintenance_note(course, input, |k| input.is_morning(k)))
+                        .map_or(String::new(), |note| format!(". {}", note))
+                ),
+            });
+        }
+    }
+
+    // prefer long courses in the morning; reported only, the score already counts mornings
+    let weights = input.weights.clone().unwrap_or_default();
+    if weights.long_course_morning_weight != 0.0 {
+        for assignment in assignments {
+            let Some(course) = course_map.get(&assignment.course_id) else {
+                continue;
+            };
+            if course.duration_slots < 2 {
+                continue;
+            }
+            let in_morning = input.is_morning(assignment.start_slot);
+            record("Long Courses in the Morning", in_morning);
+            if !in_morning {
+                unmet.push(UnmetSoftConstraint {
+                    constraint_type: "Long Courses in the Morning".to_string(),
+                    description: format!(
+                        "Course {} lasts {} slots but starts at slot {}, after the morning.",
+                        course.id, course.duration_slots, assignment.start_slot
+                    ),
+                });
+            }
+        }
+    }
+
+    // prefer the instructors' preferred teaching days
+    for assignment in assignments {
+        let Some(course) = course_map.get(&assignment.course_id) else {
+            continue;
+        };
+        let course_instructors = course.instructors();
+        for instructor in input
+            .instructors
+            .iter()
+            .filter(|i| course_instructors.contains(&i.id) && !i.preferred_days.is_empty())
+        {
+            let day = input.weekday_of(assignment.start_slot);
+            record("Preferred Teaching Days", instructor.preferred_days.contains(&day));
+            if instructor.preferred_days.contains(&day) {
+                score += 1.0;
+            } else {
+                score -= 1.0;
+                unmet.push(UnmetSoftConstraint {
+                    constraint_type: "Preferred Teaching Days".to_string(),
+                    description: format!(
+                        "Course {} is scheduled on day {}, which is not one of instructor {}'s preferred days {:?}.{}",
+                        assignment.course_id,
+                        day,
+                        instructor.id,
+                        instructor.preferred_days,
+                        maintenance_note(course, input, |k| {
+                            instructor.preferred_days.contains(&input.weekday_of(k))
+                        })
+                        .map_or(String::new(), |note| format!(" {}", note))
+                    ),
+                });
+            }
+        }
+    }
+
+    // avoid days on which an instructor comes in for a single course
+    for instructor in input.instructors.iter().filter(|i| i.avoid_single_course_days) {
+        let mut days: BTreeMap<u32, Vec<CourseId>> = BTreeMap::new();
+        for assignment in assignments {
+            if course_map
+                .get(&assignment.course_id)
+                .is_some_and(|c| c.instructors().contains(&instructor.id))
+            {
+                days.entry(input.day_of(assignment.start_slot))
+                    .or_default()
+                    .push(assignment.course_id);
+            }
+        }
+        for course_ids in days.values() {
+            record("Single-Course Day", course_ids.len() > 1);
+        }
+        for (day, course_ids) in days.into_iter().filter(|(_, course_ids)| course_ids.len() == 1) {
+            score -= 1.0;
+            unmet.push(UnmetSoftConstraint {
+                constraint_type: "Single-Course Day".to_string(),
+                description: format!(
+                    "Instructor {} teaches only course {} on day {}.",
+                    instructor.id, course_ids[0], day
+                ),
+            });
+        }
+    }
+
+    // prefer the instructors' home rooms
+    for assignment in assignments {
+        let Some(course) = course_map.get(&assignment.course_id) else {
+            continue;
+        };
+        let course_instructors = course.instructors();
+        for instructor in input.instructors.iter().filter(|i| course_instructors.contains(&i.id)) {
+            let Some(home_room) = instructor.home_room else {
+                continue;
+            };
+            record("Home Room", assignment.room_id == home_room);
+            if assignment.room_id == home_room {
+                score += 1.0;
+            } else {
+                score -= 1.0;
+                unmet.push(UnmetSoftConstraint {
+                    constraint_type: "Home Room".to_string(),
+                    description: format!(
+                        "Course {} is in room {}, not in instructor {}'s home room {}.",
+                        assignment.course_id, assignment.room_id, instructor.id, home_room
+                    ),
+                });
+            }
+        }
+    }
+
+    // reward requested adjacencies
+    for status in adjacency_statuses(assignments, input, course_map) {
+        let (first_id, second_id) = status.course_ids;
+        record("Soft Adjacency", status.satisfied);
+        if status.satisfied {
+            score += 1.0;
+        } else {
+            score -= 1.0;
+            unmet.push(UnmetSoftConstraint {
+                constraint_type: "Soft Adjacency".to_string(),
+                description: format!(
+                    "Courses {} and {} were requested to run back-to-back but are not.",
+                    first_id, second_id
+                ),
+            });
+        }
+    }
+
+    // report rooms that are far too large for their course
+    if let Some(max_slack) = input.max_capacity_slack {
+        for assignment in assignments {
+            let Some(course) = course_map.get(&assignment.course_id) else {
+                continue;
+            };
+            let rooms = assignment.rooms();
+            let capacity: u32 = input
+                .rooms
+                .iter()
+                .filter(|r| rooms.contains(&r.id))
+                .map(|r| r.capacity)
+                .sum();
+            let slack = capacity.saturating_sub(course.required_capacity);
+            record("Right-Sized Rooms", slack <= max_slack);
+            if slack > max_slack {
+                let room = match rooms {
+                    [room_id] => format!("room {} has", room_id),
+                    _ => format!("combined rooms {:?} have", rooms),
+                };
+                unmet.push(UnmetSoftConstraint {
+                    constraint_type: "Right-Sized Rooms".to_string(),
+                    description: format!(
+                        "Course {} needs {} seats but {} {}, leaving {} empty (more than {}).",
+                        course.id, course.required_capacity, room, capacity, slack, max_slack
+                    ),
+                });
+            }
+        }
+    }
+
+    // avoid back-to-back classes for instructors
+    let instructor_assignments: HashMap<InstructorId, Vec<&Assignment>> = assignments
+        .iter()
+        .filter_map(|a| course_map.get(&a.course_id).map(|c| (c, a)))
+        .flat_map(|(c, a)| c.instructors().into_iter().map(move |i_id| (i_id, a)))
+        .into_group_map();
+
+    for (instructor_id, mut instructor_assigns) in instructor_assignments {
+        let weight = input
+            .instructors
+            .iter()
+            .find(|i| i.id == instructor_id)
+            .map_or(1.0, |i| i.back_to_back_weight);
+        if weight == 0.0 {
+            continue;
+        }
+        instructor_assigns.sort_by_key(|a| a.start_slot);
+
+        for i in 0..instructor_assigns.len().saturating_sub(1) {
+            let current = instructor_assigns[i];
+            let next = instructor_assigns[i + 1];
+
+            let current_course = course_map.get(&current.course_id).unwrap();
+            let current_end_slot = current.start_slot + current_course.duration_slots;
+
+            let back_to_back = current_end_slot == next.start_slot
+                && input.day_of(current.start_slot) == input.day_of(next.start_slot);
+            let prefers_back_to_back = weight < 0.0;
+            record(
+                if prefers_back_to_back {
+                    "Prefer Back-to-Back Classes"
+                } else {
+                    "Avoid Back-to-Back Classes"
+                },
+                back_to_back == prefers_back_to_back,
+            );
+            if prefers_back_to_back {
+                // this instructor prefers back-to-back classes
+                if back_to_back {
+                    score += weight.abs();
+                } else {
+                    score -= weight.abs();
+                    unmet.push(UnmetSoftConstraint {
+                        constraint_type: "Prefer Back-to-Back Classes".to_string(),
+                        description: format!(
+                            "Instructor {} has a gap between Course {} (ends at slot {}) and Course {} (starts at slot {}).",
+                            instructor_id,
+                            current.course_id,
+                            current_end_slot,
+                            next.course_id,
+                            next.start_slot
+                        ),
+                    });
+                }
+            } else if !back_to_back {
+                score += weight.abs(); // reward for not back-to-back
+            } else {
+                score -= weight.abs(); // penalty for back-to-back
+                unmet.push(UnmetSoftConstraint {
+                    constraint_type: "Avoid Back-to-Back Classes".to_string(),
+                    description: format!(
+                        "Instructor {} has back-to-back classes: Course {} (ends at slot {}) and Course {} (starts at slot {}).",
+                        instructor_id,
+                        current.course_id,
+                        current_end_slot,
+                        next.course_id,
+                        next.start_slot
+                    ),
+                });
+            }
+        }
+    }
+
+    (score, unmet, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Parses a JSON input, filling in an empty instructor availability where it is left out.
+    fn input(mut value: serde_json::Value) -> SchedulingInput {
+        for instructor in value["instructors"].as_array_mut().into_iter().flatten() {
+            if instructor.get("unavailableSlots").is_none() {
+                instructor["unavailableSlots"] = json!([]);
+            }
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn course(id: u32, duration_slots: u32, instructor_id: u32) -> serde_json::Value {
+        json!({
+            "id": id,
+            "requiredCapacity": 10,
+            "durationSlots": duration_slots,
+            "instructorIds": [instructor_id]
+        })
+    }
+
+    #[test]
+    fn warm_start_at_the_optimum_keeps_it() {
+        let mut input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1, "backToBackWeight": -1.0}, {"id": 2}],
+            "courses": [course(1, 4, 1), course(2, 2, 1), course(3, 1, 2), course(4, 2, 2)],
+            "totalTimeslots": 12,
+            "periodsPerDay": 6
+        }));
+        let cold = solve(&input).unwrap();
+
+        input.warm_start = cold.assignments.clone();
+        let warm = solve(&input).unwrap();
+        assert_eq!(warm.objective_value, cold.objective_value);
+        assert_eq!(warm.assignments, cold.assignments);
+        assert_eq!(warm.warm_start_changes, Some(0));
+
+        // an entry that no longer fits is dropped rather than failing the solve
+        input.warm_start[0].start_slot = u32::MAX;
+        assert!(validate_input(&input).valid);
+        let repaired = solve(&input).unwrap();
+        assert_eq!(repaired.objective_value, cold.objective_value);
+        assert_eq!(repaired.warm_start_changes, Some(1));
+    }
+
+    fn start_of(assignments: &[Assignment], course_id: u32) -> Timeslot {
+        assignments.iter().find(|a| a.course_id == CourseId(course_id)).unwrap().start_slot
+    }
 
-/// solves the scheduling problem using the HiGHs ILP solver.
+    #[test]
+    fn back_to_back_weight_is_per_instructor_and_scored_by_size() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [
+                {"id": 1, "backToBackWeight": -2.0},
+                {"id": 2, "backToBackWeight": 1.5}
+            ],
+            "courses": [course(1, 1, 1), course(2, 1, 1), course(3, 1, 2), course(4, 1, 2)],
+            "totalTimeslots": 6
+        }));
+        let output = solve(&input).unwrap();
+        let gap = |first, second| {
+            start_of(&output.assignments, first).abs_diff(start_of(&output.assignments, second))
+        };
+        assert_eq!(gap(1, 2), 1);
+        assert!(gap(3, 4) > 1);
+        // four morning starts, plus each instructor's pair by the size of their weight
+        assert_eq!(output.score, 4.0 + 2.0 + 1.5);
+    }
 
-pub fn solve(input: &SchedulingInput) -> Result<SchedulingOutput, String> {
-    let start_time = Instant::now();
-    // lookups
-    let course_map: HashMap<CourseId, &Course> = input.courses.iter().map(|c| (c.id, c)).collect();
-    let instructor_map: HashMap<InstructorId, &Instructor> =
-        input.instructors.iter().map(|i| (i.id, i)).collect();
-    let instructor_courses: HashMap<InstructorId, Vec<CourseId>> = input
-        .courses
-        .iter()
-        .map(|c| (c.instructor_id, c.id))
-        .into_group_map();
+    #[test]
+    fn unmet_mornings_describe_the_actual_morning() {
+        // the instructor is only free at slot 6, the third period of day 1
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": [0, 1, 2, 3, 4, 5, 7]}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4
+        }));
+        let output = solve(&input).unwrap();
+        let [unmet] = output.unmet_soft_constraints.as_slice() else {
+            panic!("expected one unmet preference, got {:?}", output.unmet_soft_constraints);
+        };
+        assert!(
+            unmet.description.contains("first 2 period(s) of each day, slots 4 to 5 on day 1"),
+            "{}",
+            unmet.description
+        );
+    }
 
-    //model setup
-    info!(
-        "Setting up ILP model with {} courses, {} rooms, and {} timeslots...",
-        input.courses.len(),
-        input.rooms.len(),
-        input.total_timeslots
-    );
-    let mut problem = ProblemVariables::new();
-    let mut all_possible_assignments = Vec::new();
+    #[test]
+    fn consecutive_slot_cap_forces_a_break_but_not_across_days() {
+        let capped = |total_timeslots: u32, periods_per_day: u32| {
+            input(json!({
+                "rooms": [{"id": 1, "capacity": 30}],
+                "instructors": [{"id": 1, "maxConsecutiveSlots": 2}],
+                "courses": [course(1, 2, 1), course(2, 2, 1)],
+                "totalTimeslots": total_timeslots,
+                "periodsPerDay": periods_per_day
+            }))
+        };
+        let one_day = capped(5, 5);
+        for assignments in [solve(&one_day).unwrap().assignments, greedy_solve(&one_day).unwrap()] {
+            assert!(start_of(&assignments, 1).abs_diff(start_of(&assignments, 2)) >= 3);
+        }
+        // the two courses fill both days back to back, which is no run at all
+        let two_days = capped(4, 2);
+        assert_eq!(solve(&two_days).unwrap().assignments.len(), 2);
+        assert_eq!(greedy_solve(&two_days).unwrap().len(), 2);
+    }
 
-    // x_crt =  1 if course c is in room r at time t
-    //          0 otherwise
+    #[test]
+    fn scaled_morning_reward_puts_the_longer_course_in_the_morning() {
+        // only slot 0 is in the morning, and one of the two courses has to start there
+        let mut input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 2, 2)],
+            "totalTimeslots": 3
+        }));
+        input.scale_morning_by_duration = true;
+        let scaled = solve(&input).unwrap();
+        assert_eq!(start_of(&scaled.assignments, 2), 0);
+    }
 
-    // pre-filter for performance; implicitly handle some hard constraints
-    for course in &input.courses {
-        for room in &input.rooms {
-            for start_slot in 0..input.total_timeslots {
-                if is_assignment_possible(course, room, start_slot, input, &instructor_map) {
-                    all_possible_assignments.push((course.id, room.id, start_slot));
+    #[test]
+    fn a_group_daily_window_pulls_a_course_into_it() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1), course(2, 1, 1)],
+            "totalTimeslots": 6
+        });
+        // the morning preference keeps both courses before slot 3
+        assert!(start_of(&solve(&input(value.clone())).unwrap().assignments, 1) < 3);
+
+        value["studentGroups"] = json!([{"id": 1, "courseIds": [1], "dailyWindow": [3, 6]}]);
+        assert!(start_of(&solve(&input(value.clone())).unwrap().assignments, 1) >= 3);
+
+        value["courses"][0]["durationSlots"] = json!(4);
+        match solve(&input(value)) {
+            Err(SolveError::Infeasible(reason)) => assert!(reason.contains("daily window")),
+            other => panic!("expected an infeasible input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn one_session_re_solves_with_new_weights() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1), course(2, 1, 1)],
+            "totalTimeslots": 4,
+            "periodsPerDay": 4
+        }));
+        let mut session = SolverSession::new(&input).unwrap();
+        let apart = SolverWeights {
+            back_to_back_penalty: 10.0,
+            ..SolverWeights::default()
+        };
+        let together = SolverWeights {
+            back_to_back_penalty: -1.0,
+            ..SolverWeights::default()
+        };
+        let first = session.resolve_with_weights(apart.clone()).unwrap();
+        let gap = |output: &SchedulingOutput| {
+            start_of(&output.assignments, 1).abs_diff(start_of(&output.assignments, 2))
+        };
+        assert!(gap(&first) > 1);
+        let second = session.resolve_with_weights(together).unwrap();
+        assert_eq!(gap(&second), 1);
+        let third = session.resolve_with_weights(apart).unwrap();
+        assert_eq!(third.score, first.score);
+
+        // a term that needs variables of its own can't be switched on afterwards
+        let isolated = SolverWeights {
+            isolation_weight: 1.0,
+            ..SolverWeights::default()
+        };
+        assert!(matches!(
+            session.resolve_with_weights(isolated),
+            Err(SolveError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn an_anchored_course_lands_next_to_a_blocked_target() {
+        let mut anchored = course(1, 1, 1);
+        anchored["targetSlot"] = json!(3);
+        anchored["targetWeight"] = json!(2.0);
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": [3]}],
+            "courses": [anchored],
+            "totalTimeslots": 8
+        }));
+        let output = solve(&input).unwrap();
+        assert_eq!(start_of(&output.assignments, 1).abs_diff(3), 1);
+        let [deviation] = output.target_deviations.as_slice() else {
+            panic!("expected one deviation, got {:?}", output.target_deviations);
+        };
+        assert_eq!((deviation.course_id, deviation.deviation), (CourseId(1), 1));
+    }
+
+    #[test]
+    fn a_recurring_room_block_covers_its_period_of_every_day() {
+        let input = input(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "recurringUnavailable": [[0, 0], [1, 0], [2, 0]]},
+                {"id": 2, "capacity": 30, "recurringUnavailable": [[1, 2]]}
+            ],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 9,
+            "periodsPerDay": 3
+        }));
+        let session = SolverSession::new(&input).unwrap();
+        let starts_in = |room_id: u32| -> Vec<Timeslot> {
+            let mut starts: Vec<Timeslot> = session
+                .assignment_vars_map
+                .keys()
+                .filter(|(_, r_id, _)| *r_id == RoomId(room_id))
+                .map(|(_, _, start_slot)| *start_slot)
+                .collect();
+            starts.sort();
+            starts
+        };
+        assert_eq!(starts_in(1), [1, 2, 4, 5, 7, 8]);
+        assert_eq!(starts_in(2), [0, 1, 2, 3, 4, 6, 7, 8]);
+    }
+
+    #[test]
+    fn courses_land_on_preferred_days_while_those_have_room() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "preferredDays": [2]}, {"id": 2, "preferredDays": [0]}],
+            "courses": [
+                course(1, 1, 1), course(2, 1, 1),
+                course(3, 1, 2), course(4, 1, 2), course(5, 1, 2)
+            ],
+            "totalTimeslots": 6,
+            "periodsPerDay": 2,
+            "weights": {"morningPreference": 0.0, "backToBackPenalty": 0.0}
+        }));
+        let output = solve(&input).unwrap();
+        let day_of = |course_id| input.day_of(start_of(&output.assignments, course_id));
+        assert_eq!((day_of(1), day_of(2)), (2, 2));
+        assert_eq!([3, 4, 5].map(day_of).iter().filter(|day| **day == 0).count(), 2);
+
+        let off_preference: Vec<_> = output
+            .unmet_soft_constraints
+            .iter()
+            .filter(|u| u.constraint_type == "Preferred Teaching Days")
+            .collect();
+        assert_eq!(off_preference.len(), 1, "{:?}", output.unmet_soft_constraints);
+        assert!(off_preference[0].description.contains("on day 1"));
+    }
+
+    #[test]
+    fn department_compactness_pulls_its_courses_together() {
+        let mut first = course(1, 1, 1);
+        first["targetSlot"] = json!(0);
+        let mut second = course(2, 1, 2);
+        second["targetSlot"] = json!(7);
+        for course in [&mut first, &mut second] {
+            course["targetWeight"] = json!(0.1);
+            course["departmentId"] = json!(4);
+        }
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [first, second],
+            "totalTimeslots": 8,
+            "weights": {"morningPreference": 0.0}
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        let [span] = output.department_spans.as_slice() else {
+            panic!("expected one department, got {:?}", output.department_spans);
+        };
+        assert_eq!((span.department_id, span.first_slot, span.end_slot), (4, 0, 1));
+
+        value["weights"] = json!({"morningPreference": 0.0, "departmentCompactness": 0.0});
+        let apart = solve(&input(value)).unwrap();
+        assert_eq!(start_of(&apart.assignments, 2), 7);
+        assert_eq!(apart.department_spans[0].end_slot, 8);
+    }
+
+    #[test]
+    fn a_boundary_slot_may_be_touched_but_a_blocked_slot_not_used() {
+        let mut bounded = course(1, 3, 1);
+        bounded["noCrossSlots"] = json!([2]);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [bounded],
+            "totalTimeslots": 6
+        });
+        let starts = |value: &serde_json::Value| -> Vec<Timeslot> {
+            let session = SolverSession::new(&input(value.clone())).unwrap();
+            let mut starts: Vec<Timeslot> =
+                session.assignment_vars_map.keys().map(|(_, _, k)| *k).collect();
+            starts.sort();
+            starts
+        };
+        // starting on the boundary or ending in it is fine; only running through it is not
+        assert_eq!(starts(&value), [0, 2, 3]);
+
+        value["courses"][0]["noCrossSlots"] = json!([]);
+        value["rooms"][0]["recurringUnavailable"] = json!([[0, 2]]);
+        assert_eq!(starts(&value), [3]);
+    }
+
+    #[test]
+    fn adjacency_reward_pulls_a_recitation_right_after_its_lecture() {
+        let mut recitation = course(2, 1, 2);
+        recitation["targetSlot"] = json!(5);
+        recitation["targetWeight"] = json!(0.1);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            // the lecture's instructor is only free at slot 0
+            "instructors": [{"id": 1, "unavailableSlots": [1, 2, 3, 4, 5]}, {"id": 2}],
+            "courses": [course(1, 1, 1), recitation],
+            "totalTimeslots": 6,
+            "weights": {"morningPreference": 0.0},
+            "softAdjacency": [[1, 2]]
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(start_of(&output.assignments, 2), 1);
+        assert!(output.adjacencies[0].satisfied);
+
+        value["weights"] = json!({"morningPreference": 0.0, "adjacency": 0.0});
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(start_of(&output.assignments, 2), 5);
+        assert!(!output.adjacencies[0].satisfied);
+    }
+
+    #[test]
+    fn a_duration_near_u32_max_is_invalid_not_wrapped() {
+        for duration_slots in [u32::MAX, u32::MAX - 1, 5] {
+            let input = input(json!({
+                "rooms": [{"id": 1, "capacity": 30}],
+                "instructors": [{"id": 1}],
+                "courses": [course(1, duration_slots, 1), course(2, 1, 1)],
+                "totalTimeslots": 4
+            }));
+            assert!(!validate_input(&input).valid);
+            assert!(possible_assignments(&input).iter().all(|(c_id, _, _)| *c_id == CourseId(2)));
+            match solve(&input) {
+                Err(SolveError::InvalidInput(message)) => {
+                    assert!(message.contains("Course 1"), "{}", message)
                 }
+                other => panic!("expected invalid input, got {:?}", other),
             }
         }
     }
-    trace!(
-        "Generated {} potential assignment variables out of a theoretical maximum of {}.",
-        all_possible_assignments.len(),
-        input.courses.len() * input.rooms.len() * input.total_timeslots as usize
-    );
 
-    if all_possible_assignments.is_empty() {
-        return Err("No possible assignments found after pre-filtering. The problem might be too constrained.".to_string());
+    #[test]
+    fn rightsizing_moves_a_small_course_out_of_a_huge_room() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 300}, {"id": 2, "capacity": 15}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 2,
+            "weights": {"rightsizing": 1.0},
+            "maxCapacitySlack": 50
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(output.assignments[0].room_id, RoomId(2));
+        assert!(output.unmet_soft_constraints.is_empty());
+
+        // with only the hall left the mismatch is reported
+        value["rooms"][1]["recurringUnavailable"] = json!([[0, 0], [0, 1]]);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.assignments[0].room_id, RoomId(1));
+        let [mismatch] = output.unmet_soft_constraints.as_slice() else {
+            panic!("expected one mismatch, got {:?}", output.unmet_soft_constraints);
+        };
+        assert_eq!(mismatch.constraint_type, "Right-Sized Rooms");
+    }
+
+    #[test]
+    fn latest_instructor_slot_mode_finishes_every_instructor_early() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2, "unavailableSlots": [0, 1]}],
+            "courses": [course(1, 2, 1), course(2, 1, 1), course(3, 1, 2)],
+            "totalTimeslots": 12,
+            "periodsPerDay": 6,
+            "objectiveMode": "minimizeLatestInstructorSlot"
+        }));
+        let output = solve(&input).unwrap();
+        let ends: Vec<_> = output
+            .instructor_latest_ends
+            .iter()
+            .map(|end| (end.instructor_id.0, end.latest_end_period))
+            .collect();
+        // both days start free for instructor 1, so its courses go first thing on separate days
+        assert_eq!(ends, [(1, 2), (2, 1)]);
+        assert!(output.assignments.iter().all(|a| input.period_of(a.start_slot) == 0));
     }
 
-    // decision map
-    let mut assignment_vars_map: HashMap<(CourseId, RoomId, Timeslot), Variable> = HashMap::new();
-    let assignment_vars_vec =
-        problem.add_vector(variable().binary(), all_possible_assignments.len());
+    #[test]
+    fn courses_sharing_a_conflicting_tag_are_forced_apart() {
+        let mut first = course(1, 1, 1);
+        let mut second = course(2, 1, 2);
+        first["tags"] = json!(["exam-review", "math"]);
+        second["tags"] = json!(["exam-review"]);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [first, second],
+            "totalTimeslots": 2
+        });
+        // the morning preference alone puts both in slot 0
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&output.assignments, 1), start_of(&output.assignments, 2)), (0, 0));
+
+        value["tagConflicts"] = json!(["exam-review"]);
+        let output = solve(&input(value.clone())).unwrap();
+        assert_ne!(start_of(&output.assignments, 1), start_of(&output.assignments, 2));
 
-    for (i, (course_id, room_id, start_slot)) in all_possible_assignments.iter().enumerate() {
-        assignment_vars_map.insert((*course_id, *room_id, *start_slot), assignment_vars_vec[i]);
+        value["totalTimeslots"] = json!(1);
+        assert!(matches!(solve(&input(value)), Err(SolveError::Infeasible(_))));
     }
 
-    //begin soft constraints
-    let mut back_to_back_links = Vec::new();
-    if input.total_timeslots > 1 {
-        for instructor_id in instructor_courses.keys() {
-            let courses = instructor_courses.get(instructor_id).unwrap();
-            for k in 0..(input.total_timeslots - 1) {
-                let starts_at_k_plus_1: Expression = assignment_vars_map
-                    .iter()
-                    .filter(|((c_id, _, start_slot), _)| {
-                        courses.contains(c_id) && *start_slot == k + 1
-                    })
-                    .map(|(_, var)| *var)
-                    .sum();
-                let ends_at_k: Expression = assignment_vars_map
-                    .iter()
-                    .filter(|((c_id, _, start_slot), _)| {
-                        let course = course_map.get(c_id).unwrap();
-                        courses.contains(c_id) && (*start_slot + course.duration_slots - 1) == k
-                    })
-                    .map(|(_, var)| *var)
-                    .sum();
+    #[test]
+    fn a_course_pinned_to_day_two_never_starts_elsewhere() {
+        let mut pinned = course(1, 1, 1);
+        pinned["requiredDay"] = json!(2);
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [pinned, course(2, 1, 1)],
+            "totalTimeslots": 9,
+            "periodsPerDay": 3
+        }));
+        let pinned_starts: Vec<Timeslot> = possible_assignments(&input)
+            .iter()
+            .filter(|(c_id, _, _)| *c_id == CourseId(1))
+            .map(|(_, _, k)| *k)
+            .collect();
+        assert_eq!(pinned_starts, [6, 7, 8, 6, 7, 8]);
+        let output = solve(&input).unwrap();
+        assert_eq!(input.day_of(start_of(&output.assignments, 1)), 2);
+    }
+
+    #[test]
+    fn a_negative_friday_weight_moves_courses_to_earlier_days() {
+        let courses: Vec<_> = (1..=3)
+            .map(|id| {
+                let mut friday_course = course(id, 1, id);
+                friday_course["targetSlot"] = json!(8);
+                friday_course["targetWeight"] = json!(0.1);
+                friday_course
+            })
+            .collect();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "courses": courses,
+            "totalTimeslots": 10,
+            "periodsPerDay": 2
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert!(output.assignments.iter().any(|a| a.start_slot == 8));
+
+        value["dayWeights"] = json!([0.0, 0.0, 0.0, 0.0, -1.0]);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.courses_per_day.len(), 5);
+        assert_eq!(output.courses_per_day[4], 0);
+        assert_eq!(output.courses_per_day.iter().sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn the_objective_breakdown_prices_the_chosen_schedule() {
+        let mut anchored = course(3, 2, 2);
+        anchored["targetSlot"] = json!(5);
+        let mut input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 100}],
+            "instructors": [{"id": 1, "preferredDays": [1]}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), anchored],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4,
+            "weights": {"rightsizing": 0.5},
+            "explainObjective": true
+        }));
+        let output = solve(&input).unwrap();
+        let breakdown = output.objective_breakdown.unwrap();
+        assert!(breakdown.len() > 1, "{:?}", breakdown);
+        let mornings = output.assignments.iter().filter(|a| input.is_morning(a.start_slot));
+        assert_eq!(breakdown["morningPreference"], mornings.count() as f64);
+        let deviation: u32 = output.target_deviations.iter().map(|d| d.deviation).sum();
+        assert_eq!(breakdown["targetDeviation"], -(deviation as f64));
+        assert!(breakdown["rightsizing"] < 0.0, "{:?}", breakdown);
+
+        input.explain_objective = false;
+        assert!(solve(&input).unwrap().objective_breakdown.is_none());
+    }
+
+    #[test]
+    fn a_co_taught_course_needs_both_instructors_free() {
+        let mut co_taught = course(1, 1, 1);
+        co_taught["instructorIds"] = json!([1, 2]);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2, "unavailableSlots": [0]}],
+            "courses": [co_taught, course(2, 1, 2)],
+            "totalTimeslots": 3
+        });
+        // slot 0 is out for the co-taught course, and instructor 2 can't teach both at once
+        let output = solve(&input(value.clone())).unwrap();
+        let (first, second) = (start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+        assert!(first != 0 && second != 0 && first != second);
 
-                let penalty_var = problem.add(variable().binary());
-                back_to_back_links.push((starts_at_k_plus_1, ends_at_k, penalty_var));
+        value["instructors"][1]["unavailableSlots"] = json!([0, 1, 2]);
+        value["courses"][1]["instructorIds"] = json!([1]);
+        match solve(&input(value)) {
+            Err(SolveError::Infeasible(reason)) => {
+                assert!(reason.contains("Course 1"), "{}", reason)
             }
+            other => panic!("expected an infeasible input, got {:?}", other),
         }
     }
-    // soft constraints
-    // soft constraint weights
-    let morning_preference_weight = 1.0;
-    let back_to_back_penalty_weight = 0.5;
 
-    let morning_cutoff = input.total_timeslots / 2; //assume morining is from 0-5 out of assumed 12 slots
-    let morning_score: Expression = assignment_vars_map
-        .iter()
-        .filter(|((_, _, start_slot), _)| *start_slot < morning_cutoff)
-        .map(|(_, var)| *var)
-        .sum();
-    let back_to_back_penalty_score: Expression =
-        back_to_back_links.iter().map(|(_, _, var)| *var).sum();
-
-    let objective = morning_preference_weight * morning_score
-        - back_to_back_penalty_weight * back_to_back_penalty_score;
-    info!("Objective function defined with morning preference and back-to-back penalties.");
-
-    let mut model = problem
-        .maximise(objective)
-        .using(default_solver)
-        .set_option("threads", 1) // limit to 1 thread for reproducibility
-        .set_option("random_seed", 1234) //set seed for reproducibility
-        .set_option("log_to_console", "true");
-    // begin hard constraints
-
-    // sanity check so course schedule makes sense
-    info!("Adding 'course scheduled once' constraints...");
-    for course in &input.courses {
-        let scheduled_once: Expression = assignment_vars_map
-            .iter()
-            .filter(|((c_id, _, _), _)| *c_id == course.id)
-            .map(|(_, var)| *var)
-            .sum();
-        model.add_constraint(constraint!(scheduled_once == 1));
+    #[test]
+    fn a_minimum_per_active_slot_clusters_courses_into_fuller_slots() {
+        let courses: Vec<_> = (1..=4)
+            .map(|id| {
+                let mut spread = course(id, 1, id);
+                spread["targetSlot"] = json!(id - 1);
+                spread["targetWeight"] = json!(0.1);
+                spread
+            })
+            .collect();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}, {"id": 4}],
+            "courses": courses,
+            "totalTimeslots": 4,
+            "weights": {"morningPreference": 0.0}
+        });
+        let used_slots = |output: &SchedulingOutput| {
+            output.assignments.iter().map(|a| a.start_slot).collect::<HashSet<_>>().len()
+        };
+        assert_eq!(used_slots(&solve(&input(value.clone())).unwrap()), 4);
+
+        value["minCoursesPerActiveSlot"] = json!(2);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(used_slots(&output), 2);
+        assert_eq!(output.assignments.len(), 4);
     }
 
-    // no room double-booking
-    info!("Adding 'no room overlap' constraints...");
-    for room in &input.rooms {
-        for k in 0..input.total_timeslots {
-            let room_occupied: Expression = assignment_vars_map
-                .iter()
-                .filter(|((_, r_id, _), _)| *r_id == room.id)
-                .filter(|((c_id, _, start_slot), _)| {
-                    let course = course_map.get(c_id).unwrap();
-                    // Check if the course occupies the room at timeslot k
-                    k >= *start_slot && k < *start_slot + course.duration_slots
-                })
-                .map(|(_, var)| *var)
-                .sum();
-            model.add_constraint(constraint!(room_occupied <= 1));
+    #[test]
+    fn a_reserved_room_is_only_used_once_the_others_are_full() {
+        let mut value = json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "reservePreference": 1.0},
+                {"id": 2, "capacity": 30}
+            ],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 1
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(output.assignments[0].room_id, RoomId(2));
+        let usage = &output.reserved_rooms;
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].room_id, usage[0].used, usage[0].course_count), (RoomId(1), false, 0));
+
+        value["courses"] = json!([course(1, 1, 1), course(2, 1, 2)]);
+        let output = solve(&input(value)).unwrap();
+        let usage = &output.reserved_rooms;
+        assert_eq!((usage[0].used, usage[0].course_count), (true, 1));
+    }
+
+    #[test]
+    fn the_lp_model_has_a_binary_per_candidate_and_back_to_back_pair() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 5}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 2, 1), course(2, 1, 2)],
+            "totalTimeslots": 3
+        });
+        let binaries = |value: &serde_json::Value| {
+            let lp = to_lp_format(&input(value.clone())).unwrap();
+            let section = lp.split_once("\nbin\n").unwrap().1;
+            section.lines().take_while(|line| line.starts_with(' ')).count()
+        };
+        // room 2 is too small, so course 1 has 2 candidate starts and course 2 has 3
+        assert_eq!(possible_assignments(&input(value.clone())).len(), 5);
+        // plus one back-to-back indicator per instructor and pair of neighbouring slots
+        assert_eq!(binaries(&value), 5 + 2 * 2);
+
+        for instructor in value["instructors"].as_array_mut().unwrap() {
+            instructor["backToBackWeight"] = json!(0.0);
         }
+        assert_eq!(binaries(&value), 5);
     }
 
-    // no instructor overlap
-    info!("Adding 'no instructor overlap' constraints...");
-    for instructor in &input.instructors {
-        if let Some(courses_for_instructor) = instructor_courses.get(&instructor.id) {
-            for k in 0..input.total_timeslots {
-                let instructor_busy: Expression = assignment_vars_map
-                    .iter()
-                    .filter(|((c_id, _, _), _)| courses_for_instructor.contains(c_id))
-                    .filter(|((c_id, _, start_slot), _)| {
-                        let course = course_map.get(c_id).unwrap();
-                        k >= *start_slot && k < *start_slot + course.duration_slots
-                    })
-                    .map(|(_, var)| *var)
-                    .sum();
-                model.add_constraint(constraint!(instructor_busy <= 1));
+    #[test]
+    fn overriding_unavailability_makes_an_infeasible_instance_solvable_at_a_cost() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": [0, 1], "overrideCost": 50.0}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 2
+        });
+        assert!(matches!(solve(&input(value.clone())), Err(SolveError::Infeasible(_))));
+
+        value["allowOverrideUnavailability"] = json!(true);
+        value["explainObjective"] = json!(true);
+        let output = solve(&input(value)).unwrap();
+        let [used] = output.unavailability_overrides.as_slice() else {
+            panic!("expected one override, got {:?}", output.unavailability_overrides);
+        };
+        assert_eq!((used.instructor_id, used.course_id), (InstructorId(1), CourseId(1)));
+        assert_eq!(used.slots, [output.assignments[0].start_slot]);
+        assert_eq!(used.cost, 50.0);
+        assert_eq!(output.objective_breakdown.unwrap()["unavailabilityOverrides"], -50.0);
+    }
+
+    #[test]
+    fn a_course_below_its_minimum_enrollment_is_cancelled_and_others_proceed() {
+        let mut small = course(1, 1, 1);
+        small["minEnrollment"] = json!(10);
+        small["enrolledStudents"] = json!(4);
+        let mut full = course(2, 1, 1);
+        full["minEnrollment"] = json!(10);
+        full["enrolledStudents"] = json!(10);
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [small, full, course(3, 1, 1)],
+            "totalTimeslots": 2
+        }));
+        // three courses of one instructor fit into two slots only with one cancelled
+        let output = solve(&input).unwrap();
+        assert_eq!(output.cancelled_courses, [CourseId(1)]);
+        let mut scheduled: Vec<_> = output.assignments.iter().map(|a| a.course_id).collect();
+        scheduled.sort();
+        assert_eq!(scheduled, [CourseId(2), CourseId(3)]);
+    }
+
+    #[test]
+    fn first_feasible_stops_early_but_keeps_the_hard_constraints() {
+        let courses: Vec<_> = (1..=12).map(|id| course(id, 1 + id % 2, id % 4 + 1)).collect();
+        let mut input = input(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}, {"id": 3, "capacity": 5}
+            ],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}, {"id": 4}],
+            "courses": courses,
+            "totalTimeslots": 12,
+            "periodsPerDay": 6,
+            "solveMode": "firstFeasible",
+            "explainObjective": true
+        }));
+        let output = solve(&input).unwrap();
+        assert_eq!(output.solve_status.mode, SolveMode::FirstFeasible);
+        assert!(!output.solve_status.proven_optimal);
+
+        assert_eq!(output.assignments.len(), 12);
+        let mut room_slots = HashSet::new();
+        let mut instructor_slots = HashSet::new();
+        for assignment in &output.assignments {
+            let course = &input.courses[assignment.course_id.0 as usize - 1];
+            assert_ne!(assignment.room_id, RoomId(3));
+            let end_slot = assignment.start_slot + course.duration_slots;
+            assert_eq!(input.day_of(assignment.start_slot), input.day_of(end_slot - 1));
+            for k in assignment.start_slot..end_slot {
+                assert!(room_slots.insert((assignment.room_id, k)));
+                assert!(instructor_slots.insert((course.instructors()[0], k)));
             }
         }
+
+        input.solve_mode = SolveMode::Optimize;
+        let optimal = solve(&input).unwrap();
+        assert!(optimal.solve_status.proven_optimal);
+        let objective = |output: &SchedulingOutput| -> f64 {
+            output.objective_breakdown.as_ref().unwrap().values().sum()
+        };
+        assert!(objective(&optimal) >= objective(&output) - 1e-6);
     }
 
-    //solve
-    info!("Starting ILP solver...");
-    let solution = match model.solve() {
-        Ok(s) => s,
-        Err(e) => {
-            return Err(format!(
-                "No solution found. The problem might be too constrained. Solver error: {}",
-                e
-            ));
-        }
-    };
-    let duration = start_time.elapsed();
-    info!("Solution found in {:.2?}", duration);
-
-    // get assignments from solution
-    let mut assignments = Vec::new();
-    for ((course_id, room_id, start_slot), var) in &assignment_vars_map {
-        if solution.value(*var) > 0.9 {
-            assignments.push(Assignment {
-                course_id: *course_id,
-                room_id: *room_id,
-                start_slot: *start_slot,
-            });
+    #[test]
+    fn an_instructors_courses_gravitate_to_their_home_room() {
+        let mut large = course(3, 1, 1);
+        large["requiredCapacity"] = json!(40);
+        let input = input(json!({
+            "rooms": [
+                {"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}, {"id": 3, "capacity": 50}
+            ],
+            "instructors": [{"id": 1, "homeRoom": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), large],
+            "totalTimeslots": 3
+        }));
+        let output = solve(&input).unwrap();
+        let room_of = |course_id| {
+            output.assignments.iter().find(|a| a.course_id == CourseId(course_id)).unwrap().room_id
+        };
+        assert_eq!((room_of(1), room_of(2), room_of(3)), (RoomId(2), RoomId(2), RoomId(3)));
+        let deviations: Vec<_> = output
+            .unmet_soft_constraints
+            .iter()
+            .filter(|u| u.constraint_type == "Home Room")
+            .collect();
+        assert_eq!(deviations.len(), 1);
+        assert!(deviations[0].description.starts_with("Course 3"), "{}", deviations[0]);
+    }
+
+    #[test]
+    fn a_course_count_cap_drops_an_optional_course() {
+        let mut optional = course(2, 1, 1);
+        optional["optional"] = json!(true);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1), optional],
+            "totalTimeslots": 4
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(output.assignments.len(), 2);
+        assert!(output.unscheduled_courses.is_empty());
+
+        value["instructors"][0]["maxCourses"] = json!(1);
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(output.assignments.len(), 1);
+        assert_eq!(output.assignments[0].course_id, CourseId(1));
+        assert_eq!(output.unscheduled_courses, [CourseId(2)]);
+
+        // a required course can't be dropped to meet the cap
+        value["courses"][1]["optional"] = json!(false);
+        assert!(solve(&input(value.clone())).is_err());
+
+        // nor can it be dropped when no room fits it, but an optional one can
+        value["instructors"][0]["maxCourses"] = json!(null);
+        value["courses"][1]["requiredCapacity"] = json!(40);
+        assert!(matches!(solve(&input(value.clone())), Err(SolveError::Infeasible(_))));
+        value["courses"][1]["optional"] = json!(true);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.unscheduled_courses, [CourseId(2)]);
+    }
+
+    #[test]
+    fn a_compact_schedule_weight_shortens_the_makespan() {
+        let courses: Vec<_> = (1..=3)
+            .map(|id| {
+                let mut late = course(id, 1, id);
+                late["targetSlot"] = json!(2 * id);
+                late["targetWeight"] = json!(0.1);
+                late
+            })
+            .collect();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "courses": courses,
+            "totalTimeslots": 8,
+            "weights": {"morningPreference": 0.0}
+        });
+        let spread = solve(&input(value.clone())).unwrap();
+        assert_eq!(spread.makespan, Some(7));
+
+        value["weights"] = json!({"morningPreference": 0.0, "compactSchedule": 1.0});
+        let compact = solve(&input(value)).unwrap();
+        // two rooms, so three single-slot courses need two slots at least
+        assert_eq!(compact.makespan, Some(2));
+        assert_eq!(compact.assignments.len(), 3);
+        let mut used: Vec<_> =
+            compact.assignments.iter().map(|a| (a.room_id, a.start_slot)).collect();
+        used.sort();
+        used.dedup();
+        assert_eq!(used.len(), 3);
+    }
+
+    #[test]
+    fn a_building_wide_cap_spreads_courses_over_more_slots() {
+        let mut value = json!({
+            "rooms": [
+                {"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}, {"id": 3, "capacity": 30}
+            ],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "courses": [course(1, 2, 1), course(2, 1, 2), course(3, 1, 3)],
+            "totalTimeslots": 6,
+            "morningCutoff": 1
+        });
+        let starts = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            [1, 2, 3].map(|id| start_of(&output.assignments, id))
+        };
+        assert_eq!(starts(&value), [0, 0, 0]);
+
+        value["maxConcurrentCourses"] = json!(2);
+        let [first, second, third] = starts(&value);
+        // course 1 runs through slots first and first + 1
+        let running = |k: Timeslot| {
+            usize::from((first..first + 2).contains(&k)) + usize::from(second == k)
+                + usize::from(third == k)
+        };
+        assert!((0..6).all(|k| running(k) <= 2));
+        assert!([first, second, third].iter().any(|k| *k != 0));
+    }
+
+    #[test]
+    fn an_isolation_penalty_puts_two_lone_courses_in_one_slot() {
+        let courses: Vec<_> = [(1, 1), (2, 5)]
+            .map(|(id, target)| {
+                let mut lone = course(id, 1, id);
+                lone["targetSlot"] = json!(target);
+                lone["targetWeight"] = json!(0.1);
+                lone
+            })
+            .to_vec();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": courses,
+            "totalTimeslots": 6,
+            "morningCutoff": 0,
+            "weights": {"isolationWeight": 0.01}
+        });
+        // too light to outweigh the targets, but the lone courses are reported
+        let apart = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&apart.assignments, 1), start_of(&apart.assignments, 2)), (1, 5));
+        assert_eq!(apart.isolated_slots, [1, 5]);
+
+        value["weights"] = json!({"isolationWeight": 1.0});
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+        assert!(output.isolated_slots.is_empty());
+    }
+
+    #[test]
+    fn a_failed_ilp_solve_falls_back_to_the_greedy_schedule() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 2, 1), course(2, 1, 1), course(3, 1, 2)],
+            "totalTimeslots": 4
+        });
+        let failure = || SolveError::SolverFailure("No solution found.".to_string());
+        let session = SolverSession::new(&input(value.clone())).unwrap();
+        let weights = SolverWeights::default();
+        let refused = session.fall_back(&weights, failure());
+        assert!(matches!(refused, Err(SolveError::SolverFailure(_))));
+
+        value["fallbackToHeuristic"] = json!(true);
+        let input = input(value);
+        let session = SolverSession::new(&input).unwrap();
+        let output = session.fall_back(&weights, failure()).unwrap();
+        assert_eq!(output.solve_status.method, SolveMethod::Heuristic);
+        assert!(!output.solve_status.proven_optimal);
+        assert_eq!(output.objective_value, None);
+        let mut placed = output.assignments.clone();
+        placed.sort();
+        let mut greedy = greedy_solve(&input).unwrap();
+        greedy.sort();
+        assert_eq!(placed, greedy);
+        // instructor 1 teaches courses 1 and 2, which must not overlap
+        let (first, second) = (start_of(&placed, 1), start_of(&placed, 2));
+        assert!(second >= first + 2 || second < first);
+    }
+
+    #[test]
+    fn labels_and_colours_pass_through_to_their_assignment() {
+        let mut labelled = course(1, 1, 1);
+        labelled["label"] = json!("Intro to X");
+        labelled["color"] = json!("#1f77b4");
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [labelled, course(2, 1, 1)],
+            "totalTimeslots": 4
+        }));
+        let output = solve(&input).unwrap();
+        let assignment = |course_id| {
+            output.assignments.iter().find(|a| a.course_id == CourseId(course_id)).unwrap()
+        };
+        assert_eq!(assignment(1).label.as_deref(), Some("Intro to X"));
+        assert_eq!(assignment(1).color.as_deref(), Some("#1f77b4"));
+        let plain = serde_json::to_value(assignment(2)).unwrap();
+        assert!(plain.get("label").is_none() && plain.get("color").is_none());
+    }
+
+    #[test]
+    fn requiring_every_room_spreads_courses_over_all_of_them() {
+        let mut value = json!({
+            "rooms": [
+                {"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}, {"id": 3, "capacity": 30}
+            ],
+            "instructors": [{"id": 1, "homeRoom": 1}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), course(3, 1, 1)],
+            "totalTimeslots": 4
+        });
+        let rooms_used = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            output.assignments.iter().map(|a| a.room_id).collect::<HashSet<_>>().len()
+        };
+        assert_eq!(rooms_used(&value), 1);
+
+        value["requireAllRoomsUsed"] = json!(true);
+        assert_eq!(rooms_used(&value), 3);
+
+        value["rooms"].as_array_mut().unwrap().push(json!({"id": 4, "capacity": 30}));
+        match solve(&input(value)) {
+            Err(SolveError::Infeasible(reason)) => {
+                assert!(reason.contains("4 rooms"), "{}", reason)
+            }
+            other => panic!("expected an infeasible input, got {:?}", other),
         }
     }
-    assignments.sort();
 
-    // get score
-    let (score, unmet_soft_constraints) =
-        calculate_score_and_unmet_constraints(&assignments, input, &course_map);
+    #[test]
+    fn an_integer_objective_is_exact_and_repeatable() {
+        assert_eq!(fraction_denominator(0.1), 10);
+        assert_eq!(fraction_denominator(-1.0 / 3.0), 3);
+        assert_eq!(fraction_denominator(2.0), 1);
 
-    // build the final output
-    Ok(SchedulingOutput {
-        assignments,
-        score,
-        unmet_soft_constraints,
-    })
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 2, 1), course(2, 1, 1), course(3, 1, 2), course(4, 2, 2)],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4,
+            "integerObjective": true,
+            "weights": {"morningPreference": 0.1, "backToBackPenalty": 0.3}
+        }));
+        let first = solve(&input).unwrap();
+        let second = solve(&input).unwrap();
+        let objective = first.objective_value.unwrap();
+        assert_eq!(second.objective_value, Some(objective));
+        assert_eq!(second.assignments, first.assignments);
+        // every weight is a multiple of 0.1, so the value is too, with no round-off left
+        assert_eq!(objective, (objective * 10.0).round() / 10.0);
+    }
 
-}
+    #[test]
+    fn a_zone_runs_two_courses_or_none() {
+        let courses: Vec<_> = [(1, 0), (2, 3)]
+            .map(|(id, target)| {
+                let mut wing_only = course(id, 1, id);
+                wing_only["requiredCapacity"] = json!(25);
+                wing_only["targetSlot"] = json!(target);
+                wing_only["targetWeight"] = json!(0.1);
+                wing_only
+            })
+            .to_vec();
+        let mut value = json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "zone": 1},
+                {"id": 2, "capacity": 30, "zone": 1},
+                {"id": 3, "capacity": 20}
+            ],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": courses,
+            "totalTimeslots": 4,
+            "morningCutoff": 0
+        });
+        let apart = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&apart.assignments, 1), start_of(&apart.assignments, 2)), (0, 3));
 
-// implicitly checks the hard constraints on overlap and capacity
-fn is_assignment_possible(
-    course: &Course,
-    room: &Room,
-    start_slot: Timeslot,
-    input: &SchedulingInput,
-    instructor_map: &HashMap<InstructorId, &Instructor>,
-) -> bool {
-    // course fits in remaining timeslots
-    if start_slot + course.duration_slots > input.total_timeslots {
-        return false;
+        // neither fits the room outside the wing, so the second joins the first
+        value["minCoursesPerActiveZone"] = json!(2);
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+
+        // courses that fit outside leave the wing empty instead of giving up their targets
+        for course in value["courses"].as_array_mut().unwrap() {
+            course["requiredCapacity"] = json!(10);
+        }
+        let output = solve(&input(value)).unwrap();
+        let placed: Vec<_> = output.assignments.iter().map(|a| (a.room_id, a.start_slot)).collect();
+        assert_eq!(placed, [(RoomId(3), 0), (RoomId(3), 3)]);
     }
 
-    // room has capacity
-    if room.capacity < course.required_capacity {
-        return false;
+    #[test]
+    fn a_reachable_target_is_met_and_an_unreachable_one_is_infeasible() {
+        // both courses in the morning is the best there is, worth 2
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 2)],
+            "totalTimeslots": 4,
+            "minObjective": 1.5
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert!(output.objective_value.unwrap() >= 1.5);
+        assert_eq!(output.solve_status.mode, SolveMode::FirstFeasible);
+        assert!(!output.solve_status.proven_optimal);
+
+        // the heuristic can't promise a target either, so it isn't tried
+        value["minObjective"] = json!(2.5);
+        value["fallbackToHeuristic"] = json!(true);
+        match solve(&input(value)) {
+            Err(SolveError::Infeasible(reason)) => assert!(reason.contains("2.5"), "{}", reason),
+            other => panic!("expected an infeasible target, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn the_only_available_eligible_instructor_is_chosen() {
+        let mut flexible = course(1, 1, 1);
+        flexible["instructorIds"] = json!([]);
+        flexible["eligibleInstructors"] = json!([1, 2, 3]);
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [
+                {"id": 1, "unavailableSlots": [0, 1, 2, 3]},
+                {"id": 2, "unavailableSlots": [0, 1, 2, 3]},
+                {"id": 3, "unavailableSlots": [0]}
+            ],
+            "courses": [flexible, course(2, 1, 3)],
+            "totalTimeslots": 4
+        }));
+        let output = solve(&input).unwrap();
+        let flexible = output.assignments.iter().find(|a| a.course_id == CourseId(1)).unwrap();
+        assert_eq!(flexible.instructor_id, Some(InstructorId(3)));
+        // instructor 3 also teaches course 2, so the two can't share a slot
+        assert_ne!(flexible.start_slot, start_of(&output.assignments, 2));
+        assert_ne!(flexible.start_slot, 0);
+    }
+
+    #[test]
+    fn prefilter_stats_count_the_surviving_candidates() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 5}],
+            "instructors": [{"id": 1, "unavailableSlots": [0]}],
+            "courses": [course(1, 2, 1)],
+            "totalTimeslots": 4
+        });
+        assert_eq!(solve(&input(value.clone())).unwrap().prefilter_stats, None);
+
+        value["includeStats"] = json!(true);
+        let stats = solve(&input(value)).unwrap().prefilter_stats.unwrap();
+        // room 2 is too small, slot 0 is unavailable and slot 3 leaves no room for two slots
+        assert_eq!((stats.candidates, stats.theoretical_max), (2, 8));
+        assert_eq!(stats.reduction_ratio, 0.75);
     }
 
-    // instructor has to be available
-    if let Some(instructor) = instructor_map.get(&course.instructor_id) {
-        let required_slots: HashSet<Timeslot> =
-            (start_slot..start_slot + course.duration_slots).collect();
-        let unavailable_set: HashSet<Timeslot> =
-            instructor.unavailable_slots.iter().cloned().collect();
+    #[test]
+    fn a_holiday_week_pushes_courses_into_the_next_one() {
+        let holiday = json!([0, 1, 2, 3]);
+        let courses: Vec<_> = (1..=2)
+            .map(|id| {
+                let mut early = course(id, 1, id);
+                early["targetSlot"] = json!(0);
+                early
+            })
+            .collect();
+        // three weeks of two days of two periods
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [
+                {"id": 1, "unavailableSlots": holiday},
+                {"id": 2, "unavailableSlots": holiday}
+            ],
+            "courses": courses,
+            "totalTimeslots": 12,
+            "periodsPerDay": 2,
+            "weeks": 3
+        }));
+        assert_eq!((input.days_per_week(), input.week_of(5), input.weekday_of(5)), (2, 1, 0));
+        assert_eq!(input.week_of(11), 2);
 
-        if !required_slots.is_disjoint(&unavailable_set) {
-            return false; // not available
+        let output = solve(&input).unwrap();
+        for assignment in &output.assignments {
+            assert_eq!(input.week_of(assignment.start_slot), 1, "{:?}", assignment);
         }
-    } else {
-        return false;
     }
 
-    true
-}
+    #[test]
+    fn room_clustering_keeps_an_instructors_rooms_close() {
+        let mut large = course(1, 1, 1);
+        large["requiredCapacity"] = json!(25);
+        // course 1 only fits room 1, which is only free in slot 0, so course 2 goes elsewhere
+        let mut value = json!({
+            "rooms": [
+                {"id": 1, "capacity": 30, "recurringUnavailable": [[0, 1], [0, 2], [0, 3]]},
+                {"id": 2, "capacity": 20},
+                {"id": 3, "capacity": 20}
+            ],
+            "instructors": [{"id": 1, "homeRoom": 2}],
+            "courses": [large, course(2, 1, 1)],
+            "totalTimeslots": 4,
+            "roomDistances": [[1, 2, 100.0], [1, 3, 1.0], [2, 3, 100.0]]
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        let room_of_second = |output: &SchedulingOutput| {
+            output.assignments.iter().find(|a| a.course_id == CourseId(2)).unwrap().room_id
+        };
+        assert_eq!(room_of_second(&output), RoomId(2));
+        assert!(output.instructor_room_distances.is_empty());
 
-fn calculate_score_and_unmet_constraints(
-    assignments: &[Assignment],
-    input: &SchedulingInput,
-    course_map: &HashMap<CourseId, &Course>,
-) -> (i32, Vec<UnmetSoftConstraint>) {
-    let mut score = 0;
-    let mut unmet = Vec::new();
-    let morning_cutoff = input.total_timeslots / 2;
+        value["weights"] = json!({"roomClusteringWeight": 1.0});
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(room_of_second(&output), RoomId(3));
+        let distances: Vec<_> = output
+            .instructor_room_distances
+            .iter()
+            .map(|d| (d.instructor_id, d.total_distance))
+            .collect();
+        assert_eq!(distances, [(InstructorId(1), 1.0)]);
+    }
 
-    // prefer morning slots.
-    for assignment in assignments {
-        if assignment.start_slot < morning_cutoff {
-            score += 1; //add score if met
-        } else {
-            score -= 1; //penalize if not met
-            unmet.push(UnmetSoftConstraint {
-                constraint_type: "Prefer Mornings".to_string(),
-                description: format!(
-                    "Course {} is scheduled at slot {}, which is not in the morning. Morning starts at 6 am (slot 0) and ends at 12 pm (slot 6)",
-                    assignment.course_id, assignment.start_slot
-                ),
-            });
+    #[test]
+    fn a_start_cap_on_the_first_slot_moves_a_course_later() {
+        let courses: Vec<_> = (1..=2)
+            .map(|id| {
+                let mut early = course(id, 2, id);
+                early["targetSlot"] = json!(0);
+                early
+            })
+            .collect();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": courses,
+            "totalTimeslots": 4
+        });
+        let starts = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            let mut starts: Vec<_> = output.assignments.iter().map(|a| a.start_slot).collect();
+            starts.sort_unstable();
+            starts
+        };
+        assert_eq!(starts(&value), [0, 0]);
+
+        // only starts count: the course in slot 0 still runs through slot 1
+        value["maxCoursesInSlot"] = json!([[0, 1]]);
+        assert_eq!(starts(&value), [0, 1]);
+    }
+
+    #[test]
+    fn a_daily_span_bound_rules_out_an_early_and_late_day() {
+        let courses: Vec<_> = [(1, 0), (2, 5)]
+            .map(|(id, target)| {
+                let mut pinned = course(id, 1, 1);
+                pinned["targetSlot"] = json!(target);
+                pinned
+            })
+            .to_vec();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": courses,
+            "totalTimeslots": 6,
+            "periodsPerDay": 6
+        });
+        let span = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            let first = start_of(&output.assignments, 1);
+            let second = start_of(&output.assignments, 2);
+            first.max(second) + 1 - first.min(second)
+        };
+        assert_eq!(span(&value), 6);
+
+        value["instructors"][0]["maxDailySpan"] = json!(3);
+        assert_eq!(span(&value), 3);
+    }
+
+    #[test]
+    fn a_day_soft_cap_lightens_the_day_unless_nothing_else_fits() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "courses": [course(1, 1, 1), course(2, 1, 2), course(3, 1, 3)],
+            "totalTimeslots": 4,
+            "periodsPerDay": 2,
+            "morningCutoff": 0,
+            "dayWeights": [0.0, 1.0]
+        });
+        let on_day_one = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            let count = output.assignments.iter().filter(|a| a.start_slot >= 2).count();
+            (count, output.day_overflows)
+        };
+        assert_eq!(on_day_one(&value).0, 3);
+
+        value["daySoftCaps"] = json!([[1, 1, 5.0]]);
+        let (count, overflows) = on_day_one(&value);
+        assert_eq!(count, 1);
+        assert_eq!((overflows[0].day, overflows[0].course_count, overflows[0].overflow), (1, 1, 0));
+
+        // with day 0 closed the cap bends instead of failing
+        value["maintenanceWindows"] = json!([{"slots": [0, 1], "reason": "Closed"}]);
+        let (count, overflows) = on_day_one(&value);
+        assert_eq!(count, 3);
+        assert_eq!((overflows[0].course_count, overflows[0].overflow), (3, 2));
+    }
+
+    #[test]
+    fn a_distinct_instructor_group_splits_two_flexible_courses() {
+        let courses: Vec<_> = (1..=2)
+            .map(|id| {
+                let mut flexible = course(id, 1, 1);
+                flexible["instructorIds"] = json!([]);
+                flexible["eligibleInstructors"] = json!([1, 2]);
+                flexible
+            })
+            .collect();
+        // only instructor 1 is rewarded for the one room, so both courses go to them
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "homeRoom": 1}, {"id": 2}],
+            "courses": courses,
+            "totalTimeslots": 4
+        });
+        let instructors = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            let mut chosen: Vec<_> = output.assignments.iter().map(|a| a.instructor_id).collect();
+            chosen.sort();
+            chosen
+        };
+        assert_eq!(instructors(&value), [Some(InstructorId(1)); 2]);
+
+        value["distinctInstructorGroups"] = json!([[1, 2]]);
+        assert_eq!(instructors(&value), [Some(InstructorId(1)), Some(InstructorId(2))]);
+    }
+
+    #[test]
+    fn the_variable_cap_counts_candidates_after_pre_filtering() {
+        // one room and a 2-slot course over 4 slots: 3 candidates
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 2, 1)],
+            "totalTimeslots": 4,
+            "maxVariables": 3
+        });
+        assert_eq!(solve(&input(value.clone())).unwrap().assignments.len(), 1);
+
+        value["maxVariables"] = json!(2);
+        match solve(&input(value.clone())) {
+            Err(SolveError::InvalidInput(message)) => assert_eq!(
+                message,
+                "Pre-filtering left 3 candidate assignments, more than maxVariables (2)."
+            ),
+            other => panic!("expected the cap to be hit, got {:?}", other),
         }
+
+        value["fallbackToHeuristic"] = json!(true);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.solve_status.method, SolveMethod::Heuristic);
+        assert_eq!(output.assignments.len(), 1);
     }
 
-    // avoid back-to-back classes for instructors
-    let instructor_assignments: HashMap<InstructorId, Vec<&Assignment>> = assignments
-        .iter()
-        .filter_map(|a| course_map.get(&a.course_id).map(|c| (c.instructor_id, a)))
-        .into_group_map();
+    #[test]
+    fn a_linked_lab_starts_after_its_lecture() {
+        let mut lecture = course(1, 2, 1);
+        lecture["targetSlot"] = json!(2);
+        let mut lab = course(2, 1, 2);
+        lab["targetSlot"] = json!(0);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [lecture, lab],
+            "totalTimeslots": 6
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&output.assignments, 1), start_of(&output.assignments, 2)), (2, 0));
 
-    for (instructor_id, mut instructor_assigns) in instructor_assignments {
-        instructor_assigns.sort_by_key(|a| a.start_slot);
+        value["sectionLinks"] = json!([{"lecture": 1, "lab": 2, "labAfterLecture": true}]);
+        let output = solve(&input(value.clone())).unwrap();
+        let lecture_start = start_of(&output.assignments, 1);
+        assert!(start_of(&output.assignments, 2) >= lecture_start + 2, "{:?}", output.assignments);
 
-        for i in 0..instructor_assigns.len().saturating_sub(1) {
-            let current = instructor_assigns[i];
-            let next = instructor_assigns[i + 1];
+        value["sectionLinks"] = json!([{"lecture": 1, "lab": 9, "labAfterLecture": true}]);
+        assert!(matches!(solve(&input(value)), Err(SolveError::InvalidInput(_))));
+    }
 
-            let current_course = course_map.get(&current.course_id).unwrap();
-            let current_end_slot = current.start_slot + current_course.duration_slots;
+    #[test]
+    fn an_idle_time_penalty_closes_a_mid_day_gap() {
+        let courses: Vec<_> = [(1, 0), (2, 5)]
+            .map(|(id, target)| {
+                let mut apart = course(id, 1, 1);
+                apart["targetSlot"] = json!(target);
+                apart["targetWeight"] = json!(0.1);
+                apart
+            })
+            .to_vec();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": courses,
+            "totalTimeslots": 6,
+            "periodsPerDay": 6,
+            "morningCutoff": 0,
+            "weights": {"backToBackPenalty": 0.0}
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&output.assignments, 1), start_of(&output.assignments, 2)), (0, 5));
+        assert!(output.instructor_idle_slots.is_empty());
 
-            if current_end_slot != next.start_slot {
-                score += 1; // reward for not back-to-back
-            } else {
-                score -= 1; // penalty for back-to-back
-                unmet.push(UnmetSoftConstraint {
-                    constraint_type: "Avoid Back-to-Back Classes".to_string(),
-                    description: format!(
-                        "Instructor {} has back-to-back classes: Course {} (ends at slot {}) and Course {} (starts at slot {}).",
-                        instructor_id,
-                        current.course_id,
-                        current_end_slot,
-                        next.course_id,
-                        next.start_slot
-                    ),
-                });
-            }
+        value["weights"]["idleTimeWeight"] = json!(1.0);
+        let output = solve(&input(value)).unwrap();
+        let (first, second) = (start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+        assert_eq!(second, first + 1);
+        let idle: Vec<_> =
+            output.instructor_idle_slots.iter().map(|i| (i.instructor_id, i.idle_slots)).collect();
+        assert_eq!(idle, [(InstructorId(1), 0)]);
+    }
+
+    #[test]
+    fn repairing_a_warm_start_moves_only_the_conflicting_course() {
+        // all three in the afternoon, with instructor 1 teaching two of them at once
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), course(3, 1, 2)],
+            "totalTimeslots": 4,
+            "warmStart": [
+                {"courseId": 1, "roomId": 1, "startSlot": 3},
+                {"courseId": 2, "roomId": 2, "startSlot": 3},
+                {"courseId": 3, "roomId": 1, "startSlot": 2}
+            ]
+        });
+        let warm_start: Vec<Assignment> =
+            serde_json::from_value(value["warmStart"].clone()).unwrap();
+        // the morning preference alone moves every course
+        assert_eq!(solve(&input(value.clone())).unwrap().warm_start_changes, Some(3));
+
+        value["repairWarmStart"] = json!(true);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.warm_start_changes, Some(1));
+        let kept = output.assignments.iter().filter(|a| warm_start.contains(a)).count();
+        assert_eq!(kept, 2);
+        assert!(output.assignments.contains(&warm_start[2]));
+    }
+
+    #[test]
+    fn a_fractional_mock_solution_is_reported_not_dropped() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 2
+        }));
+        let session = SolverSession::new(&input).unwrap();
+        // a degenerate solution that splits the course evenly over its two slots
+        let half = |_: Variable| 0.5;
+
+        let assignments = session.chosen_assignments(half, DEFAULT_SOLUTION_TOLERANCE);
+        assert!(assignments.is_empty());
+        let problem = session.fractional_choice(&assignments, half, DEFAULT_SOLUTION_TOLERANCE);
+        let problem = problem.expect("a missing course is reported");
+        assert!(problem.starts_with("Course 1 has no assignment variable"), "{}", problem);
+        assert!(problem.contains("largest 0.500"), "{}", problem);
+
+        // a tolerance loose enough to take both halves picks the course twice instead
+        let assignments = session.chosen_assignments(half, 0.6);
+        assert_eq!(assignments.len(), 2);
+        let problem = session.fractional_choice(&assignments, half, 0.6).unwrap();
+        assert!(problem.contains("has 2 assignment variables"), "{}", problem);
+    }
+
+    #[test]
+    fn avoiding_single_course_days_puts_two_lone_days_together() {
+        let courses: Vec<_> = [(1, 0), (2, 2)]
+            .map(|(id, target)| {
+                let mut apart = course(id, 1, 1);
+                apart["targetSlot"] = json!(target);
+                apart["targetWeight"] = json!(0.1);
+                apart
+            })
+            .to_vec();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": courses,
+            "totalTimeslots": 4,
+            "periodsPerDay": 2,
+            "morningCutoff": 0,
+            "weights": {"backToBackPenalty": 0.0}
+        });
+        let single_days = |output: &SchedulingOutput| {
+            let unmet = output.unmet_soft_constraints.iter();
+            unmet.filter(|u| u.constraint_type == "Single-Course Day").count()
+        };
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!((start_of(&output.assignments, 1), start_of(&output.assignments, 2)), (0, 2));
+        assert_eq!(single_days(&output), 0);
+
+        value["instructors"][0]["avoidSingleCourseDays"] = json!(true);
+        let output = solve(&input(value.clone())).unwrap();
+        let (first, second) = (start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+        assert_eq!(first / 2, second / 2);
+        assert_eq!(single_days(&output), 0);
+
+        // without a penalty the lone days stay and are reported
+        value["weights"]["singleCourseDay"] = json!(0.0);
+        assert_eq!(single_days(&solve(&input(value)).unwrap()), 2);
+    }
+
+    #[test]
+    fn exam_spread_puts_one_exam_a_day_hardest_first() {
+        let exams: Vec<_> = [(1, 1), (2, 5), (3, 3)]
+            .map(|(id, difficulty)| {
+                let mut exam = course(id, 1, id);
+                exam["difficulty"] = json!(difficulty);
+                exam
+            })
+            .to_vec();
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "courses": exams,
+            "totalTimeslots": 6,
+            "periodsPerDay": 2,
+            "studentGroups": [{"id": 1, "courseIds": [1, 2, 3]}],
+            "objectiveMode": "examSpread"
+        }));
+        let output = solve(&input).unwrap();
+        let day = |course_id| input.day_of(start_of(&output.assignments, course_id));
+        assert_eq!((day(2), day(3), day(1)), (0, 1, 2));
+    }
+
+    #[test]
+    fn a_cohort_room_change_penalty_keeps_the_group_in_one_room() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1, "homeRoom": 1}, {"id": 2, "homeRoom": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 2)],
+            "totalTimeslots": 4,
+            "studentGroups": [{"id": 1, "courseIds": [1, 2]}]
+        });
+        let rooms = |output: &SchedulingOutput| {
+            output.assignments.iter().map(|a| a.room_id).collect::<HashSet<_>>().len()
+        };
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(rooms(&output), 2);
+        assert!(output.cohort_room_changes.is_empty());
+
+        value["weights"] = json!({"cohortRoomChangesWeight": 5.0});
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(rooms(&output), 1);
+        let changes: Vec<_> = output
+            .cohort_room_changes
+            .iter()
+            .map(|c| (c.student_group_id.0, c.day, c.room_changes))
+            .collect();
+        assert_eq!(changes, [(1, 0, 0)]);
+    }
+
+    #[test]
+    fn an_earliest_start_pushes_the_course_later_every_day() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 2, 1)],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4
+        });
+        assert_eq!(start_of(&solve(&input(value.clone())).unwrap().assignments, 1), 0);
+
+        value["instructors"][0]["earliestStart"] = json!(2);
+        let starts: Vec<_> =
+            possible_assignments(&input(value.clone())).iter().map(|(_, _, k)| *k).collect();
+        assert_eq!(starts, [2, 6]);
+        let start = start_of(&solve(&input(value.clone())).unwrap().assignments, 1);
+        assert_eq!(start % 4, 2);
+
+        // a 2-slot course can't both start at period 2 and end by period 3
+        value["instructors"][0]["latestEnd"] = json!(3);
+        assert!(matches!(solve(&input(value)), Err(SolveError::Infeasible(_))));
+    }
+
+    #[test]
+    fn binding_constraints_name_the_fully_booked_room_slots() {
+        let mut full = course(1, 2, 1);
+        full["requiredCapacity"] = json!(30);
+        let mut small = course(2, 1, 2);
+        for (pinned, slot) in [(&mut full, 0), (&mut small, 3)] {
+            pinned["targetSlot"] = json!(slot);
+            pinned["targetWeight"] = json!(10.0);
         }
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [full, small],
+            "totalTimeslots": 4
+        });
+        assert!(solve(&input(value.clone())).unwrap().binding_constraints.is_empty());
+
+        value["includeBinding"] = json!(true);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(
+            output.binding_constraints,
+            [
+                "noRoomOverlap: room 1 is booked in slot(s) 0, 1, 3",
+                "noInstructorOverlap: instructor 1 teaches in slot(s) 0, 1",
+                "noInstructorOverlap: instructor 2 teaches in slot(s) 3",
+                "roomCapacity: course 1 fills all 30 seat(s) of room 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn minimising_instructors_consolidates_courses_onto_fewer_people() {
+        // both courses want slot 0, which only two different instructors can give them
+        let courses: Vec<_> = [(1, vec![1]), (2, vec![1, 2])]
+            .into_iter()
+            .map(|(id, eligible)| {
+                let mut flexible = course(id, 1, 1);
+                flexible["instructorIds"] = json!([]);
+                flexible["eligibleInstructors"] = json!(eligible);
+                flexible["targetSlot"] = json!(0);
+                flexible["targetWeight"] = json!(0.1);
+                flexible
+            })
+            .collect();
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": courses,
+            "totalTimeslots": 4,
+            "morningCutoff": 0,
+            "weights": {"backToBackPenalty": 0.0}
+        });
+        let instructors = |output: &SchedulingOutput| {
+            output.assignments.iter().map(|a| a.instructor_id.unwrap()).collect::<BTreeSet<_>>()
+        };
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(instructors(&output).len(), 2);
+        assert!(output.instructors_used.is_empty());
+
+        value["weights"]["minimizeInstructorsWeight"] = json!(1.0);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(instructors(&output), BTreeSet::from([InstructorId(1)]));
+        assert_eq!(output.instructors_used, [InstructorId(1)]);
+        assert_ne!(start_of(&output.assignments, 1), start_of(&output.assignments, 2));
+    }
+
+    #[test]
+    fn a_fixed_room_leaves_the_slot_to_the_solver() {
+        let mut pinned = course(1, 1, 1);
+        pinned["fixedRoom"] = json!(2);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1, "homeRoom": 1, "unavailableSlots": [0]}],
+            "courses": [pinned],
+            "totalTimeslots": 4
+        });
+        let candidates = possible_assignments(&input(value.clone()));
+        assert!(candidates.iter().all(|(_, r_id, _)| *r_id == RoomId(2)));
+        assert_eq!(candidates.len(), 3);
+        let placed = |value: &serde_json::Value| {
+            let output = solve(&input(value.clone())).unwrap();
+            (output.assignments[0].room_id, output.assignments[0].start_slot)
+        };
+        // the home room is not allowed, but the morning still is
+        assert_eq!(placed(&value), (RoomId(2), 1));
+
+        // and the other way round
+        value["courses"][0]["fixedRoom"] = json!(null);
+        value["courses"][0]["fixedSlot"] = json!(3);
+        assert_eq!(placed(&value), (RoomId(1), 3));
+    }
+
+    #[test]
+    fn soft_constraint_percentages_match_a_hand_count() {
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "preferredDays": [0]}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), course(3, 1, 1), course(4, 1, 1)],
+            "totalTimeslots": 8,
+            "periodsPerDay": 4
+        }));
+        // three morning starts out of four, and one of four on the preferred day 0
+        let assignments: Vec<Assignment> = serde_json::from_value(json!([
+            {"courseId": 1, "roomId": 1, "startSlot": 0},
+            {"courseId": 2, "roomId": 1, "startSlot": 4},
+            {"courseId": 3, "roomId": 1, "startSlot": 5},
+            {"courseId": 4, "roomId": 1, "startSlot": 6}
+        ]))
+        .unwrap();
+        let course_map: HashMap<CourseId, &Course> =
+            input.courses.iter().map(|c| (c.id, c)).collect();
+        let (_, _, summary) =
+            calculate_score_and_unmet_constraints(&assignments, &input, &course_map);
+        let entry = |constraint_type: &str| {
+            let entry = summary.iter().find(|s| s.constraint_type == constraint_type).unwrap();
+            (entry.met, entry.total, entry.percentage)
+        };
+        assert_eq!(entry("Prefer Mornings"), (3, 4, 75.0));
+        assert_eq!(entry("Preferred Teaching Days"), (1, 4, 25.0));
+    }
+
+    #[test]
+    fn a_relaxed_capacity_reports_the_over_capacity_placement() {
+        let mut large = course(1, 1, 1);
+        large["requiredCapacity"] = json!(25);
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 20}],
+            "instructors": [{"id": 1}],
+            "courses": [large],
+            "totalTimeslots": 2
+        });
+        assert!(matches!(solve(&input(value.clone())), Err(SolveError::Infeasible(_))));
+
+        value["relaxations"] = json!({"capacity": 2.0});
+        let output = solve(&input(value)).unwrap();
+        assert_eq!(output.assignments[0].room_id, RoomId(1));
+        assert_eq!(
+            output.constraint_violations,
+            ["roomCapacity: course 1 needs 25 seat(s) but room 1 has 20"]
+        );
+        // the morning reward of 1 less the penalty of 2
+        assert_eq!(output.objective_value, Some(-1.0));
+    }
+
+    #[test]
+    fn a_back_to_back_cap_breaks_a_pair_at_a_morning_cost() {
+        // a morning pair is worth 2 - 0.5 for either instructor, a broken one only 1
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}, {"id": 2, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 1), course(3, 1, 2), course(4, 1, 2)],
+            "totalTimeslots": 6,
+            "morningCutoff": 2,
+            "weights": {"backToBackPenalty": 0.5}
+        });
+        let pairs = |output: &SchedulingOutput| {
+            let starts = |first, second| {
+                start_of(&output.assignments, first).abs_diff(start_of(&output.assignments, second))
+            };
+            [starts(1, 2), starts(3, 4)].iter().filter(|gap| **gap == 1).count()
+        };
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!((pairs(&output), output.objective_value), (2, Some(3.0)));
+
+        value["maxBackToBackPairs"] = json!(1);
+        let output = solve(&input(value)).unwrap();
+        assert_eq!((pairs(&output), output.objective_value), (1, Some(2.5)));
+    }
+
+    #[test]
+    fn an_exam_stays_out_of_class_only_slots() {
+        let mut exam = course(1, 1, 1);
+        exam["isExam"] = json!(true);
+        let input = input(json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [exam, course(2, 1, 2)],
+            "totalTimeslots": 4,
+            "slotModes": [[0, "classOnly"], [1, "classOnly"], [3, "examOnly"]]
+        }));
+        let starts = |course_id| {
+            possible_assignments(&input)
+                .into_iter()
+                .filter(|(c_id, _, _)| *c_id == CourseId(course_id))
+                .map(|(_, _, k)| k)
+                .collect::<Vec<_>>()
+        };
+        // slot 2 is not listed, so it takes either
+        assert_eq!(starts(1), [2, 3]);
+        assert_eq!(starts(2), [0, 1, 2]);
+        let output = solve(&input).unwrap();
+        assert!(start_of(&output.assignments, 1) >= 2);
+    }
+
+    #[test]
+    fn a_long_course_wins_the_morning_over_a_short_one() {
+        let mut short = course(2, 1, 2);
+        short["targetSlot"] = json!(0);
+        short["targetWeight"] = json!(0.1);
+        // one room and a single morning slot, which the short course's target tips its way
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 2, 1), short],
+            "totalTimeslots": 4,
+            "morningCutoff": 1
+        });
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(start_of(&output.assignments, 2), 0);
+
+        value["weights"] = json!({"longCourseMorningWeight": 1.0});
+        let output = solve(&input(value.clone())).unwrap();
+        assert_eq!(start_of(&output.assignments, 1), 0);
+        let long_unmet = |output: &SchedulingOutput| {
+            let unmet = output.unmet_soft_constraints.iter();
+            unmet.filter(|u| u.constraint_type == "Long Courses in the Morning").count()
+        };
+        assert_eq!(long_unmet(&output), 0);
+
+        // kept out of the morning, the long course is reported
+        value["instructors"][0]["unavailableSlots"] = json!([0]);
+        assert_eq!(long_unmet(&solve(&input(value)).unwrap()), 1);
     }
 
-    (score, unmet)
+    #[test]
+    fn a_daily_slot_cap_splits_two_courses_across_days() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "maxSlotsPerDay": 5}],
+            "courses": [course(1, 4, 1), course(2, 2, 1)],
+            "totalTimeslots": 12,
+            "periodsPerDay": 6
+        });
+        let a = solve(&input(value.clone())).unwrap().assignments;
+        assert_ne!(start_of(&a, 1) / 6, start_of(&a, 2) / 6);
+
+        // with the second day closed, six slots fit on the first only without the cap
+        value["instructors"][0]["unavailableSlots"] = json!([6, 7, 8, 9, 10, 11]);
+        assert!(solve(&input(value.clone())).is_err());
+        value["instructors"][0]["maxSlotsPerDay"] = json!(null);
+        let a = solve(&input(value)).unwrap().assignments;
+        assert_eq!(start_of(&a, 1) / 6, start_of(&a, 2) / 6);
+    }
+
+    #[test]
+    fn an_unmet_preference_names_the_maintenance_window_behind_it() {
+        let unmet = |value: &serde_json::Value, constraint_type: &str| {
+            let output = solve(&input(value.clone())).unwrap();
+            let mut unmet = output.unmet_soft_constraints.into_iter();
+            unmet.find(|u| u.constraint_type == constraint_type).unwrap().description
+        };
+        // the window closes the whole morning of the only day
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}],
+            "courses": [course(1, 1, 1)],
+            "totalTimeslots": 4,
+            "periodsPerDay": 4,
+            "maintenanceWindows": [{"slots": [0, 1], "reason": "Boiler replacement"}]
+        });
+        let description = unmet(&value, "Prefer Mornings");
+        assert!(description.contains("closed for maintenance: Boiler replacement"));
+
+        // and here the whole of the instructor's preferred day
+        value["periodsPerDay"] = json!(2);
+        value["instructors"][0]["preferredDays"] = json!([0]);
+        let description = unmet(&value, "Preferred Teaching Days");
+        assert!(description.contains("closed for maintenance: Boiler replacement"));
+    }
+
+    #[test]
+    fn a_relaxed_room_overlap_survives_the_isolation_term() {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1}, {"id": 2}],
+            "courses": [course(1, 1, 1), course(2, 1, 2)],
+            "totalTimeslots": 1,
+            "relaxations": {"roomOverlap": 5.0}
+        });
+        assert_eq!(solve(&input(value.clone())).unwrap().assignments.len(), 2);
+
+        // two courses in the one room must not hit a one-course-per-slot bound
+        value["weights"] = json!({"isolationWeight": 1.0});
+        assert_eq!(solve(&input(value.clone())).unwrap().assignments.len(), 2);
+        value["minCoursesPerActiveSlot"] = json!(2);
+        assert_eq!(solve(&input(value)).unwrap().assignments.len(), 2);
+    }
 }
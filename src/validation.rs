@@ -0,0 +1,879 @@
+use crate::data::{Assignment, CourseId, ObjectiveMode, SchedulingInput, ValidationReport};
+use jiff::tz::TimeZone;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Largest magnitude accepted for any weight. Beyond this the objective terms differ by so
+/// many orders of magnitude that HiGHS's tolerances swallow the smaller ones.
+const MAX_WEIGHT: f64 = 1e6;
+
+/// Checks an input for structural errors and degenerate settings without building a model.
+pub fn validate_input(input: &SchedulingInput) -> ValidationReport {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    // every check sees course demands as the sections they become
+    let expanded = match input.with_sections_expanded() {
+        Ok(expanded) => expanded,
+        Err(error) => {
+            return ValidationReport {
+                valid: false,
+                errors: vec![error],
+                warnings,
+            };
+        }
+    };
+    let input = &expanded;
+
+    // later entries would silently replace earlier ones in every id lookup
+    for id in duplicates(input.courses.iter().map(|c| c.id)) {
+        errors.push(format!("Course id {} is used more than once.", id));
+    }
+    for id in duplicates(input.rooms.iter().map(|r| r.id)) {
+        errors.push(format!("Room id {} is used more than once.", id));
+    }
+    for id in duplicates(input.instructors.iter().map(|i| i.id)) {
+        errors.push(format!("Instructor id {} is used more than once.", id));
+    }
+    // a template id names its sections in the output, so no course may have it
+    let templates: BTreeSet<CourseId> = input.courses.iter().filter_map(|c| c.section_of).collect();
+    for template_id in templates {
+        if input.courses.iter().any(|c| c.id == template_id && c.section_of.is_none()) {
+            errors.push(format!(
+                "Course demand {} has the id of a course; give its template an unused id.",
+                template_id
+            ));
+        }
+    }
+
+    if let Some(periods) = input.periods_per_day
+        && (periods == 0 || !input.total_timeslots.is_multiple_of(periods))
+    {
+        errors.push(format!(
+            "totalTimeslots ({}) must be a positive multiple of periodsPerDay ({}).",
+            input.total_timeslots, periods
+        ));
+    } else {
+        // day-based checks only make sense once the day length is valid
+        let num_days = input.total_timeslots / input.slots_per_day();
+        if let Some(weeks) = input.weeks
+            && (weeks == 0 || !num_days.is_multiple_of(weeks))
+        {
+            errors.push(format!(
+                "The {} day(s) of totalTimeslots ({}) must split into a positive number of equal weeks, not {}.",
+                num_days, input.total_timeslots, weeks
+            ));
+        }
+        let days_per_week = input.days_per_week();
+        for room in &input.rooms {
+            for (day, period) in &room.recurring_unavailable {
+                if *day >= days_per_week || *period >= input.slots_per_day() {
+                    errors.push(format!(
+                        "Room {} is blocked on day {} period {}, outside the {} day(s) of {} period(s) in a week.",
+                        room.id,
+                        day,
+                        period,
+                        days_per_week,
+                        input.slots_per_day()
+                    ));
+                }
+            }
+        }
+        for instructor in &input.instructors {
+            if let Some(day) = instructor.preferred_days.iter().find(|d| **d >= days_per_week) {
+                warnings.push(format!(
+                    "Instructor {} prefers day {}, but a week only has {} day(s).",
+                    instructor.id, day, days_per_week
+                ));
+            }
+            let earliest = instructor.earliest_start.unwrap_or(0);
+            let latest = instructor.latest_end.unwrap_or(input.slots_per_day());
+            if earliest >= latest.min(input.slots_per_day()) {
+                errors.push(format!(
+                    "Instructor {} may only teach from period {} until period {} of a {}-period day, which leaves no period.",
+                    instructor.id,
+                    earliest,
+                    latest,
+                    input.slots_per_day()
+                ));
+            }
+        }
+
+        for course in &input.courses {
+            if let Some(day) = course.required_day
+                && day >= num_days
+            {
+                errors.push(format!(
+                    "Course {} is required on day {}, but there are only {} day(s).",
+                    course.id, day, num_days
+                ));
+            }
+            if let (Some(day), Some(slot)) = (course.required_day, course.fixed_slot)
+                && input.day_of(slot) != day
+            {
+                errors.push(format!(
+                    "Course {} is fixed to slot {}, which is not on its required day {}.",
+                    course.id, slot, day
+                ));
+            }
+        }
+
+        if let Some(day_weights) = &input.day_weights
+            && day_weights.len() != num_days as usize
+        {
+            errors.push(format!(
+                "dayWeights has {} entries but there are {} day(s).",
+                day_weights.len(),
+                num_days
+            ));
+        }
+        for (day, _, _) in &input.day_soft_caps {
+            if *day >= num_days {
+                errors.push(format!(
+                    "daySoftCaps caps day {}, but there are only {} day(s).",
+                    day, num_days
+                ));
+            }
+        }
+
+        let cutoff = input.morning_cutoff();
+        if cutoff == 0 || cutoff >= input.slots_per_day() {
+            warnings.push(format!(
+                "The morning cutoff is period {} of a {}-period day, so {} slot counts as morning and the morning preference has no effect.",
+                cutoff,
+                input.slots_per_day(),
+                if cutoff == 0 { "no" } else { "every" }
+            ));
+        }
+    }
+
+    match input.slot_minutes {
+        Some(0) => errors.push("slotMinutes must be positive.".to_string()),
+        Some(slot_minutes) => {
+            for course in &input.courses {
+                if let Some(minutes) = course.duration_minutes
+                    && !minutes.is_multiple_of(slot_minutes)
+                {
+                    warnings.push(format!(
+                        "Course {} lasts {} minutes, which is not a whole number of {}-minute slots; it is rounded up to {} slots.",
+                        course.id,
+                        minutes,
+                        slot_minutes,
+                        minutes.div_ceil(slot_minutes)
+                    ));
+                }
+            }
+        }
+        None => {
+            for course in input.courses.iter().filter(|c| c.duration_minutes.is_some()) {
+                errors.push(format!(
+                    "Course {} gives durationMinutes but slotMinutes is not set.",
+                    course.id
+                ));
+            }
+        }
+    }
+    for course in &input.courses {
+        if course.duration_minutes.unwrap_or(course.duration_slots) == 0 {
+            errors.push(format!(
+                "Course {} has no duration; set durationSlots or durationMinutes.",
+                course.id
+            ));
+        }
+    }
+    // rejects absurd durations before any slot arithmetic can overflow
+    for course in &input.with_durations_in_slots().courses {
+        if course.duration_slots > input.total_timeslots {
+            errors.push(format!(
+                "Course {} lasts {} slots, longer than the {} available timeslots.",
+                course.id, course.duration_slots, input.total_timeslots
+            ));
+        }
+    }
+
+    for course in &input.courses {
+        match &course.eligible_instructors {
+            Some(eligible) if eligible.is_empty() => {
+                errors.push(format!("Course {} has an empty eligibleInstructors list.", course.id));
+            }
+            Some(eligible) => {
+                for instructor_id in eligible {
+                    if !input.instructors.iter().any(|i| i.id == *instructor_id) {
+                        errors.push(format!(
+                            "Course {} lists unknown eligible instructor {}.",
+                            course.id, instructor_id
+                        ));
+                    }
+                }
+                if course.instructor_id.is_some() || !course.instructor_ids.is_empty() {
+                    warnings.push(format!(
+                        "Course {} sets both eligibleInstructors and fixed instructors; the fixed instructors are ignored.",
+                        course.id
+                    ));
+                }
+            }
+            None if course.instructors().is_empty() => {
+                errors.push(format!(
+                    "Course {} has no instructor; set instructorIds.",
+                    course.id
+                ));
+            }
+            None => {}
+        }
+        if let Some(room_id) = course.fixed_room {
+            match input.rooms.iter().find(|r| r.id == room_id) {
+                None => errors.push(format!(
+                    "Course {} is fixed to unknown room {}.",
+                    course.id, room_id
+                )),
+                Some(room)
+                    if room.capacity < course.required_capacity
+                        && input.relaxations.capacity.is_none() =>
+                {
+                    errors.push(format!(
+                        "Course {} needs {} seats but is fixed to room {}, which has {}.",
+                        course.id, course.required_capacity, room_id, room.capacity
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(slot) = course.fixed_slot
+            && slot >= input.total_timeslots
+        {
+            errors.push(format!(
+                "Course {} is fixed to slot {}, beyond the {} timeslot(s).",
+                course.id, slot, input.total_timeslots
+            ));
+        }
+    }
+    // a relaxed room overlap lets a room hold several courses at once
+    let rooms_bound_concurrency = input.relaxations.room_overlap.is_none();
+    if let Some(min_courses) = input.min_courses_per_active_slot
+        && rooms_bound_concurrency
+        && min_courses as usize > input.rooms.len()
+    {
+        errors.push(format!(
+            "minCoursesPerActiveSlot ({}) exceeds the number of rooms ({}), so no slot could be used.",
+            min_courses,
+            input.rooms.len()
+        ));
+    }
+    if let Some(min_courses) = input.min_courses_per_active_zone
+        && rooms_bound_concurrency
+    {
+        let mut zone_sizes: BTreeMap<u32, usize> = BTreeMap::new();
+        for zone in input.rooms.iter().filter_map(|r| r.zone) {
+            *zone_sizes.entry(zone).or_insert(0) += 1;
+        }
+        for (zone, size) in zone_sizes {
+            if min_courses as usize > size {
+                warnings.push(format!(
+                    "Zone {} has {} room(s), fewer than minCoursesPerActiveZone ({}), so none of its rooms can be used.",
+                    zone, size, min_courses
+                ));
+            }
+        }
+    }
+    // every mandatory course needs a room for its whole duration; blocks and capacities are
+    // left out, so this only catches inputs that could never fit
+    let demand: u64 = input
+        .with_durations_in_slots()
+        .courses
+        .iter()
+        .filter(|c| !c.optional && !c.is_cancelled())
+        .map(|c| c.duration_slots as u64)
+        .sum();
+    let supply = input.rooms.len() as u64 * input.total_timeslots as u64;
+    if demand > supply {
+        let message = format!(
+            "The mandatory courses need {} room-slot(s), but {} room(s) over {} timeslot(s) only offer {}.",
+            demand,
+            input.rooms.len(),
+            input.total_timeslots,
+            supply
+        );
+        if input.reject_oversubscribed {
+            errors.push(message);
+        } else {
+            warnings.push(message);
+        }
+    }
+    if input.max_concurrent_courses == Some(0) && !input.courses.is_empty() {
+        errors.push("maxConcurrentCourses is 0, so no course could be scheduled.".to_string());
+    }
+    for window in &input.maintenance_windows {
+        if let Some(slot) = window.slots.iter().find(|k| **k >= input.total_timeslots) {
+            errors.push(format!(
+                "Maintenance window \"{}\" closes slot {}, but there are only {} timeslot(s).",
+                window.reason, slot, input.total_timeslots
+            ));
+        }
+    }
+    for (slot, _) in input.max_courses_in_slot.iter().flatten() {
+        if *slot >= input.total_timeslots {
+            errors.push(format!(
+                "maxCoursesInSlot caps slot {}, but there are only {} timeslot(s).",
+                slot, input.total_timeslots
+            ));
+        }
+    }
+    for (index, group) in input.distinct_instructor_groups.iter().enumerate() {
+        for course_id in group.iter().filter(|c_id| !input.courses.iter().any(|c| c.id == **c_id)) {
+            errors.push(format!(
+                "Distinct instructor group {} refers to unknown course {}.",
+                index, course_id
+            ));
+        }
+        let courses: Vec<_> = input.courses.iter().filter(|c| group.contains(&c.id)).collect();
+        // fixed instructors cannot be moved apart by the solver
+        for (position, first) in courses.iter().enumerate() {
+            for second in &courses[position + 1..] {
+                if let Some(shared) =
+                    first.instructors().into_iter().find(|i| second.instructors().contains(i))
+                {
+                    errors.push(format!(
+                        "Courses {} and {} of distinct instructor group {} are both taught by instructor {}.",
+                        first.id, second.id, index, shared
+                    ));
+                }
+            }
+        }
+    }
+    for (first_id, second_id) in &input.soft_adjacency {
+        for course_id in [first_id, second_id] {
+            if !input.courses.iter().any(|c| c.id == *course_id) {
+                errors.push(format!(
+                    "Soft adjacency ({}, {}) refers to unknown course {}.",
+                    first_id, second_id, course_id
+                ));
+            }
+        }
+    }
+    for link in &input.section_links {
+        for course_id in [link.lecture, link.lab] {
+            if !input.courses.iter().any(|c| c.id == course_id) {
+                errors.push(format!(
+                    "Section link of lecture {} and lab {} refers to unknown course {}.",
+                    link.lecture, link.lab, course_id
+                ));
+            }
+        }
+        if link.lecture == link.lab {
+            errors.push(format!("Course {} is linked to itself as its own lab.", link.lecture));
+        } else if !link.same_week && !link.lab_after_lecture {
+            warnings.push(format!(
+                "Section link of lecture {} and lab {} has neither sameWeek nor labAfterLecture, so the lab only needs its lecture to be scheduled.",
+                link.lecture, link.lab
+            ));
+        }
+    }
+    let resolved = input.with_durations_in_slots();
+    // the end of a placement, if it stays within the horizon
+    let placement_end = |entry: &Assignment| {
+        let course = resolved.courses.iter().find(|c| c.id == entry.course_id)?;
+        let end_slot = entry.start_slot.checked_add(course.duration_slots)?;
+        (end_slot <= input.total_timeslots).then_some(end_slot)
+    };
+    for id in duplicates(input.warm_start.iter().map(|a| a.course_id)) {
+        errors.push(format!("warmStart places course {} more than once.", id));
+    }
+    for entry in &input.warm_start {
+        if !input.courses.iter().any(|c| c.id == entry.course_id) {
+            warnings.push(format!(
+                "warmStart places unknown course {}; it is ignored.",
+                entry.course_id
+            ));
+        } else if placement_end(entry).is_none() {
+            warnings.push(format!(
+                "warmStart places course {} at slot {}, so it runs past the {} timeslot(s); it is ignored.",
+                entry.course_id, entry.start_slot, input.total_timeslots
+            ));
+        }
+    }
+    for id in duplicates(input.frozen_assignments.iter().map(|a| a.course_id)) {
+        errors.push(format!("frozenAssignments places course {} more than once.", id));
+    }
+    for entry in &input.frozen_assignments {
+        if !input.courses.iter().any(|c| c.id == entry.course_id) {
+            errors.push(format!(
+                "frozenAssignments places unknown course {}.",
+                entry.course_id
+            ));
+        } else if placement_end(entry).is_none() {
+            errors.push(format!(
+                "frozenAssignments places course {} at slot {}, so it runs past the {} timeslot(s).",
+                entry.course_id, entry.start_slot, input.total_timeslots
+            ));
+        }
+    }
+    if let Some(slot) = input.frozen_slots.iter().find(|k| **k >= input.total_timeslots) {
+        warnings.push(format!(
+            "frozenSlots lists slot {}, beyond the {} timeslot(s).",
+            slot, input.total_timeslots
+        ));
+    }
+    for slot in duplicates(input.slot_modes.iter().map(|(slot, _)| *slot)) {
+        errors.push(format!("slotModes lists slot {} more than once.", slot));
+    }
+    if let Some((slot, _)) = input.slot_modes.iter().find(|(k, _)| *k >= input.total_timeslots) {
+        warnings.push(format!(
+            "slotModes lists slot {}, beyond the {} timeslot(s).",
+            slot, input.total_timeslots
+        ));
+    }
+    if !input.frozen_assignments.is_empty() && input.frozen_slots.is_empty() {
+        warnings.push(
+            "frozenAssignments are given without frozenSlots, so none of them is fixed."
+                .to_string(),
+        );
+    }
+    if input.objective_mode != ObjectiveMode::ExamSpread
+        && let Some(course) = input.courses.iter().find(|c| c.difficulty.is_some())
+    {
+        warnings.push(format!(
+            "Course {} has a difficulty, which only objectiveMode examSpread uses.",
+            course.id
+        ));
+    }
+    if input.objective_mode == ObjectiveMode::ExamSpread && input.student_groups.is_empty() {
+        warnings.push(
+            "objectiveMode examSpread spreads the exams of student groups, but there are none."
+                .to_string(),
+        );
+    }
+    if input.repair_warm_start && input.warm_start.is_empty() {
+        warnings.push("repairWarmStart is set without a warmStart to repair.".to_string());
+    }
+    for (first_id, second_id, distance) in &input.room_distances {
+        for room_id in [first_id, second_id] {
+            if !input.rooms.iter().any(|r| r.id == *room_id) {
+                errors.push(format!(
+                    "Room distance ({}, {}) refers to unknown room {}.",
+                    first_id, second_id, room_id
+                ));
+            }
+        }
+        if !(distance.is_finite() && *distance >= 0.0) {
+            errors.push(format!(
+                "Room distance ({}, {}) must be a non-negative number, got {}.",
+                first_id, second_id, distance
+            ));
+        }
+    }
+    for (index, room_ids) in input.combinable.iter().enumerate() {
+        for room_id in room_ids.iter().filter(|r_id| !input.rooms.iter().any(|r| r.id == **r_id)) {
+            errors.push(format!(
+                "Combinable room set {} refers to unknown room {}.",
+                index, room_id
+            ));
+        }
+        for room_id in duplicates(room_ids.iter()) {
+            errors.push(format!("Combinable room set {} lists room {} twice.", index, room_id));
+        }
+        if room_ids.len() < 2 {
+            warnings.push(format!(
+                "Combinable room set {} has fewer than two rooms and combines nothing.",
+                index
+            ));
+        }
+        if input.combined_room_id(index).is_none() {
+            errors.push(format!(
+                "Combinable room set {} needs a room id above every room id, but none is left.",
+                index
+            ));
+        }
+    }
+    // overflow is only bounded from below, so a reward would be unbounded
+    for (day, _, weight) in &input.day_soft_caps {
+        if *weight < 0.0 {
+            errors.push(format!(
+                "The daySoftCaps weight of day {} must not be negative, got {}.",
+                day, weight
+            ));
+        }
+    }
+    // the model only bounds each pair of rooms from below, so a reward would be unbounded
+    if let Some(weights) = &input.weights
+        && weights.room_clustering_weight < 0.0
+    {
+        errors.push(format!(
+            "weights.roomClusteringWeight must not be negative, got {}.",
+            weights.room_clustering_weight
+        ));
+    }
+    // likewise idle slots are only bounded from below
+    if let Some(weights) = &input.weights
+        && weights.idle_time_weight < 0.0
+    {
+        errors.push(format!(
+            "weights.idleTimeWeight must not be negative, got {}.",
+            weights.idle_time_weight
+        ));
+    }
+    if let Some(weights) = &input.weights
+        && weights.cohort_room_changes_weight < 0.0
+    {
+        errors.push(format!(
+            "weights.cohortRoomChangesWeight must not be negative, got {}.",
+            weights.cohort_room_changes_weight
+        ));
+    }
+    // a negative penalty would reward breaking the constraint
+    let relaxations = [
+        ("roomOverlap", input.relaxations.room_overlap),
+        ("instructorOverlap", input.relaxations.instructor_overlap),
+        ("capacity", input.relaxations.capacity),
+    ];
+    for (name, penalty) in relaxations {
+        if let Some(penalty) = penalty.filter(|p| *p < 0.0) {
+            errors.push(format!(
+                "relaxations.{} must not be negative, got {}.",
+                name, penalty
+            ));
+        }
+    }
+    if let Some(weights) = &input.weights
+        && weights.minimize_instructors_weight < 0.0
+    {
+        errors.push(format!(
+            "weights.minimizeInstructorsWeight must not be negative, got {}.",
+            weights.minimize_instructors_weight
+        ));
+    }
+    if let Some(tolerance) = input.solution_tolerance
+        && !(tolerance > 0.0 && tolerance < 1.0)
+    {
+        errors.push(format!("solutionTolerance must be between 0 and 1, got {}.", tolerance));
+    }
+    if let Some(seconds) = input.time_limit_seconds
+        && !(seconds.is_finite() && seconds > 0.0)
+    {
+        errors.push(format!("timeLimitSeconds must be positive, got {}.", seconds));
+    }
+    if let Some(min_objective) = input.min_objective
+        && !min_objective.is_finite()
+    {
+        errors.push(format!("minObjective must be finite, got {}.", min_objective));
+    }
+    for instructor in &input.instructors {
+        if let Some(home_room) = instructor.home_room
+            && !input.rooms.iter().any(|r| r.id == home_room)
+        {
+            errors.push(format!(
+                "Instructor {} has unknown home room {}.",
+                instructor.id, home_room
+            ));
+        }
+        if let Some(max_span) = instructor.max_daily_span
+            && let Some(course) = resolved.courses.iter().find(|c| {
+                !c.optional
+                    && c.instructors().contains(&instructor.id)
+                    && c.duration_slots > max_span
+            })
+        {
+            errors.push(format!(
+                "Instructor {} has a maxDailySpan of {} slot(s), but course {} lasts {}.",
+                instructor.id, max_span, course.id, course.duration_slots
+            ));
+        }
+        if let Some(max_slots) = instructor.max_slots_per_day
+            && let Some(course) = resolved.courses.iter().find(|c| {
+                !c.optional
+                    && c.instructors().contains(&instructor.id)
+                    && c.duration_slots > max_slots
+            })
+        {
+            errors.push(format!(
+                "Instructor {} has a maxSlotsPerDay of {} slot(s), but course {} lasts {}.",
+                instructor.id, max_slots, course.id, course.duration_slots
+            ));
+        }
+    }
+    let mut calendar_slots = BTreeMap::new();
+    for entry in &input.slot_calendar {
+        if entry.slot >= input.total_timeslots {
+            errors.push(format!(
+                "slotCalendar dates slot {}, but there are only {} timeslot(s).",
+                entry.slot, input.total_timeslots
+            ));
+        }
+        if calendar_slots.insert(entry.slot, entry.start).is_some() {
+            errors.push(format!("slotCalendar dates slot {} more than once.", entry.slot));
+        }
+    }
+    if let Some(name) = &input.time_zone
+        && TimeZone::get(name).is_err()
+    {
+        errors.push(format!("timeZone {:?} is not a known IANA time zone.", name));
+    }
+    let date_ranges = input
+        .rooms
+        .iter()
+        .flat_map(|r| {
+            r.unavailable_dates.iter().map(move |range| (format!("Room {}", r.id), range))
+        })
+        .chain(input.instructors.iter().flat_map(|i| {
+            i.unavailable_dates.iter().map(move |range| (format!("Instructor {}", i.id), range))
+        }));
+    for (owner, range) in date_ranges {
+        if range.end <= range.start {
+            errors.push(format!(
+                "{} has an unavailable date range from {} to {} that does not end after it starts.",
+                owner, range.start, range.end
+            ));
+        }
+        if input.slot_calendar.is_empty() {
+            errors.push(format!("{} gives unavailableDates but slotCalendar is not set.", owner));
+        }
+    }
+    for room in &input.rooms {
+        if let Some(slot) = room.unavailable_slots.iter().find(|k| **k >= input.total_timeslots) {
+            errors.push(format!(
+                "Room {} is unavailable in slot {}, but there are only {} timeslot(s).",
+                room.id, slot, input.total_timeslots
+            ));
+        }
+    }
+
+    let mut weights: Vec<(String, f64)> = Vec::new();
+    if let Some(global) = &input.weights {
+        weights.extend([
+            ("weights.morningPreference".to_string(), global.morning_preference),
+            ("weights.backToBackPenalty".to_string(), global.back_to_back_penalty),
+            ("weights.preferredDay".to_string(), global.preferred_day),
+            ("weights.departmentCompactness".to_string(), global.department_compactness),
+            ("weights.adjacency".to_string(), global.adjacency),
+            ("weights.rightsizing".to_string(), global.rightsizing),
+            ("weights.homeRoom".to_string(), global.home_room),
+            ("weights.optionalCourse".to_string(), global.optional_course),
+            ("weights.compactSchedule".to_string(), global.compact_schedule),
+            ("weights.isolationWeight".to_string(), global.isolation_weight),
+            ("weights.roomClusteringWeight".to_string(), global.room_clustering_weight),
+            ("weights.idleTimeWeight".to_string(), global.idle_time_weight),
+            ("weights.cohortRoomChangesWeight".to_string(), global.cohort_room_changes_weight),
+            (
+                "weights.minimizeInstructorsWeight".to_string(),
+                global.minimize_instructors_weight,
+            ),
+            ("weights.longCourseMorningWeight".to_string(), global.long_course_morning_weight),
+            ("weights.singleCourseDay".to_string(), global.single_course_day),
+        ]);
+    }
+    for course in &input.courses {
+        weights.push((format!("course {} targetWeight", course.id), course.target_weight));
+    }
+    for (name, penalty) in relaxations {
+        weights.extend(penalty.map(|penalty| (format!("relaxations.{}", name), penalty)));
+    }
+    for instructor in &input.instructors {
+        weights.push((
+            format!("instructor {} backToBackWeight", instructor.id),
+            instructor.back_to_back_weight,
+        ));
+        if let Some(cost) = instructor.override_cost {
+            weights.push((format!("instructor {} overrideCost", instructor.id), cost));
+        }
+    }
+    for room in &input.rooms {
+        weights.push((format!("room {} reservePreference", room.id), room.reserve_preference));
+    }
+    for (day, weight) in input.day_weights.iter().flatten().enumerate() {
+        weights.push((format!("dayWeights[{}]", day), *weight));
+    }
+    for (index, (_, _, weight)) in input.day_soft_caps.iter().enumerate() {
+        weights.push((format!("daySoftCaps[{}] weight", index), *weight));
+    }
+    for (field, value) in weights {
+        if !value.is_finite() || value.abs() > MAX_WEIGHT {
+            errors.push(format!(
+                "{} is {:e}; weights must be finite and between -{:e} and {:e}.",
+                field, value, MAX_WEIGHT, MAX_WEIGHT
+            ));
+        }
+    }
+
+    ValidationReport {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+/// Ids that occur more than once, in ascending order.
+fn duplicates<T: Ord>(ids: impl Iterator<Item = T>) -> Vec<T> {
+    let mut counts = BTreeMap::new();
+    for id in ids {
+        *counts.entry(id).or_insert(0) += 1;
+    }
+    counts.into_iter().filter(|(_, count)| *count > 1).map(|(id, _)| id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::SolverWeights;
+    use crate::solver::{self, SolveError};
+    use serde_json::json;
+
+    /// One room, one instructor and one 2-slot course over a single 4-period day; `extra`
+    /// overrides or adds top-level fields.
+    fn input(extra: serde_json::Value) -> SchedulingInput {
+        let mut value = json!({
+            "rooms": [{"id": 1, "capacity": 30}],
+            "instructors": [{"id": 1, "unavailableSlots": []}],
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 2, "instructorIds": [1]
+            }],
+            "totalTimeslots": 4
+        });
+        for (key, field) in extra.as_object().unwrap() {
+            value[key] = field.clone();
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn frozen_assignments_past_the_horizon_are_rejected() {
+        let placement = |start_slot: u32| {
+            input(json!({
+                "frozenSlots": [0, 1],
+                "frozenAssignments": [{"courseId": 1, "roomId": 1, "startSlot": start_slot}]
+            }))
+        };
+        assert!(validate_input(&placement(0)).valid);
+        for start_slot in [3, u32::MAX] {
+            let input = placement(start_slot);
+            assert!(!validate_input(&input).valid);
+            assert!(input.frozen_placements().is_empty());
+            assert!(matches!(
+                crate::solver::solve(&input),
+                Err(crate::solver::SolveError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn a_single_timeslot_warns_that_the_morning_preference_is_moot() {
+        let report = validate_input(&input(json!({
+            "courses": [{
+                "id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+            }],
+            "totalTimeslots": 1
+        })));
+        assert!(report.valid);
+        assert!(report.warnings.iter().any(|w| w.contains("morning preference has no effect")));
+    }
+
+    #[test]
+    fn ninety_minutes_in_hour_slots_round_up_to_two_slots_with_a_warning() {
+        let ninety_minutes = |slot_minutes: Option<u32>| {
+            input(json!({
+                "courses": [{
+                    "id": 1, "requiredCapacity": 10, "durationMinutes": 90, "instructorIds": [1]
+                }],
+                "slotMinutes": slot_minutes
+            }))
+        };
+        let input = ninety_minutes(Some(60));
+        assert_eq!(input.with_durations_in_slots().courses[0].duration_slots, 2);
+        let report = validate_input(&input);
+        assert!(report.valid, "{:?}", report.errors);
+        assert!(report.warnings.iter().any(|w| w.contains("rounded up to 2 slots")));
+
+        let report = validate_input(&ninety_minutes(Some(30)));
+        assert!(report.valid && report.warnings.iter().all(|w| !w.contains("rounded up")));
+        assert!(!validate_input(&ninety_minutes(None)).valid);
+    }
+
+    #[test]
+    fn nan_and_infinite_weights_are_rejected_by_name() {
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, MAX_WEIGHT * 2.0] {
+            let mut global = input(json!({}));
+            global.weights = Some(SolverWeights {
+                back_to_back_penalty: bad,
+                ..SolverWeights::default()
+            });
+            let mut per_instructor = input(json!({}));
+            per_instructor.instructors[0].back_to_back_weight = bad;
+            let mut per_course = input(json!({}));
+            per_course.courses[0].target_weight = bad;
+
+            for (input, field) in [
+                (global, "weights.backToBackPenalty"),
+                (per_instructor, "instructor 1 backToBackWeight"),
+                (per_course, "course 1 targetWeight"),
+            ] {
+                let report = validate_input(&input);
+                assert!(!report.valid);
+                assert!(report.errors.iter().any(|e| e.starts_with(field)), "{:?}", report.errors);
+                match crate::solver::solve(&input) {
+                    Err(crate::solver::SolveError::InvalidInput(message)) => {
+                        assert!(message.contains(field), "{}", message)
+                    }
+                    other => panic!("expected invalid input, got {:?}", other),
+                }
+            }
+        }
+        assert!(validate_input(&input(json!({"weights": {"backToBackPenalty": -5.0}}))).valid);
+    }
+
+    /// The error `solve` reports for `input`, which must be invalid.
+    fn invalid_input(input: &SchedulingInput) -> String {
+        match solver::solve(input) {
+            Err(SolveError::InvalidInput(message)) => message,
+            other => panic!("expected an invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn a_duplicate_course_id_is_named() {
+        let course = json!({
+            "id": 7, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]
+        });
+        let input = input(json!({"courses": [course.clone(), course]}));
+        assert_eq!(invalid_input(&input), "Course id 7 is used more than once.");
+    }
+
+    #[test]
+    fn a_duplicate_room_id_is_named() {
+        let room = json!({"id": 3, "capacity": 30});
+        let input = input(json!({"rooms": [room.clone(), room]}));
+        assert_eq!(invalid_input(&input), "Room id 3 is used more than once.");
+    }
+
+    #[test]
+    fn a_duplicate_instructor_id_is_named() {
+        let instructor = json!({"id": 1, "unavailableSlots": []});
+        let input = input(json!({"instructors": [instructor.clone(), instructor]}));
+        assert_eq!(invalid_input(&input), "Instructor id 1 is used more than once.");
+    }
+
+    #[test]
+    fn demand_beyond_the_room_slot_supply_warns_or_fails() {
+        let course = |id: u32, optional: bool| {
+            json!({
+                "id": id, "requiredCapacity": 10, "durationSlots": 2, "instructorIds": [1],
+                "optional": optional
+            })
+        };
+        let message = "The mandatory courses need 6 room-slot(s), but 1 room(s) over 4 \
+            timeslot(s) only offer 4.";
+        let report = validate_input(&input(json!({
+            "courses": [course(1, false), course(2, false), course(3, false)]
+        })));
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.warnings.iter().any(|w| w == message), "{:?}", report.warnings);
+
+        let report = validate_input(&input(json!({
+            "courses": [course(1, false), course(2, false), course(3, false)],
+            "rejectOversubscribed": true
+        })));
+        assert!(report.errors.iter().any(|e| e == message), "{:?}", report.errors);
+
+        // optional courses may be dropped, so they don't count
+        let report = validate_input(&input(json!({
+            "courses": [course(1, false), course(2, false), course(3, true)]
+        })));
+        assert!(report.warnings.iter().all(|w| !w.contains("room-slot")), "{:?}", report.warnings);
+    }
+}
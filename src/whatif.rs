@@ -0,0 +1,175 @@
+use crate::calendar;
+use crate::data::{
+    self, InstructorId, Room, RoomId, ScheduleDiff, SchedulingInput, SchedulingOutput, Timeslot,
+};
+use crate::solver::{self, SolveError};
+use serde::{Deserialize, Serialize};
+
+/// One change to try on an input.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum InputPatch {
+    /// Block a room in one more timeslot.
+    BlockRoomSlot { room_id: RoomId, slot: Timeslot },
+    /// Lift a one-off block of a room; recurring blocks cannot be lifted for a single slot.
+    UnblockRoomSlot { room_id: RoomId, slot: Timeslot },
+    /// Make an instructor unavailable in one more timeslot.
+    BlockInstructorSlot {
+        instructor_id: InstructorId,
+        slot: Timeslot,
+    },
+    /// Make an instructor available in a timeslot they were unavailable in.
+    UnblockInstructorSlot {
+        instructor_id: InstructorId,
+        slot: Timeslot,
+    },
+    AddRoom { room: Room },
+    SetRoomCapacity { room_id: RoomId, capacity: u32 },
+}
+
+/// A base input and the change to compare it against.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhatIfRequest {
+    pub input: SchedulingInput,
+    pub patch: InputPatch,
+}
+
+/// The schedule of the patched input and how it differs from the base schedule.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhatIfResult {
+    pub solution: SchedulingOutput,
+    pub diff: ScheduleDiff,
+    /// Score of the patched schedule minus the score of the base schedule.
+    pub score_change: f64,
+}
+
+/// Copy of `input` with `patch` applied. Date ranges are turned into slots first, so
+/// unblocking a slot also lifts a date range that covers it.
+pub fn apply_patch(
+    input: &SchedulingInput,
+    patch: &InputPatch,
+) -> Result<SchedulingInput, SolveError> {
+    let mut input = calendar::with_dates_as_slots(input);
+    let unknown_room = |room_id: &RoomId| {
+        SolveError::InvalidInput(format!("The patch refers to unknown room {}.", room_id))
+    };
+    let unknown_instructor = |instructor_id: &InstructorId| {
+        SolveError::InvalidInput(format!(
+            "The patch refers to unknown instructor {}.",
+            instructor_id
+        ))
+    };
+    match patch {
+        InputPatch::BlockRoomSlot { room_id, slot } => {
+            let room = input.rooms.iter_mut().find(|r| r.id == *room_id);
+            let room = room.ok_or_else(|| unknown_room(room_id))?;
+            if !room.unavailable_slots.contains(slot) {
+                room.unavailable_slots.push(*slot);
+            }
+        }
+        InputPatch::UnblockRoomSlot { room_id, slot } => {
+            let index = input.rooms.iter().position(|r| r.id == *room_id);
+            let index = index.ok_or_else(|| unknown_room(room_id))?;
+            let recurring = input.rooms[index]
+                .recurring_unavailable
+                .iter()
+                .flat_map(|(day, period)| input.weekly_slots(*day, *period))
+                .any(|k| k == *slot);
+            if recurring {
+                return Err(SolveError::InvalidInput(format!(
+                    "Room {} is blocked in slot {} by a recurring block, which a patch cannot lift for one slot.",
+                    room_id, slot
+                )));
+            }
+            input.rooms[index].unavailable_slots.retain(|k| k != slot);
+        }
+        InputPatch::BlockInstructorSlot {
+            instructor_id,
+            slot,
+        } => {
+            let instructor = input.instructors.iter_mut().find(|i| i.id == *instructor_id);
+            let instructor = instructor.ok_or_else(|| unknown_instructor(instructor_id))?;
+            if !instructor.unavailable_slots.contains(slot) {
+                instructor.unavailable_slots.push(*slot);
+            }
+        }
+        InputPatch::UnblockInstructorSlot {
+            instructor_id,
+            slot,
+        } => {
+            let instructor = input.instructors.iter_mut().find(|i| i.id == *instructor_id);
+            let instructor = instructor.ok_or_else(|| unknown_instructor(instructor_id))?;
+            instructor.unavailable_slots.retain(|k| k != slot);
+        }
+        InputPatch::AddRoom { room } => {
+            if input.rooms.iter().any(|r| r.id == room.id) {
+                return Err(SolveError::InvalidInput(format!(
+                    "The patch adds room {}, which already exists.",
+                    room.id
+                )));
+            }
+            input.rooms.push(room.clone());
+        }
+        InputPatch::SetRoomCapacity { room_id, capacity } => {
+            let room = input.rooms.iter_mut().find(|r| r.id == *room_id);
+            room.ok_or_else(|| unknown_room(room_id))?.capacity = *capacity;
+        }
+    }
+    Ok(input)
+}
+
+/// Solves `input` as given and with `patch` applied, and compares the two schedules.
+///
+/// Both solves are independent, so courses the patch does not touch may still move when the
+/// solver finds an equally good schedule elsewhere.
+pub fn what_if(input: &SchedulingInput, patch: &InputPatch) -> Result<WhatIfResult, SolveError> {
+    let patched = apply_patch(input, patch)?;
+    let base = solver::solve(input)?;
+    let solution = solver::solve(&patched)?;
+    Ok(WhatIfResult {
+        diff: data::diff_schedules(&base.assignments, &solution.assignments),
+        score_change: solution.score - base.score,
+        solution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CourseId;
+    use serde_json::json;
+
+    #[test]
+    fn a_larger_room_fits_a_course_that_was_left_out() {
+        let request: WhatIfRequest = serde_json::from_value(json!({
+            "input": {
+                "rooms": [{"id": 1, "capacity": 30}],
+                "instructors": [{"id": 1, "unavailableSlots": []}],
+                "courses": [
+                    {"id": 1, "requiredCapacity": 10, "durationSlots": 1, "instructorIds": [1]},
+                    {
+                        "id": 2, "requiredCapacity": 40, "durationSlots": 1, "instructorIds": [1],
+                        "optional": true
+                    }
+                ],
+                "totalTimeslots": 4
+            },
+            "patch": {"op": "setRoomCapacity", "roomId": 1, "capacity": 50}
+        }))
+        .unwrap();
+        let result = what_if(&request.input, &request.patch).unwrap();
+        let added: Vec<_> = result.diff.added.iter().map(|a| a.course_id).collect();
+        assert_eq!(added, [CourseId(2)]);
+        assert!(result.diff.removed.is_empty());
+        assert_eq!(result.solution.assignments.len(), 2);
+        assert_eq!(request.input.rooms[0].capacity, 30);
+
+        let unknown = InputPatch::SetRoomCapacity {
+            room_id: RoomId(9),
+            capacity: 50,
+        };
+        assert!(matches!(what_if(&request.input, &unknown), Err(SolveError::InvalidInput(_))));
+    }
+}